@@ -0,0 +1,86 @@
+-- BMP: file header, DIB header (core, info or V4/V5), palette and pixel data
+local COMPRESSION = {
+    [0] = "BI_RGB",
+    [1] = "BI_RLE8",
+    [2] = "BI_RLE4",
+    [3] = "BI_BITFIELDS",
+    [4] = "BI_JPEG",
+    [5] = "BI_PNG",
+    [6] = "BI_ALPHABITFIELDS",
+}
+local HEADERS = {
+    [12] = "BITMAPCOREHEADER",
+    [40] = "BITMAPINFOHEADER",
+    [52] = "BITMAPV2INFOHEADER",
+    [56] = "BITMAPV3INFOHEADER",
+    [108] = "BITMAPV4HEADER",
+    [124] = "BITMAPV5HEADER",
+}
+
+return {
+    name = "BMP",
+    magic = "BM",
+    parse = function()
+        local pixels
+        group("file header", 0, 14, function()
+            field("signature", "str", 0, 2)
+            field("file size", "u32le", 2)
+            field("reserved 1", "u16le", 6)
+            field("reserved 2", "u16le", 8)
+            pixels = field("pixel data offset", "u32le", 10)
+        end)
+        local dib = read("u32le", 14)
+        local bpp, image_size = 0, 0
+        group("info header", 14, dib, function()
+            field("header size", "u32le", 14, nil, HEADERS)
+            if dib == 12 then
+                field("width", "u16le", 18)
+                field("height", "u16le", 20)
+                field("planes", "u16le", 22)
+                bpp = field("bits per pixel", "u16le", 24)
+                return
+            end
+            field("width", "i32le", 18)
+            field("height", "i32le", 22)
+            field("planes", "u16le", 26)
+            bpp = field("bits per pixel", "u16le", 28)
+            field("compression", "u32le", 30, nil, COMPRESSION)
+            image_size = field("image size", "u32le", 34)
+            field("x pixels per meter", "i32le", 38)
+            field("y pixels per meter", "i32le", 42)
+            field("colors used", "u32le", 46)
+            field("important colors", "u32le", 50)
+            if dib >= 52 then
+                field("red mask", "u32le", 54)
+                field("green mask", "u32le", 58)
+                field("blue mask", "u32le", 62)
+            end
+            if dib >= 56 then
+                field("alpha mask", "u32le", 66)
+            end
+            if dib >= 108 then
+                field("color space", "str", 70, 4)
+                field("endpoints", "bytes", 74, 36)
+                field("gamma red", "u32le", 110)
+                field("gamma green", "u32le", 114)
+                field("gamma blue", "u32le", 118)
+            end
+            if dib >= 124 then
+                field("intent", "u32le", 122)
+                field("profile data", "u32le", 126)
+                field("profile size", "u32le", 130)
+                field("reserved", "u32le", 134)
+            end
+        end)
+        local palette = 14 + dib
+        if pixels > palette then
+            field(bpp <= 8 and "palette" or "gap", "bytes", palette, pixels - palette)
+        end
+        if image_size == 0 or pixels + image_size > size then
+            image_size = math.max(size - pixels, 0)
+        end
+        if image_size > 0 then
+            field("pixel data", "bytes", pixels, image_size)
+        end
+    end,
+}
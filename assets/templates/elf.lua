@@ -0,0 +1,152 @@
+-- ELF: identification, file header, program headers and section headers, 32 or 64 bit, either byte order
+local CLASSES = { [1] = "ELF32", [2] = "ELF64" }
+local DATA = { [1] = "Little endian", [2] = "Big endian" }
+local OSABI = { [0] = "System V", [3] = "Linux", [6] = "Solaris", [9] = "FreeBSD", [12] = "OpenBSD" }
+local TYPES = { [0] = "NONE", [1] = "REL", [2] = "EXEC", [3] = "DYN", [4] = "CORE" }
+local MACHINES = {
+    [3] = "x86",
+    [8] = "MIPS",
+    [20] = "PowerPC",
+    [21] = "PowerPC64",
+    [40] = "ARM",
+    [42] = "SuperH",
+    [50] = "IA-64",
+    [62] = "x86-64",
+    [183] = "AArch64",
+    [243] = "RISC-V",
+    [258] = "LoongArch",
+}
+local SEGMENTS = {
+    [0] = "NULL",
+    [1] = "LOAD",
+    [2] = "DYNAMIC",
+    [3] = "INTERP",
+    [4] = "NOTE",
+    [5] = "SHLIB",
+    [6] = "PHDR",
+    [7] = "TLS",
+    [0x6474e550] = "GNU_EH_FRAME",
+    [0x6474e551] = "GNU_STACK",
+    [0x6474e552] = "GNU_RELRO",
+    [0x6474e553] = "GNU_PROPERTY",
+}
+local SECTIONS = {
+    [0] = "NULL",
+    [1] = "PROGBITS",
+    [2] = "SYMTAB",
+    [3] = "STRTAB",
+    [4] = "RELA",
+    [5] = "HASH",
+    [6] = "DYNAMIC",
+    [7] = "NOTE",
+    [8] = "NOBITS",
+    [9] = "REL",
+    [11] = "DYNSYM",
+    [14] = "INIT_ARRAY",
+    [15] = "FINI_ARRAY",
+    [0x6ffffff6] = "GNU_HASH",
+}
+
+-- reads consecutive fields, returning their values by name
+local function struct(off, list)
+    local values = {}
+    for _, f in ipairs(list) do
+        values[f[1]] = field(f[1], f[2], off, nil, f[3])
+        off = off + sizeof(f[2])
+    end
+    return values
+end
+
+return {
+    name = "ELF",
+    magic = "\127ELF",
+    parse = function()
+        local class = read("u8", 4)
+        local e = read("u8", 5) == 2 and "be" or "le"
+        local half, word = "u16" .. e, "u32" .. e
+        local addr = class == 2 and "u64" .. e or "u32" .. e
+        group("identification", 0, 16, function()
+            field("magic", "bytes", 0, 4)
+            field("class", "u8", 4, nil, CLASSES)
+            field("data", "u8", 5, nil, DATA)
+            field("version", "u8", 6)
+            field("os abi", "u8", 7, nil, OSABI)
+            field("abi version", "u8", 8)
+            field("padding", "bytes", 9, 7)
+        end)
+        local h
+        group("file header", 16, nil, function()
+            h = struct(16, {
+                { "type", half, TYPES },
+                { "machine", half, MACHINES },
+                { "version", word },
+                { "entry", addr },
+                { "phoff", addr },
+                { "shoff", addr },
+                { "flags", word },
+                { "ehsize", half },
+                { "phentsize", half },
+                { "phnum", half },
+                { "shentsize", half },
+                { "shnum", half },
+                { "shstrndx", half },
+            })
+        end)
+        local segment = class == 2
+                and {
+                    { "type", word, SEGMENTS },
+                    { "flags", word },
+                    { "offset", addr },
+                    { "vaddr", addr },
+                    { "paddr", addr },
+                    { "filesz", addr },
+                    { "memsz", addr },
+                    { "align", addr },
+                }
+            or {
+                { "type", word, SEGMENTS },
+                { "offset", addr },
+                { "vaddr", addr },
+                { "paddr", addr },
+                { "filesz", addr },
+                { "memsz", addr },
+                { "flags", word },
+                { "align", addr },
+            }
+        if h.phnum > 0 then
+            group("program headers", h.phoff, h.phnum * h.phentsize, function()
+                for i = 0, h.phnum - 1 do
+                    local off = h.phoff + i * h.phentsize
+                    local kind = SEGMENTS[read(word, off)] or "segment"
+                    group(i .. " " .. kind, off, h.phentsize, function()
+                        struct(off, segment)
+                    end)
+                end
+            end)
+        end
+        if h.shnum > 0 then
+            local strtab = h.shstrndx < h.shnum
+                and read(addr, h.shoff + h.shstrndx * h.shentsize + (class == 2 and 24 or 16))
+            group("section headers", h.shoff, h.shnum * h.shentsize, function()
+                for i = 0, h.shnum - 1 do
+                    local off = h.shoff + i * h.shentsize
+                    local name = strtab and read("cstr", strtab + read(word, off), nil) or ""
+                    group(i .. " " .. name, off, h.shentsize, function()
+                        struct(off, {
+                            { "name", word },
+                            { "type", word, SECTIONS },
+                            { "flags", addr },
+                            { "addr", addr },
+                            { "offset", addr },
+                            { "size", addr },
+                            { "link", word },
+                            { "info", word },
+                            { "addralign", addr },
+                            { "entsize", addr },
+                        })
+                    end)
+                end
+            end)
+        end
+    end,
+}
@@ -0,0 +1,169 @@
+-- PE/COFF: DOS header, PE signature, COFF header, optional header with data directories and section table
+local MACHINES = {
+    [0x014c] = "i386",
+    [0x01c0] = "ARM",
+    [0x01c4] = "ARMv7 Thumb",
+    [0x0200] = "IA-64",
+    [0x5064] = "RISC-V 64",
+    [0x8664] = "AMD64",
+    [0xaa64] = "ARM64",
+}
+local MAGICS = { [0x10b] = "PE32", [0x20b] = "PE32+", [0x107] = "ROM" }
+local SUBSYSTEMS = {
+    [1] = "Native",
+    [2] = "Windows GUI",
+    [3] = "Windows CUI",
+    [9] = "Windows CE GUI",
+    [10] = "EFI Application",
+    [11] = "EFI Boot Service Driver",
+    [12] = "EFI Runtime Driver",
+    [14] = "Xbox",
+    [16] = "Windows Boot Application",
+}
+local DIRECTORIES = {
+    "Export",
+    "Import",
+    "Resource",
+    "Exception",
+    "Certificate",
+    "Base Relocation",
+    "Debug",
+    "Architecture",
+    "Global Ptr",
+    "TLS",
+    "Load Config",
+    "Bound Import",
+    "IAT",
+    "Delay Import",
+    "CLR Runtime",
+    "Reserved",
+}
+
+local function struct(off, list)
+    local values = {}
+    for _, f in ipairs(list) do
+        values[f[1]] = field(f[1], f[2], off, nil, f[3])
+        off = off + sizeof(f[2])
+    end
+    return values, off
+end
+
+return {
+    name = "PE/COFF",
+    magic = "MZ",
+    parse = function()
+        local pe = read("u32le", 0x3c)
+        group("DOS header", 0, 0x40, function()
+            field("magic", "str", 0, 2)
+            field("bytes on last page", "u16le", 2)
+            field("pages", "u16le", 4)
+            field("relocations", "u16le", 6)
+            field("header paragraphs", "u16le", 8)
+            field("initial ss", "u16le", 0x0e)
+            field("initial sp", "u16le", 0x10)
+            field("initial ip", "u16le", 0x14)
+            field("initial cs", "u16le", 0x16)
+            field("pe header offset", "u32le", 0x3c)
+        end)
+        if pe > 0x40 then
+            field("DOS stub", "bytes", 0x40, pe - 0x40)
+        end
+        if read("str", pe, 4) ~= "PE\0\0" then
+            error(string.format("no PE signature at 0x%X", pe))
+        end
+        field("signature", "str", pe, 4)
+        local coff
+        group("COFF header", pe + 4, 20, function()
+            coff = struct(pe + 4, {
+                { "machine", "u16le", MACHINES },
+                { "number of sections", "u16le" },
+                { "time date stamp", "u32le" },
+                { "pointer to symbol table", "u32le" },
+                { "number of symbols", "u32le" },
+                { "size of optional header", "u16le" },
+                { "characteristics", "u16le" },
+            })
+        end)
+        local opt = pe + 24
+        if coff["size of optional header"] > 0 then
+            local plus = read("u16le", opt) == 0x20b
+            local addr = plus and "u64le" or "u32le"
+            local list = {
+                { "magic", "u16le", MAGICS },
+                { "major linker version", "u8" },
+                { "minor linker version", "u8" },
+                { "size of code", "u32le" },
+                { "size of initialized data", "u32le" },
+                { "size of uninitialized data", "u32le" },
+                { "address of entry point", "u32le" },
+                { "base of code", "u32le" },
+            }
+            if not plus then
+                table.insert(list, { "base of data", "u32le" })
+            end
+            for _, f in ipairs({
+                { "image base", addr },
+                { "section alignment", "u32le" },
+                { "file alignment", "u32le" },
+                { "major os version", "u16le" },
+                { "minor os version", "u16le" },
+                { "major image version", "u16le" },
+                { "minor image version", "u16le" },
+                { "major subsystem version", "u16le" },
+                { "minor subsystem version", "u16le" },
+                { "win32 version value", "u32le" },
+                { "size of image", "u32le" },
+                { "size of headers", "u32le" },
+                { "checksum", "u32le" },
+                { "subsystem", "u16le", SUBSYSTEMS },
+                { "dll characteristics", "u16le" },
+                { "size of stack reserve", addr },
+                { "size of stack commit", addr },
+                { "size of heap reserve", addr },
+                { "size of heap commit", addr },
+                { "loader flags", "u32le" },
+                { "number of rva and sizes", "u32le" },
+            }) do
+                table.insert(list, f)
+            end
+            group("optional header", opt, coff["size of optional header"], function()
+                local values, off = struct(opt, list)
+                local count = math.min(values["number of rva and sizes"], #DIRECTORIES)
+                if count > 0 then
+                    group("data directories", off, count * 8, function()
+                        for i = 1, count do
+                            group(DIRECTORIES[i], off, 8, function()
+                                field("virtual address", "u32le", off)
+                                field("size", "u32le", off + 4)
+                            end)
+                            off = off + 8
+                        end
+                    end)
+                end
+            end)
+        end
+        local sections = opt + coff["size of optional header"]
+        local count = coff["number of sections"]
+        if count > 0 then
+            group("section table", sections, count * 40, function()
+                for i = 0, count - 1 do
+                    local off = sections + i * 40
+                    group(read("cstr", off, 8), off, 40, function()
+                        field("name", "str", off, 8)
+                        struct(off + 8, {
+                            { "virtual size", "u32le" },
+                            { "virtual address", "u32le" },
+                            { "size of raw data", "u32le" },
+                            { "pointer to raw data", "u32le" },
+                            { "pointer to relocations", "u32le" },
+                            { "pointer to line numbers", "u32le" },
+                            { "number of relocations", "u16le" },
+                            { "number of line numbers", "u16le" },
+                            { "characteristics", "u32le" },
+                        })
+                    end)
+                end
+            end)
+        end
+    end,
+}
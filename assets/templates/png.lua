@@ -0,0 +1,58 @@
+-- PNG: signature followed by length/type/data/CRC chunks, walked by the PNG tool's chunk parser
+local COLOR_TYPES = {
+    [0] = "Grayscale",
+    [2] = "RGB",
+    [3] = "Indexed",
+    [4] = "Grayscale + Alpha",
+    [6] = "RGBA",
+}
+local UNITS = { [0] = "Unknown", [1] = "Meter" }
+
+local function chunk_body(c, body)
+    if c.type == "IHDR" then
+        field("width", "u32be", body)
+        field("height", "u32be", body + 4)
+        field("bit depth", "u8", body + 8)
+        field("color type", "u8", body + 9, nil, COLOR_TYPES)
+        field("compression", "u8", body + 10)
+        field("filter", "u8", body + 11)
+        field("interlace", "u8", body + 12, nil, { [0] = "None", [1] = "Adam7" })
+    elseif c.type == "pHYs" then
+        field("pixels per unit x", "u32be", body)
+        field("pixels per unit y", "u32be", body + 4)
+        field("unit", "u8", body + 8, nil, UNITS)
+    elseif c.type == "gAMA" then
+        field("gamma x 100000", "u32be", body)
+    elseif c.type == "tIME" then
+        field("year", "u16be", body)
+        field("month", "u8", body + 2)
+        field("day", "u8", body + 3)
+        field("hour", "u8", body + 4)
+        field("minute", "u8", body + 5)
+        field("second", "u8", body + 6)
+    elseif c.type == "tEXt" then
+        local key = field("keyword", "cstr", body, c.length)
+        local used = #key + 1
+        if used < c.length then
+            field("text", "str", body + used, c.length - used)
+        end
+    elseif c.length > 0 then
+        field("data", "bytes", body, c.length)
+    end
+end
+
+return {
+    name = "PNG",
+    magic = "\137PNG\r\n\26\n",
+    parse = function()
+        field("signature", "bytes", 0, 8)
+        for _, c in ipairs(png_chunks()) do
+            group(c.type, c.offset, c.length + 12, function()
+                field("length", "u32be", c.offset)
+                field("type", "str", c.offset + 4, 4)
+                chunk_body(c, c.offset + 8)
+                field("crc", "u32be", c.offset + 8 + c.length)
+            end)
+        end
+    end,
+}
@@ -0,0 +1,78 @@
+-- WAV: RIFF header followed by chunks, with fmt and LIST INFO decoded
+local FORMATS = {
+    [1] = "PCM",
+    [2] = "Microsoft ADPCM",
+    [3] = "IEEE float",
+    [6] = "A-law",
+    [7] = "mu-law",
+    [0x11] = "IMA ADPCM",
+    [0x55] = "MPEG Layer 3",
+    [0xFFFE] = "Extensible",
+}
+
+local function fmt(body, length)
+    local format = field("audio format", "u16le", body, nil, FORMATS)
+    field("channels", "u16le", body + 2)
+    field("sample rate", "u32le", body + 4)
+    field("byte rate", "u32le", body + 8)
+    field("block align", "u16le", body + 12)
+    field("bits per sample", "u16le", body + 14)
+    if length >= 18 then
+        local extra = field("extension size", "u16le", body + 16)
+        if format == 0xFFFE and extra >= 22 then
+            field("valid bits per sample", "u16le", body + 18)
+            field("channel mask", "u32le", body + 20)
+            field("sub format", "bytes", body + 24, 16)
+        elseif extra > 0 then
+            field("extension", "bytes", body + 18, extra)
+        end
+    end
+end
+
+local function list(body, length)
+    field("list type", "str", body, 4)
+    local off, stop = body + 4, body + length
+    while off + 8 <= stop do
+        local id = read("str", off, 4)
+        local len = read("u32le", off + 4)
+        group(id, off, 8 + len, function()
+            field("id", "str", off, 4)
+            field("size", "u32le", off + 4)
+            if len > 0 then
+                field("text", "cstr", off + 8, len)
+            end
+        end)
+        off = off + 8 + len + len % 2
+    end
+end
+
+return {
+    name = "WAV",
+    magic = "RIFF",
+    parse = function()
+        group("RIFF header", 0, 12, function()
+            field("id", "str", 0, 4)
+            field("size", "u32le", 4)
+            field("format", "str", 8, 4)
+        end)
+        local off = 12
+        while off + 8 <= size do
+            local id = read("str", off, 4)
+            local length = read("u32le", off + 4)
+            group(id, off, 8 + length, function()
+                field("id", "str", off, 4)
+                field("size", "u32le", off + 4)
+                local body = off + 8
+                if id == "fmt " then
+                    fmt(body, length)
+                elseif id == "LIST" then
+                    list(body, length)
+                elseif length > 0 then
+                    field("data", "bytes", body, math.min(length, size - body))
+                end
+            end)
+            -- chunks are padded to an even length
+            off = off + 8 + length + length % 2
+        end
+    end,
+}
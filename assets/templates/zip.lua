@@ -0,0 +1,115 @@
+-- ZIP: located from the end of central directory record, then each central and local header
+local METHODS = {
+    [0] = "Stored",
+    [8] = "Deflate",
+    [9] = "Deflate64",
+    [12] = "BZIP2",
+    [14] = "LZMA",
+    [93] = "Zstandard",
+    [95] = "XZ",
+    [99] = "AES",
+}
+
+local function local_header(off)
+    local name = read("str", off + 30, read("u16le", off + 26))
+    group("local file: " .. name, off, nil, function()
+        field("signature", "u32le", off)
+        field("version needed", "u16le", off + 4)
+        field("flags", "u16le", off + 6)
+        field("compression", "u16le", off + 8, nil, METHODS)
+        field("mod time", "u16le", off + 10)
+        field("mod date", "u16le", off + 12)
+        field("crc32", "u32le", off + 14)
+        local csize = field("compressed size", "u32le", off + 18)
+        field("uncompressed size", "u32le", off + 22)
+        local nlen = field("name length", "u16le", off + 26)
+        local xlen = field("extra length", "u16le", off + 28)
+        field("name", "str", off + 30, nlen)
+        if xlen > 0 then
+            field("extra", "bytes", off + 30 + nlen, xlen)
+        end
+        if csize > 0 then
+            field("data", "bytes", off + 30 + nlen + xlen, csize)
+        end
+    end)
+end
+
+local function central_header(off)
+    local nlen = read("u16le", off + 28)
+    local xlen = read("u16le", off + 30)
+    local clen = read("u16le", off + 32)
+    local name = read("str", off + 46, nlen)
+    group("central directory: " .. name, off, 46 + nlen + xlen + clen, function()
+        field("signature", "u32le", off)
+        field("version made by", "u16le", off + 4)
+        field("version needed", "u16le", off + 6)
+        field("flags", "u16le", off + 8)
+        field("compression", "u16le", off + 10, nil, METHODS)
+        field("mod time", "u16le", off + 12)
+        field("mod date", "u16le", off + 14)
+        field("crc32", "u32le", off + 16)
+        field("compressed size", "u32le", off + 20)
+        field("uncompressed size", "u32le", off + 24)
+        field("name length", "u16le", off + 28)
+        field("extra length", "u16le", off + 30)
+        field("comment length", "u16le", off + 32)
+        field("disk number start", "u16le", off + 34)
+        field("internal attributes", "u16le", off + 36)
+        field("external attributes", "u32le", off + 38)
+        field("local header offset", "u32le", off + 42)
+        field("name", "str", off + 46, nlen)
+        if xlen > 0 then
+            field("extra", "bytes", off + 46 + nlen, xlen)
+        end
+        if clen > 0 then
+            field("comment", "str", off + 46 + nlen + xlen, clen)
+        end
+    end)
+    return off + 46 + nlen + xlen + clen
+end
+
+return {
+    name = "ZIP",
+    magic = "PK\3\4",
+    parse = function()
+        local eocd = rfind("PK\5\6")
+        if not eocd then
+            error("end of central directory record not found")
+        end
+        local count = read("u16le", eocd + 10)
+        local cd_offset = read("u32le", eocd + 16)
+        if cd_offset == 0xFFFFFFFF then
+            error("ZIP64 archives are not supported")
+        end
+        -- collect local headers first so the tree follows file order
+        local locals = {}
+        local off = cd_offset
+        for _ = 1, count do
+            table.insert(locals, read("u32le", off + 42))
+            off = off + 46 + read("u16le", off + 28) + read("u16le", off + 30) + read("u16le", off + 32)
+        end
+        table.sort(locals)
+        for _, local_offset in ipairs(locals) do
+            local_header(local_offset)
+        end
+        group("central directory", cd_offset, nil, function()
+            local entry = cd_offset
+            for _ = 1, count do
+                entry = central_header(entry)
+            end
+        end)
+        group("end of central directory", eocd, nil, function()
+            field("signature", "u32le", eocd)
+            field("disk number", "u16le", eocd + 4)
+            field("central directory disk", "u16le", eocd + 6)
+            field("entries on disk", "u16le", eocd + 8)
+            field("total entries", "u16le", eocd + 10)
+            field("central directory size", "u32le", eocd + 12)
+            field("central directory offset", "u32le", eocd + 16)
+            local clen = field("comment length", "u16le", eocd + 20)
+            if clen > 0 then
+                field("comment", "str", eocd + 22, clen)
+            end
+        end)
+    end,
+}
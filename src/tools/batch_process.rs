@@ -196,27 +196,9 @@ impl BatchProcess {
             Err(errors.join("\n"))
         }
     }
-    fn scan_dir(&mut self, dir: &std::path::PathBuf) -> Result<(), String> {
+    fn scan_dir(&mut self, dir: &std::path::Path) -> Result<(), String> {
         let mut files = Vec::new();
-        if self.recusive {
-            for entry in std::fs::read_dir(dir).map_err(|e| e.to_string())? {
-                let entry = entry.map_err(|e| e.to_string())?;
-                let path = entry.path();
-                if path.is_dir() {
-                    self.scan_dir(&path)?;
-                } else if path.is_file() {
-                    files.push(path);
-                }
-            }
-        } else {
-            for entry in std::fs::read_dir(dir).map_err(|e| e.to_string())? {
-                let entry = entry.map_err(|e| e.to_string())?;
-                let path = entry.path();
-                if path.is_file() {
-                    files.push(path);
-                }
-            }
-        }
+        scan_dir(dir, self.recusive, &mut files)?;
         for file in files {
             self.from_list.push(file.to_string_lossy().to_string());
         }
@@ -224,6 +206,29 @@ impl BatchProcess {
     }
 }
 
+/// Collects the files in `dir`, descending into subdirectories when `recursive`.
+/// Files of a subdirectory come before the files of its parent.
+pub fn scan_dir(
+    dir: &std::path::Path,
+    recursive: bool,
+    out: &mut Vec<std::path::PathBuf>,
+) -> Result<(), String> {
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(dir).map_err(|e| e.to_string())? {
+        let entry = entry.map_err(|e| e.to_string())?;
+        let path = entry.path();
+        if path.is_dir() {
+            if recursive {
+                scan_dir(&path, recursive, out)?;
+            }
+        } else if path.is_file() {
+            files.push(path);
+        }
+    }
+    out.extend(files);
+    Ok(())
+}
+
 impl ToolComponent for BatchProcess {
     fn paint_ui(&mut self, ui: &mut egui::Ui, _ctx: &egui::Context) {
         ui.horizontal(|ui| {
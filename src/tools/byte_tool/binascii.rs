@@ -0,0 +1,619 @@
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum Scheme {
+    Base32,
+    Base32Hex,
+    Base32Crockford,
+    Base58Bitcoin,
+    Base58Flickr,
+    Base58Check,
+    Z85,
+    Ascii85,
+    Base85,
+    Base91,
+    Base45,
+    Uuencode,
+    Yenc,
+}
+
+pub const SCHEMES: [Scheme; 13] = [
+    Scheme::Base32,
+    Scheme::Base32Hex,
+    Scheme::Base32Crockford,
+    Scheme::Base58Bitcoin,
+    Scheme::Base58Flickr,
+    Scheme::Base58Check,
+    Scheme::Z85,
+    Scheme::Ascii85,
+    Scheme::Base85,
+    Scheme::Base91,
+    Scheme::Base45,
+    Scheme::Uuencode,
+    Scheme::Yenc,
+];
+
+/// Order tried by [`decode_auto`]: framed formats first, then the smallest alphabets,
+/// since a string in a small alphabet usually also decodes under a larger one
+const AUTO_ORDER: [Scheme; 13] = [
+    Scheme::Yenc,
+    Scheme::Uuencode,
+    Scheme::Base58Check,
+    Scheme::Base32,
+    Scheme::Base32Hex,
+    Scheme::Base32Crockford,
+    Scheme::Base45,
+    Scheme::Base58Bitcoin,
+    Scheme::Base58Flickr,
+    Scheme::Ascii85,
+    Scheme::Z85,
+    Scheme::Base85,
+    Scheme::Base91,
+];
+
+impl Scheme {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Scheme::Base32 => "Base32",
+            Scheme::Base32Hex => "Base32hex",
+            Scheme::Base32Crockford => "Base32 Crockford",
+            Scheme::Base58Bitcoin => "Base58 Bitcoin",
+            Scheme::Base58Flickr => "Base58 Flickr",
+            Scheme::Base58Check => "Base58Check",
+            Scheme::Z85 => "Z85",
+            Scheme::Ascii85 => "Ascii85",
+            Scheme::Base85 => "Base85 (RFC 1924)",
+            Scheme::Base91 => "Base91",
+            Scheme::Base45 => "Base45",
+            Scheme::Uuencode => "uuencode",
+            Scheme::Yenc => "yEnc",
+        }
+    }
+}
+
+const Z85_ALPHABET: &[u8; 85] =
+    b"0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ.-:+=^!/*?&<>()[]{}@%$#";
+const ASCII85_ALPHABET: &[u8; 85] =
+    b"!\"#$%&'()*+,-./0123456789:;<=>?@ABCDEFGHIJKLMNOPQRSTUVWXYZ[\\]^_`abcdefghijklmnopqrstu";
+const BASE85_ALPHABET: &[u8; 85] =
+    b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz!#$%&()*+-;<=>?@^_`{|}~";
+const BASE91_ALPHABET: &[u8; 91] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789!#$%&()*+,./:;<=>?@[]^_`{|}~\"";
+const BASE45_ALPHABET: &[u8; 45] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ $%*+-./:";
+
+pub fn encode(input: &[u8], scheme: Scheme) -> Result<String, String> {
+    Ok(match scheme {
+        Scheme::Base32 => data_encoding::BASE32.encode(input),
+        Scheme::Base32Hex => data_encoding::BASE32HEX.encode(input),
+        Scheme::Base32Crockford => crockford()?.encode(input),
+        Scheme::Base58Bitcoin => bs58::encode(input).into_string(),
+        Scheme::Base58Flickr => bs58::encode(input)
+            .with_alphabet(bs58::Alphabet::FLICKR)
+            .into_string(),
+        Scheme::Base58Check => bs58::encode(input).with_check().into_string(),
+        Scheme::Z85 => {
+            if !input.len().is_multiple_of(4) {
+                return Err(format!(
+                    "Z85 needs a multiple of 4 bytes, input is {} bytes",
+                    input.len()
+                ));
+            }
+            base85_encode(input, Z85_ALPHABET, false)
+        }
+        Scheme::Ascii85 => format!("<~{}~>", base85_encode(input, ASCII85_ALPHABET, true)),
+        Scheme::Base85 => base85_encode(input, BASE85_ALPHABET, false),
+        Scheme::Base91 => base91_encode(input),
+        Scheme::Base45 => base45_encode(input),
+        Scheme::Uuencode => uuencode(input),
+        Scheme::Yenc => yenc_encode(input),
+    })
+}
+
+pub fn decode(input: &str, scheme: Scheme) -> Result<Vec<u8>, String> {
+    let trimmed = input.trim();
+    match scheme {
+        Scheme::Base32 => data_encoding::BASE32
+            .decode(trimmed.as_bytes())
+            .map_err(|e| e.to_string()),
+        Scheme::Base32Hex => data_encoding::BASE32HEX
+            .decode(trimmed.as_bytes())
+            .map_err(|e| e.to_string()),
+        Scheme::Base32Crockford => crockford()?
+            .decode(trimmed.as_bytes())
+            .map_err(|e| e.to_string()),
+        Scheme::Base58Bitcoin => bs58::decode(trimmed).into_vec().map_err(|e| e.to_string()),
+        Scheme::Base58Flickr => bs58::decode(trimmed)
+            .with_alphabet(bs58::Alphabet::FLICKR)
+            .into_vec()
+            .map_err(|e| e.to_string()),
+        Scheme::Base58Check => bs58::decode(trimmed)
+            .with_check(None)
+            .into_vec()
+            .map_err(|e| e.to_string()),
+        Scheme::Z85 => {
+            if !trimmed.len().is_multiple_of(5) {
+                return Err(format!(
+                    "Z85 needs a multiple of 5 characters, input has {}",
+                    trimmed.len()
+                ));
+            }
+            base85_decode(trimmed, Z85_ALPHABET, false)
+        }
+        Scheme::Ascii85 => {
+            let inner = trimmed.strip_prefix("<~").unwrap_or(trimmed);
+            let inner = inner.strip_suffix("~>").unwrap_or(inner);
+            base85_decode(inner, ASCII85_ALPHABET, true)
+        }
+        Scheme::Base85 => base85_decode(trimmed, BASE85_ALPHABET, false),
+        Scheme::Base91 => base91_decode(trimmed),
+        Scheme::Base45 => base45_decode(trimmed),
+        Scheme::Uuencode => uudecode(input),
+        Scheme::Yenc => yenc_decode(input),
+    }
+}
+
+/// Tries each scheme in turn and returns the first that decodes, like `base64_decode`
+/// does for its variants. Many inputs are valid in several schemes, so the match is a guess.
+pub fn decode_auto(input: &str) -> Result<(Scheme, Vec<u8>), String> {
+    let mut err_colector = String::new();
+    for scheme in AUTO_ORDER {
+        match decode(input, scheme) {
+            Ok(v) => return Ok((scheme, v)),
+            Err(e) => {
+                err_colector.push_str(&format!("{}: {}\n", scheme.name(), e));
+            }
+        }
+    }
+    Err(err_colector)
+}
+
+fn crockford() -> Result<data_encoding::Encoding, String> {
+    let mut spec = data_encoding::Specification::new();
+    spec.symbols.push_str("0123456789ABCDEFGHJKMNPQRSTVWXYZ");
+    // lower case is accepted, and the easily confused letters read as digits
+    spec.translate.from.push_str("abcdefghjkmnpqrstvwxyzOoIiLl");
+    spec.translate.to.push_str("ABCDEFGHJKMNPQRSTVWXYZ001111");
+    spec.ignore.push('-');
+    spec.encoding().map_err(|e| e.to_string())
+}
+
+/// Base85 in groups of 4 bytes to 5 digits. A short final group is padded
+/// and only the digits it needs are written.
+fn base85_encode(input: &[u8], alphabet: &[u8; 85], zero_z: bool) -> String {
+    let mut out = String::with_capacity(input.len() * 5 / 4 + 5);
+    for chunk in input.chunks(4) {
+        let mut group = [0u8; 4];
+        group[..chunk.len()].copy_from_slice(chunk);
+        let mut value = u32::from_be_bytes(group);
+        if zero_z && chunk.len() == 4 && value == 0 {
+            out.push('z');
+            continue;
+        }
+        let mut digits = [0u8; 5];
+        for d in digits.iter_mut().rev() {
+            *d = alphabet[(value % 85) as usize];
+            value /= 85;
+        }
+        out.extend(digits[..chunk.len() + 1].iter().map(|&c| c as char));
+    }
+    out
+}
+
+fn base85_group(out: &mut Vec<u8>, digits: &[u8], take: usize) -> Result<(), String> {
+    let value = digits.iter().fold(0u64, |v, &d| v * 85 + d as u64);
+    if value > u32::MAX as u64 {
+        return Err("Group value is out of range".to_string());
+    }
+    out.extend_from_slice(&(value as u32).to_be_bytes()[..take]);
+    Ok(())
+}
+
+fn base85_decode(input: &str, alphabet: &[u8; 85], zero_z: bool) -> Result<Vec<u8>, String> {
+    let mut lookup = [u8::MAX; 256];
+    for (i, &c) in alphabet.iter().enumerate() {
+        lookup[c as usize] = i as u8;
+    }
+    let mut out = Vec::with_capacity(input.len() * 4 / 5);
+    let mut group = Vec::with_capacity(5);
+    for (pos, c) in input.char_indices() {
+        if c.is_ascii_whitespace() {
+            continue;
+        }
+        if zero_z && c == 'z' && group.is_empty() {
+            out.extend_from_slice(&[0; 4]);
+            continue;
+        }
+        let digit = if c.is_ascii() {
+            lookup[c as usize]
+        } else {
+            u8::MAX
+        };
+        if digit == u8::MAX {
+            return Err(format!("Invalid character {:?} at position {}", c, pos));
+        }
+        group.push(digit);
+        if group.len() == 5 {
+            base85_group(&mut out, &group, 4)?;
+            group.clear();
+        }
+    }
+    match group.len() {
+        0 => {}
+        1 => return Err("Dangling final character".to_string()),
+        n => {
+            group.resize(5, 84);
+            base85_group(&mut out, &group, n - 1)?;
+        }
+    }
+    Ok(out)
+}
+
+fn base91_encode(input: &[u8]) -> String {
+    let mut out = String::with_capacity(input.len() * 16 / 13 + 2);
+    let (mut b, mut n) = (0u32, 0u32);
+    for &byte in input {
+        b |= (byte as u32) << n;
+        n += 8;
+        if n > 13 {
+            let mut v = b & 8191;
+            if v > 88 {
+                b >>= 13;
+                n -= 13;
+            } else {
+                v = b & 16383;
+                b >>= 14;
+                n -= 14;
+            }
+            out.push(BASE91_ALPHABET[(v % 91) as usize] as char);
+            out.push(BASE91_ALPHABET[(v / 91) as usize] as char);
+        }
+    }
+    if n > 0 {
+        out.push(BASE91_ALPHABET[(b % 91) as usize] as char);
+        if n > 7 || b > 90 {
+            out.push(BASE91_ALPHABET[(b / 91) as usize] as char);
+        }
+    }
+    out
+}
+
+fn base91_decode(input: &str) -> Result<Vec<u8>, String> {
+    let mut out = Vec::with_capacity(input.len() * 14 / 16);
+    let (mut b, mut n) = (0u32, 0u32);
+    let mut pending: Option<u32> = None;
+    for (pos, c) in input.char_indices() {
+        if c.is_ascii_whitespace() {
+            continue;
+        }
+        let digit = BASE91_ALPHABET
+            .iter()
+            .position(|&a| a as char == c)
+            .ok_or(format!("Invalid character {:?} at position {}", c, pos))?
+            as u32;
+        match pending.take() {
+            None => pending = Some(digit),
+            Some(first) => {
+                let v = first + digit * 91;
+                b |= v << n;
+                n += if v & 8191 > 88 { 13 } else { 14 };
+                while n > 7 {
+                    out.push(b as u8);
+                    b >>= 8;
+                    n -= 8;
+                }
+            }
+        }
+    }
+    if let Some(v) = pending {
+        out.push((b | v << n) as u8);
+    }
+    Ok(out)
+}
+
+fn base45_encode(input: &[u8]) -> String {
+    let mut out = String::with_capacity(input.len() * 3 / 2 + 2);
+    for chunk in input.chunks(2) {
+        let (mut n, digits) = match chunk {
+            [a, b] => ((*a as u32) * 256 + *b as u32, 3),
+            [a] => (*a as u32, 2),
+            _ => unreachable!(),
+        };
+        for _ in 0..digits {
+            out.push(BASE45_ALPHABET[(n % 45) as usize] as char);
+            n /= 45;
+        }
+    }
+    out
+}
+
+fn base45_decode(input: &str) -> Result<Vec<u8>, String> {
+    let digits = input
+        .char_indices()
+        .map(|(pos, c)| {
+            BASE45_ALPHABET
+                .iter()
+                .position(|&a| a as char == c)
+                .map(|d| d as u32)
+                .ok_or(format!("Invalid character {:?} at position {}", c, pos))
+        })
+        .collect::<Result<Vec<u32>, String>>()?;
+    let mut out = Vec::with_capacity(digits.len() * 2 / 3);
+    for group in digits.chunks(3) {
+        match group {
+            [c, d, e] => {
+                let n = c + d * 45 + e * 45 * 45;
+                if n > 0xFFFF {
+                    return Err(format!("Triplet value {} is out of range", n));
+                }
+                out.extend_from_slice(&(n as u16).to_be_bytes());
+            }
+            [c, d] => {
+                let n = c + d * 45;
+                if n > 0xFF {
+                    return Err(format!("Final pair value {} is out of range", n));
+                }
+                out.push(n as u8);
+            }
+            _ => return Err("Length must not leave a single trailing character".to_string()),
+        }
+    }
+    Ok(out)
+}
+
+fn uu_char(value: u8) -> char {
+    // a space would be lost to whitespace trimming, so zero is written as a backquote
+    if value == 0 {
+        '`'
+    } else {
+        (value + 32) as char
+    }
+}
+
+fn uuencode(input: &[u8]) -> String {
+    let mut out = String::from("begin 644 data\n");
+    for line in input.chunks(45) {
+        out.push(uu_char(line.len() as u8));
+        for group in line.chunks(3) {
+            let mut g = [0u8; 3];
+            g[..group.len()].copy_from_slice(group);
+            out.push(uu_char(g[0] >> 2));
+            out.push(uu_char((g[0] & 3) << 4 | g[1] >> 4));
+            out.push(uu_char((g[1] & 15) << 2 | g[2] >> 6));
+            out.push(uu_char(g[2] & 63));
+        }
+        out.push('\n');
+    }
+    out.push_str("`\nend\n");
+    out
+}
+
+fn uudecode(input: &str) -> Result<Vec<u8>, String> {
+    let mut lines: Vec<&str> = input.lines().collect();
+    let begin = lines
+        .iter()
+        .position(|l| l.starts_with("begin "))
+        .ok_or("Missing \"begin\" line".to_string())?;
+    lines.drain(..=begin);
+    let mut out = Vec::new();
+    for (number, line) in lines.iter().enumerate() {
+        let line = line.trim_end_matches('\r');
+        if line == "end" {
+            break;
+        }
+        if line.is_empty() {
+            continue;
+        }
+        let bytes = line.as_bytes();
+        if bytes.iter().any(|&c| !(b' '..=b'`').contains(&c)) {
+            return Err(format!(
+                "Line {} has characters outside uuencode's range",
+                number + 1
+            ));
+        }
+        let length = ((bytes[0] - 32) & 63) as usize;
+        let values: Vec<u8> = bytes[1..].iter().map(|&c| (c - 32) & 63).collect();
+        let mut decoded = Vec::with_capacity(length + 2);
+        for g in values.chunks(4) {
+            let g = [
+                g[0],
+                *g.get(1).unwrap_or(&0),
+                *g.get(2).unwrap_or(&0),
+                *g.get(3).unwrap_or(&0),
+            ];
+            decoded.push(g[0] << 2 | g[1] >> 4);
+            decoded.push(g[1] << 4 | g[2] >> 2);
+            decoded.push(g[2] << 6 | g[3]);
+        }
+        if decoded.len() < length {
+            return Err(format!(
+                "Line {} holds {} bytes but declares {}",
+                number + 1,
+                decoded.len(),
+                length
+            ));
+        }
+        out.extend_from_slice(&decoded[..length]);
+    }
+    Ok(out)
+}
+
+fn crc32(input: &[u8]) -> u32 {
+    let mut crc = flate2::Crc::new();
+    crc.update(input);
+    crc.sum()
+}
+
+/// yEnc output is 8-bit, so each output byte is written as the character
+/// with the same code point (Latin-1)
+fn yenc_encode(input: &[u8]) -> String {
+    const LINE: usize = 128;
+    let mut out = format!("=ybegin line={} size={} name=data\n", LINE, input.len());
+    let mut column = 0;
+    for (i, &b) in input.iter().enumerate() {
+        let c = b.wrapping_add(42);
+        let line_end = column + 1 >= LINE || i + 1 == input.len();
+        let escape = matches!(c, 0 | b'\n' | b'\r' | b'=')
+            || (matches!(c, b'\t' | b' ') && (column == 0 || line_end))
+            || (c == b'.' && column == 0);
+        if escape {
+            out.push('=');
+            out.push(char::from(c.wrapping_add(64)));
+            column += 2;
+        } else {
+            out.push(char::from(c));
+            column += 1;
+        }
+        if column >= LINE {
+            out.push('\n');
+            column = 0;
+        }
+    }
+    if column > 0 {
+        out.push('\n');
+    }
+    out.push_str(&format!(
+        "=yend size={} crc32={:08x}\n",
+        input.len(),
+        crc32(input)
+    ));
+    out
+}
+
+/// Value of a `key=value` parameter on a yEnc header line
+fn yenc_param<'a>(line: &'a str, key: &str) -> Option<&'a str> {
+    line.split(' ')
+        .find_map(|part| part.strip_prefix(key)?.strip_prefix('='))
+}
+
+fn yenc_decode(input: &str) -> Result<Vec<u8>, String> {
+    let mut lines = input.lines();
+    let header = lines
+        .by_ref()
+        .find(|l| l.starts_with("=ybegin "))
+        .ok_or("Missing \"=ybegin\" line".to_string())?;
+    let size: Option<usize> = yenc_param(header, "size").and_then(|s| s.parse().ok());
+    let mut out = Vec::with_capacity(size.unwrap_or(input.len()));
+    let mut trailer = None;
+    for line in lines {
+        if line.starts_with("=ypart ") {
+            continue;
+        }
+        if line.starts_with("=yend") {
+            trailer = Some(line);
+            break;
+        }
+        let mut escaped = false;
+        for c in line.chars() {
+            let byte =
+                u8::try_from(c as u32).map_err(|_| format!("{:?} is not an 8-bit character", c))?;
+            match byte {
+                b'\r' => {}
+                b'=' if !escaped => escaped = true,
+                _ => {
+                    let byte = if escaped { byte.wrapping_sub(64) } else { byte };
+                    out.push(byte.wrapping_sub(42));
+                    escaped = false;
+                }
+            }
+        }
+    }
+    let trailer = trailer.ok_or("Missing \"=yend\" line".to_string())?;
+    if let Some(size) = size {
+        if size != out.len() {
+            return Err(format!(
+                "Size is {} but header declares {}",
+                out.len(),
+                size
+            ));
+        }
+    }
+    if let Some(expected) = yenc_param(trailer, "crc32") {
+        let actual = crc32(&out);
+        if u32::from_str_radix(expected, 16).ok() != Some(actual) {
+            return Err(format!(
+                "CRC32 is {:08x} but trailer declares {}",
+                actual, expected
+            ));
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod test {
+    use super::Scheme;
+
+    #[test]
+    fn test_vectors() {
+        let cases: [(Scheme, &[u8], &str); 9] = [
+            (Scheme::Base32, b"foobar", "MZXW6YTBOI======"),
+            (Scheme::Base32Hex, b"foobar", "CPNMUOJ1E8======"),
+            (Scheme::Base58Bitcoin, b"Hello World!", "2NEpo7TZRRrLZSi2U"),
+            (
+                Scheme::Z85,
+                &[0x86, 0x4F, 0xD2, 0x6F, 0xB5, 0x59, 0xF7, 0x5B],
+                "HelloWorld",
+            ),
+            (Scheme::Ascii85, b"sure.", "<~F*2M7/c~>"),
+            (Scheme::Base85, b"hello", "Xk~0{Zv"),
+            (Scheme::Base91, b"test", "fPNKd"),
+            (Scheme::Base45, b"AB", "BB8"),
+            (Scheme::Base45, b"Hello!!", "%69 VD92EX0"),
+        ];
+        for (scheme, raw, text) in cases {
+            assert_eq!(
+                super::encode(raw, scheme).unwrap(),
+                text,
+                "{}",
+                scheme.name()
+            );
+            assert_eq!(
+                super::decode(text, scheme).unwrap(),
+                raw,
+                "{}",
+                scheme.name()
+            );
+        }
+        // Crockford is case-insensitive and ignores hyphens
+        let text = super::encode(b"crockford", Scheme::Base32Crockford).unwrap();
+        let sloppy = format!("{}-{}", &text[..4], &text[4..]).to_lowercase();
+        assert_eq!(
+            super::decode(&sloppy, Scheme::Base32Crockford).unwrap(),
+            b"crockford"
+        );
+        assert!(super::decode("GGW", Scheme::Base45).is_err());
+    }
+    #[test]
+    fn test_roundtrip() {
+        let data: Vec<u8> = (0..=255u8).chain([0, 0, 0, 0, 42, 214, 19]).collect();
+        for scheme in super::SCHEMES {
+            let data = if scheme == Scheme::Z85 {
+                &data[..256]
+            } else {
+                &data[..]
+            };
+            let text = super::encode(data, scheme).unwrap();
+            assert_eq!(
+                super::decode(&text, scheme).unwrap(),
+                data,
+                "{}",
+                scheme.name()
+            );
+        }
+    }
+    #[test]
+    fn test_auto() {
+        let data = b"auto detect me".to_vec();
+        for scheme in [
+            Scheme::Yenc,
+            Scheme::Uuencode,
+            Scheme::Base58Check,
+            Scheme::Base32,
+        ] {
+            let text = super::encode(&data, scheme).unwrap();
+            assert_eq!(super::decode_auto(&text).unwrap(), (scheme, data.clone()));
+        }
+        let yenc = super::encode(&data, Scheme::Yenc)
+            .unwrap()
+            .replace("size=14", "size=15");
+        assert!(super::decode(&yenc, Scheme::Yenc).is_err());
+    }
+}
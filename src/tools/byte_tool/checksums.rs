@@ -0,0 +1,508 @@
+use std::hash::Hasher;
+use std::io::Cursor;
+
+pub type SeededFn = fn(&[u8], u64) -> String;
+pub type KeyedFn = fn(&[u8], &[u8; 16]) -> String;
+
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub struct CrcParams {
+    pub width: u8,
+    pub poly: u64,
+    pub init: u64,
+    pub refin: bool,
+    pub refout: bool,
+    pub xorout: u64,
+}
+
+/// Parameters and check value (CRC of "123456789") of well-known CRC algorithms,
+/// named after the catalogue of parametrised CRC algorithms.
+pub const CRC_CATALOGUE: &[(&str, CrcParams, u64)] = &[
+    (
+        "CRC-8/SMBUS",
+        crc_p(8, 0x07, 0x00, false, false, 0x00),
+        0xF4,
+    ),
+    (
+        "CRC-8/CDMA2000",
+        crc_p(8, 0x9B, 0xFF, false, false, 0x00),
+        0xDA,
+    ),
+    ("CRC-8/DARC", crc_p(8, 0x39, 0x00, true, true, 0x00), 0x15),
+    (
+        "CRC-8/DVB-S2",
+        crc_p(8, 0xD5, 0x00, false, false, 0x00),
+        0xBC,
+    ),
+    ("CRC-8/EBU", crc_p(8, 0x1D, 0xFF, true, true, 0x00), 0x97),
+    (
+        "CRC-8/I-CODE",
+        crc_p(8, 0x1D, 0xFD, false, false, 0x00),
+        0x7E,
+    ),
+    ("CRC-8/ITU", crc_p(8, 0x07, 0x00, false, false, 0x55), 0xA1),
+    ("CRC-8/MAXIM", crc_p(8, 0x31, 0x00, true, true, 0x00), 0xA1),
+    ("CRC-8/ROHC", crc_p(8, 0x07, 0xFF, true, true, 0x00), 0xD0),
+    ("CRC-8/WCDMA", crc_p(8, 0x9B, 0x00, true, true, 0x00), 0x25),
+    (
+        "CRC-16/ARC",
+        crc_p(16, 0x8005, 0x0000, true, true, 0x0000),
+        0xBB3D,
+    ),
+    (
+        "CRC-16/AUG-CCITT",
+        crc_p(16, 0x1021, 0x1D0F, false, false, 0x0000),
+        0xE5CC,
+    ),
+    (
+        "CRC-16/BUYPASS",
+        crc_p(16, 0x8005, 0x0000, false, false, 0x0000),
+        0xFEE8,
+    ),
+    (
+        "CRC-16/CCITT-FALSE",
+        crc_p(16, 0x1021, 0xFFFF, false, false, 0x0000),
+        0x29B1,
+    ),
+    (
+        "CRC-16/DECT-X",
+        crc_p(16, 0x0589, 0x0000, false, false, 0x0000),
+        0x007F,
+    ),
+    (
+        "CRC-16/DNP",
+        crc_p(16, 0x3D65, 0x0000, true, true, 0xFFFF),
+        0xEA82,
+    ),
+    (
+        "CRC-16/GENIBUS",
+        crc_p(16, 0x1021, 0xFFFF, false, false, 0xFFFF),
+        0xD64E,
+    ),
+    (
+        "CRC-16/KERMIT",
+        crc_p(16, 0x1021, 0x0000, true, true, 0x0000),
+        0x2189,
+    ),
+    (
+        "CRC-16/MAXIM",
+        crc_p(16, 0x8005, 0x0000, true, true, 0xFFFF),
+        0x44C2,
+    ),
+    (
+        "CRC-16/MCRF4XX",
+        crc_p(16, 0x1021, 0xFFFF, true, true, 0x0000),
+        0x6F91,
+    ),
+    (
+        "CRC-16/MODBUS",
+        crc_p(16, 0x8005, 0xFFFF, true, true, 0x0000),
+        0x4B37,
+    ),
+    (
+        "CRC-16/USB",
+        crc_p(16, 0x8005, 0xFFFF, true, true, 0xFFFF),
+        0xB4C8,
+    ),
+    (
+        "CRC-16/X-25",
+        crc_p(16, 0x1021, 0xFFFF, true, true, 0xFFFF),
+        0x906E,
+    ),
+    (
+        "CRC-16/XMODEM",
+        crc_p(16, 0x1021, 0x0000, false, false, 0x0000),
+        0x31C3,
+    ),
+    (
+        "CRC-32",
+        crc_p(32, 0x04C11DB7, 0xFFFFFFFF, true, true, 0xFFFFFFFF),
+        0xCBF43926,
+    ),
+    (
+        "CRC-32/BZIP2",
+        crc_p(32, 0x04C11DB7, 0xFFFFFFFF, false, false, 0xFFFFFFFF),
+        0xFC891918,
+    ),
+    (
+        "CRC-32C",
+        crc_p(32, 0x1EDC6F41, 0xFFFFFFFF, true, true, 0xFFFFFFFF),
+        0xE3069283,
+    ),
+    (
+        "CRC-32D",
+        crc_p(32, 0xA833982B, 0xFFFFFFFF, true, true, 0xFFFFFFFF),
+        0x87315576,
+    ),
+    (
+        "CRC-32/JAMCRC",
+        crc_p(32, 0x04C11DB7, 0xFFFFFFFF, true, true, 0x00000000),
+        0x340BC6D9,
+    ),
+    (
+        "CRC-32/MPEG-2",
+        crc_p(32, 0x04C11DB7, 0xFFFFFFFF, false, false, 0x00000000),
+        0x0376E6E7,
+    ),
+    (
+        "CRC-32/POSIX",
+        crc_p(32, 0x04C11DB7, 0x00000000, false, false, 0xFFFFFFFF),
+        0x765E7680,
+    ),
+    (
+        "CRC-32Q",
+        crc_p(32, 0x814141AB, 0x00000000, false, false, 0x00000000),
+        0x3010BF7F,
+    ),
+    (
+        "CRC-32/XFER",
+        crc_p(32, 0x000000AF, 0x00000000, false, false, 0x00000000),
+        0xBD0BE338,
+    ),
+    (
+        "CRC-64/ECMA-182",
+        crc_p(64, 0x42F0E1EBA9EA3693, 0, false, false, 0),
+        0x6C40DF5F0B497347,
+    ),
+    (
+        "CRC-64/GO-ISO",
+        crc_p(64, 0x1B, u64::MAX, true, true, u64::MAX),
+        0xB90956C775A41001,
+    ),
+    (
+        "CRC-64/WE",
+        crc_p(64, 0x42F0E1EBA9EA3693, u64::MAX, false, false, u64::MAX),
+        0x62EC59E3F1A4F00A,
+    ),
+    (
+        "CRC-64/XZ",
+        crc_p(64, 0x42F0E1EBA9EA3693, u64::MAX, true, true, u64::MAX),
+        0x995DC9BBDF1939FA,
+    ),
+];
+
+const fn crc_p(
+    width: u8,
+    poly: u64,
+    init: u64,
+    refin: bool,
+    refout: bool,
+    xorout: u64,
+) -> CrcParams {
+    CrcParams {
+        width,
+        poly,
+        init,
+        refin,
+        refout,
+        xorout,
+    }
+}
+
+impl CrcParams {
+    fn mask(&self) -> u64 {
+        if self.width >= 64 {
+            u64::MAX
+        } else {
+            (1u64 << self.width) - 1
+        }
+    }
+    pub fn validate(&self) -> Result<(), String> {
+        if self.width == 0 || self.width > 64 {
+            return Err(format!("CRC width must be in 1..=64, got {}", self.width));
+        }
+        let mask = self.mask();
+        if self.poly & !mask != 0 || self.init & !mask != 0 || self.xorout & !mask != 0 {
+            return Err(format!(
+                "Poly, init and xorout must fit in {} bits",
+                self.width
+            ));
+        }
+        Ok(())
+    }
+    /// Number of hex digits needed to display a value of this width
+    pub fn hex_digits(&self) -> usize {
+        (self.width as usize).div_ceil(4)
+    }
+}
+
+fn reflect(value: u64, width: u8) -> u64 {
+    value.reverse_bits() >> (64 - width as u32)
+}
+
+/// Bitwise CRC engine for any width in 1..=64.
+///
+/// The register is kept left-aligned in a u64 so the same loop handles every width.
+pub fn crc(input: &[u8], params: &CrcParams) -> u64 {
+    let width = params.width as u32;
+    let shift = 64 - width;
+    let poly = params.poly << shift;
+    let mut reg = params.init << shift;
+    for &b in input {
+        let b = if params.refin { b.reverse_bits() } else { b };
+        reg ^= (b as u64) << 56;
+        for _ in 0..8 {
+            if reg & (1 << 63) != 0 {
+                reg = (reg << 1) ^ poly;
+            } else {
+                reg <<= 1;
+            }
+        }
+    }
+    let mut reg = reg >> shift;
+    if params.refout {
+        reg = reflect(reg, params.width);
+    }
+    (reg ^ params.xorout) & params.mask()
+}
+
+pub fn crc_hex(input: &[u8], params: &CrcParams) -> String {
+    format!(
+        "{:0width$x}",
+        crc(input, params),
+        width = params.hex_digits()
+    )
+}
+
+pub fn adler32(input: &[u8]) -> String {
+    const MOD_ADLER: u32 = 65521;
+    let mut a = 1u32;
+    let mut b = 0u32;
+    // 5552 is the largest n such that b does not overflow before the modulo
+    for chunk in input.chunks(5552) {
+        for &byte in chunk {
+            a += byte as u32;
+            b += a;
+        }
+        a %= MOD_ADLER;
+        b %= MOD_ADLER;
+    }
+    format!("{:08x}", (b << 16) | a)
+}
+
+pub fn fletcher16(input: &[u8]) -> String {
+    let mut sum1 = 0u32;
+    let mut sum2 = 0u32;
+    for &byte in input {
+        sum1 = (sum1 + byte as u32) % 255;
+        sum2 = (sum2 + sum1) % 255;
+    }
+    format!("{:04x}", (sum2 << 8) | sum1)
+}
+
+pub fn fletcher32(input: &[u8]) -> String {
+    let mut sum1 = 0u64;
+    let mut sum2 = 0u64;
+    for word in input.chunks(2) {
+        let mut buf = [0u8; 2];
+        buf[..word.len()].copy_from_slice(word);
+        sum1 = (sum1 + u16::from_le_bytes(buf) as u64) % 0xFFFF;
+        sum2 = (sum2 + sum1) % 0xFFFF;
+    }
+    format!("{:08x}", (sum2 << 16) | sum1)
+}
+
+pub fn fletcher64(input: &[u8]) -> String {
+    let mut sum1 = 0u128;
+    let mut sum2 = 0u128;
+    for word in input.chunks(4) {
+        let mut buf = [0u8; 4];
+        buf[..word.len()].copy_from_slice(word);
+        sum1 = (sum1 + u32::from_le_bytes(buf) as u128) % 0xFFFFFFFF;
+        sum2 = (sum2 + sum1) % 0xFFFFFFFF;
+    }
+    format!("{:016x}", (sum2 << 32) | sum1)
+}
+
+pub fn fnv1_32(input: &[u8]) -> String {
+    let mut hash = 0x811c9dc5u32;
+    for &byte in input {
+        hash = hash.wrapping_mul(0x01000193);
+        hash ^= byte as u32;
+    }
+    format!("{:08x}", hash)
+}
+
+pub fn fnv1a_32(input: &[u8]) -> String {
+    let mut hash = 0x811c9dc5u32;
+    for &byte in input {
+        hash ^= byte as u32;
+        hash = hash.wrapping_mul(0x01000193);
+    }
+    format!("{:08x}", hash)
+}
+
+pub fn fnv1_64(input: &[u8]) -> String {
+    let mut hash = 0xcbf29ce484222325u64;
+    for &byte in input {
+        hash = hash.wrapping_mul(0x100000001b3);
+        hash ^= byte as u64;
+    }
+    format!("{:016x}", hash)
+}
+
+pub fn fnv1a_64(input: &[u8]) -> String {
+    let mut hash = 0xcbf29ce484222325u64;
+    for &byte in input {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("{:016x}", hash)
+}
+
+pub fn xxh32(input: &[u8], seed: u64) -> String {
+    format!("{:08x}", xxhash_rust::xxh32::xxh32(input, seed as u32))
+}
+
+pub fn xxh64(input: &[u8], seed: u64) -> String {
+    format!("{:016x}", xxhash_rust::xxh64::xxh64(input, seed))
+}
+
+pub fn xxh3_64(input: &[u8], seed: u64) -> String {
+    format!("{:016x}", xxhash_rust::xxh3::xxh3_64_with_seed(input, seed))
+}
+
+pub fn xxh3_128(input: &[u8], seed: u64) -> String {
+    format!(
+        "{:032x}",
+        xxhash_rust::xxh3::xxh3_128_with_seed(input, seed)
+    )
+}
+
+pub fn murmur3_32(input: &[u8], seed: u64) -> String {
+    match murmur3::murmur3_32(&mut Cursor::new(input), seed as u32) {
+        Ok(v) => format!("{:08x}", v),
+        Err(e) => format!("Error: {}", e),
+    }
+}
+
+pub fn murmur3_x86_128(input: &[u8], seed: u64) -> String {
+    match murmur3::murmur3_x86_128(&mut Cursor::new(input), seed as u32) {
+        Ok(v) => format!("{:032x}", v),
+        Err(e) => format!("Error: {}", e),
+    }
+}
+
+pub fn murmur3_x64_128(input: &[u8], seed: u64) -> String {
+    match murmur3::murmur3_x64_128(&mut Cursor::new(input), seed as u32) {
+        Ok(v) => format!("{:032x}", v),
+        Err(e) => format!("Error: {}", e),
+    }
+}
+
+pub fn siphash24(input: &[u8], key: &[u8; 16]) -> String {
+    let (k0, k1) = sip_keys(key);
+    let mut hasher = siphasher::sip::SipHasher24::new_with_keys(k0, k1);
+    hasher.write(input);
+    format!("{:016x}", hasher.finish())
+}
+
+pub fn siphash13(input: &[u8], key: &[u8; 16]) -> String {
+    let (k0, k1) = sip_keys(key);
+    let mut hasher = siphasher::sip::SipHasher13::new_with_keys(k0, k1);
+    hasher.write(input);
+    format!("{:016x}", hasher.finish())
+}
+
+pub fn siphash24_128(input: &[u8], key: &[u8; 16]) -> String {
+    use siphasher::sip128::Hasher128;
+    let (k0, k1) = sip_keys(key);
+    let mut hasher = siphasher::sip128::SipHasher24::new_with_keys(k0, k1);
+    hasher.write(input);
+    hex::encode(hasher.finish128().as_bytes())
+}
+
+fn sip_keys(key: &[u8; 16]) -> (u64, u64) {
+    let mut k0 = [0u8; 8];
+    let mut k1 = [0u8; 8];
+    k0.copy_from_slice(&key[..8]);
+    k1.copy_from_slice(&key[8..]);
+    (u64::from_le_bytes(k0), u64::from_le_bytes(k1))
+}
+
+/// Parses a seed written as hex, big-endian, up to 8 bytes. Empty means zero.
+pub fn parse_seed(input: &str) -> Result<u64, String> {
+    let input = input.trim().trim_start_matches("0x");
+    if input.is_empty() {
+        return Ok(0);
+    }
+    u64::from_str_radix(input, 16).map_err(|e| format!("Invalid seed: {}", e))
+}
+
+/// Parses a 128-bit SipHash key written as 32 hex digits. Empty means the all-zero key.
+pub fn parse_sip_key(input: &str) -> Result<[u8; 16], String> {
+    let input = input.trim();
+    let mut key = [0u8; 16];
+    if input.is_empty() {
+        return Ok(key);
+    }
+    let bytes = hex::decode(input).map_err(|e| format!("Invalid key: {}", e))?;
+    if bytes.len() != 16 {
+        return Err(format!("SipHash key must be 16 bytes, got {}", bytes.len()));
+    }
+    key.copy_from_slice(&bytes);
+    Ok(key)
+}
+
+/// Parses a hex CRC parameter such as `0x04C11DB7` or `04c11db7`
+pub fn parse_crc_value(input: &str) -> Result<u64, String> {
+    let input = input.trim();
+    let input = input
+        .strip_prefix("0x")
+        .or_else(|| input.strip_prefix("0X"))
+        .unwrap_or(input);
+    if input.is_empty() {
+        return Ok(0);
+    }
+    u64::from_str_radix(input, 16).map_err(|e| format!("Invalid hex value \"{}\": {}", input, e))
+}
+
+#[cfg(test)]
+mod test {
+    const CHECK: &[u8] = b"123456789";
+
+    #[test]
+    fn test_crc_catalogue() {
+        for (name, params, check) in super::CRC_CATALOGUE {
+            assert!(params.validate().is_ok(), "{}", name);
+            assert_eq!(super::crc(CHECK, params), *check, "{}", name);
+        }
+    }
+    #[test]
+    fn test_crc_odd_width() {
+        // CRC-5/USB and CRC-12/UMTS exercise widths that are not a multiple of 8
+        let crc5 = super::crc_p(5, 0x05, 0x1F, true, true, 0x1F);
+        assert_eq!(super::crc(CHECK, &crc5), 0x19);
+        let crc12 = super::crc_p(12, 0x80F, 0x000, false, true, 0x000);
+        assert_eq!(super::crc(CHECK, &crc12), 0xDAF);
+    }
+    #[test]
+    fn test_crc_matches_flate2() {
+        let data = b"The quick brown fox jumps over the lazy dog";
+        let mut crc = flate2::Crc::new();
+        crc.update(data);
+        let (_, params, _) = super::CRC_CATALOGUE
+            .iter()
+            .find(|(name, _, _)| *name == "CRC-32")
+            .unwrap();
+        assert_eq!(super::crc(data, params), crc.sum() as u64);
+    }
+    #[test]
+    fn test_checksums() {
+        assert_eq!(super::adler32(b"Wikipedia"), "11e60398");
+        assert_eq!(super::fletcher16(b"abcde"), "c8f0");
+        assert_eq!(super::fletcher32(b"abcde"), "f04fc729");
+        assert_eq!(super::fletcher64(b"abcde"), "c8c6c527646362c6");
+        assert_eq!(super::fnv1a_32(b"a"), "e40c292c");
+        assert_eq!(super::fnv1_32(b"a"), "050c5d7e");
+        assert_eq!(super::fnv1a_64(b"a"), "af63dc4c8601ec8c");
+        assert_eq!(super::fnv1_64(b"a"), "af63bd4c8601b7be");
+    }
+    #[test]
+    fn test_seeded() {
+        assert_eq!(super::xxh32(b"", 0), "02cc5d05");
+        assert_eq!(super::xxh64(b"", 0), "ef46db3751d8e999");
+        assert_eq!(super::murmur3_32(b"", 1), "514e28b7");
+        assert_eq!(super::parse_seed("0x10"), Ok(16));
+        assert!(super::parse_sip_key("0011").is_err());
+    }
+}
@@ -0,0 +1,251 @@
+use std::io::{Read, Write};
+
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum Codec {
+    Gzip,
+    Zlib,
+    Deflate,
+    Zstd,
+    Brotli,
+    Lz4,
+    Bzip2,
+    Xz,
+    Snappy,
+    SnappyRaw,
+}
+
+pub const CODECS: [Codec; 10] = [
+    Codec::Gzip,
+    Codec::Zlib,
+    Codec::Deflate,
+    Codec::Zstd,
+    Codec::Brotli,
+    Codec::Lz4,
+    Codec::Bzip2,
+    Codec::Xz,
+    Codec::Snappy,
+    Codec::SnappyRaw,
+];
+
+impl Codec {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Codec::Gzip => "gzip",
+            Codec::Zlib => "zlib",
+            Codec::Deflate => "Raw Deflate",
+            Codec::Zstd => "Zstandard",
+            Codec::Brotli => "Brotli",
+            Codec::Lz4 => "LZ4 Frame",
+            Codec::Bzip2 => "bzip2",
+            Codec::Xz => "xz",
+            Codec::Snappy => "Snappy Framed",
+            Codec::SnappyRaw => "Snappy Raw",
+        }
+    }
+    /// (min, max, default) compression level, None if the codec has no levels
+    pub fn levels(&self) -> Option<(u32, u32, u32)> {
+        match self {
+            Codec::Gzip | Codec::Zlib | Codec::Deflate => Some((0, 9, 6)),
+            Codec::Zstd => Some((1, 22, 3)),
+            Codec::Brotli => Some((0, 11, 9)),
+            Codec::Bzip2 => Some((1, 9, 9)),
+            Codec::Xz => Some((0, 9, 6)),
+            Codec::Lz4 | Codec::Snappy | Codec::SnappyRaw => None,
+        }
+    }
+}
+
+fn io_err(e: std::io::Error) -> String {
+    e.to_string()
+}
+
+pub fn compress(input: &[u8], codec: Codec, level: u32) -> Result<Vec<u8>, String> {
+    match codec {
+        Codec::Gzip => {
+            let mut encoder =
+                flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::new(level));
+            encoder.write_all(input).map_err(io_err)?;
+            encoder.finish().map_err(io_err)
+        }
+        Codec::Zlib => {
+            let mut encoder =
+                flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::new(level));
+            encoder.write_all(input).map_err(io_err)?;
+            encoder.finish().map_err(io_err)
+        }
+        Codec::Deflate => {
+            let mut encoder =
+                flate2::write::DeflateEncoder::new(Vec::new(), flate2::Compression::new(level));
+            encoder.write_all(input).map_err(io_err)?;
+            encoder.finish().map_err(io_err)
+        }
+        Codec::Zstd => zstd::encode_all(input, level as i32).map_err(io_err),
+        Codec::Brotli => {
+            let mut output = Vec::new();
+            {
+                // 22 is the default window size of the reference encoder
+                let mut encoder = brotli::CompressorWriter::new(&mut output, 4096, level, 22);
+                encoder.write_all(input).map_err(io_err)?;
+            }
+            Ok(output)
+        }
+        Codec::Lz4 => {
+            let mut encoder = lz4_flex::frame::FrameEncoder::new(Vec::new());
+            encoder.write_all(input).map_err(io_err)?;
+            encoder.finish().map_err(|e| e.to_string())
+        }
+        Codec::Bzip2 => {
+            let mut encoder =
+                bzip2::write::BzEncoder::new(Vec::new(), bzip2::Compression::new(level));
+            encoder.write_all(input).map_err(io_err)?;
+            encoder.finish().map_err(io_err)
+        }
+        Codec::Xz => {
+            let mut encoder = xz2::write::XzEncoder::new(Vec::new(), level);
+            encoder.write_all(input).map_err(io_err)?;
+            encoder.finish().map_err(io_err)
+        }
+        Codec::Snappy => {
+            let mut encoder = snap::write::FrameEncoder::new(Vec::new());
+            encoder.write_all(input).map_err(io_err)?;
+            encoder.into_inner().map_err(|e| e.to_string())
+        }
+        Codec::SnappyRaw => snap::raw::Encoder::new()
+            .compress_vec(input)
+            .map_err(|e| e.to_string()),
+    }
+}
+
+/// Most output the Decompress button keeps, so a small bomb can't take all memory
+pub const DECOMPRESS_LIMIT: u64 = 256 << 20;
+
+/// Decompresses at most `limit` bytes, returning whether the output was cut off there
+pub fn decompress(input: &[u8], codec: Codec, limit: u64) -> Result<(Vec<u8>, bool), String> {
+    let reader: Box<dyn Read + '_> = match codec {
+        Codec::Gzip => Box::new(flate2::read::MultiGzDecoder::new(input)),
+        Codec::Zlib => Box::new(flate2::read::ZlibDecoder::new(input)),
+        Codec::Deflate => Box::new(flate2::read::DeflateDecoder::new(input)),
+        Codec::Zstd => Box::new(zstd::stream::read::Decoder::new(input).map_err(io_err)?),
+        Codec::Brotli => Box::new(brotli::Decompressor::new(input, 4096)),
+        Codec::Lz4 => Box::new(lz4_flex::frame::FrameDecoder::new(input)),
+        Codec::Bzip2 => Box::new(bzip2::read::MultiBzDecoder::new(input)),
+        Codec::Xz => Box::new(xz2::read::XzDecoder::new_multi_decoder(input)),
+        Codec::Snappy => Box::new(snap::read::FrameDecoder::new(input)),
+        Codec::SnappyRaw => {
+            // a raw block can't be read in part, but its length comes first
+            let len = snap::raw::decompress_len(input).map_err(|e| e.to_string())?;
+            if len as u64 > limit {
+                return Err(format!(
+                    "Block of {} is over the limit of {}",
+                    crate::format_mem(len),
+                    crate::format_mem(limit as usize)
+                ));
+            }
+            let output = snap::raw::Decoder::new()
+                .decompress_vec(input)
+                .map_err(|e| e.to_string())?;
+            return Ok((output, false));
+        }
+    };
+    let mut output = Vec::new();
+    reader
+        .take(limit.saturating_add(1))
+        .read_to_end(&mut output)
+        .map_err(io_err)?;
+    let truncated = output.len() as u64 > limit;
+    output.truncate(limit as usize);
+    Ok((output, truncated))
+}
+
+fn magic_match(input: &[u8]) -> Option<Codec> {
+    if input.starts_with(b"\x1f\x8b") {
+        Some(Codec::Gzip)
+    } else if input.starts_with(b"\x28\xb5\x2f\xfd") {
+        Some(Codec::Zstd)
+    } else if input.starts_with(b"\xfd7zXZ\x00") {
+        Some(Codec::Xz)
+    } else if input.starts_with(b"BZh") {
+        Some(Codec::Bzip2)
+    } else if input.starts_with(b"\x04\x22\x4d\x18") {
+        Some(Codec::Lz4)
+    } else if input.starts_with(b"\xff\x06\x00\x00sNaPpY") {
+        Some(Codec::Snappy)
+    } else if input.len() >= 2
+        && input[0] & 0x0f == 8
+        && input[0] >> 4 <= 7
+        && (input[0] as u16 * 256 + input[1] as u16).is_multiple_of(31)
+    {
+        Some(Codec::Zlib)
+    } else {
+        None
+    }
+}
+
+/// Output a trial decode stops after, enough to tell a stream from noise
+const TRIAL_LIMIT: u64 = 64 * 1024;
+
+/// Whether the start of `input` decodes, without inflating all of it
+fn trial(input: &[u8], codec: Codec) -> bool {
+    decompress(input, codec, TRIAL_LIMIT).is_ok()
+}
+
+/// Suggests decompressors for the input, most likely first.
+///
+/// Streams with a magic header are recognised directly. Brotli, raw deflate and
+/// raw Snappy have no header, so they are only suggested when a trial decode of the
+/// start of the input succeeds.
+pub fn detect(input: &[u8]) -> Vec<Codec> {
+    let mut result = Vec::new();
+    if let Some(codec) = magic_match(input) {
+        result.push(codec);
+    }
+    for codec in [Codec::Deflate, Codec::Brotli, Codec::SnappyRaw] {
+        if !result.contains(&codec) && trial(input, codec) {
+            result.push(codec);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn test_roundtrip() {
+        let data = b"compress me, compress me, compress me, compress me!".repeat(10);
+        for codec in super::CODECS {
+            let level = codec.levels().map_or(0, |l| l.2);
+            let packed = super::compress(&data, codec, level).unwrap();
+            assert_eq!(
+                super::decompress(&packed, codec, super::DECOMPRESS_LIMIT)
+                    .unwrap()
+                    .0,
+                data,
+                "{}",
+                codec.name()
+            );
+            match super::decompress(&packed, codec, 10) {
+                Ok((head, truncated)) => {
+                    assert_eq!(head, data[..10], "{}", codec.name());
+                    assert!(truncated, "{}", codec.name());
+                }
+                Err(_) => assert_eq!(codec, super::Codec::SnappyRaw),
+            }
+        }
+    }
+    #[test]
+    fn test_detect() {
+        let data = b"detect me, detect me, detect me".repeat(4);
+        for codec in super::CODECS {
+            let level = codec.levels().map_or(0, |l| l.2);
+            let packed = super::compress(&data, codec, level).unwrap();
+            assert!(super::detect(&packed).contains(&codec), "{}", codec.name());
+        }
+        assert!(super::detect(b"plain text is not compressed").is_empty());
+        // only the start of a stream that inflates past the trial limit is decoded
+        let zeros = vec![0u8; 4 << 20];
+        for codec in [super::Codec::Deflate, super::Codec::Brotli] {
+            let packed = super::compress(&zeros, codec, 9).unwrap();
+            assert!(super::detect(&packed).contains(&codec), "{}", codec.name());
+        }
+    }
+}
@@ -0,0 +1,334 @@
+use std::ops::Range;
+
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum Change {
+    Equal,
+    Replace,
+    Delete,
+    Insert,
+}
+
+impl Change {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Change::Equal => "equal",
+            Change::Replace => "replace",
+            Change::Delete => "delete",
+            Change::Insert => "insert",
+        }
+    }
+}
+
+/// A run of bytes that is either the same in both buffers or differs between them
+#[derive(PartialEq, Eq, Hash, Clone, Debug)]
+pub struct Hunk {
+    pub change: Change,
+    pub a: Range<usize>,
+    pub b: Range<usize>,
+}
+
+/// Aligns `b` against `a` with Myers' algorithm, so inserted or deleted bytes
+/// do not make everything after them differ.
+///
+/// Once `deadline` passes the diff degrades to a coarser but still valid alignment.
+/// Adjacent differing runs are merged, so hunks alternate between equal and not.
+pub fn diff(a: &[u8], b: &[u8], deadline: Option<std::time::Instant>) -> Vec<Hunk> {
+    let ops = similar::capture_diff_slices_deadline(similar::Algorithm::Myers, a, b, deadline);
+    let mut hunks: Vec<Hunk> = Vec::new();
+    for op in ops {
+        let (tag, a_range, b_range) = op.as_tag_tuple();
+        if a_range.is_empty() && b_range.is_empty() {
+            continue;
+        }
+        let change = match tag {
+            similar::DiffTag::Equal => Change::Equal,
+            similar::DiffTag::Delete => Change::Delete,
+            similar::DiffTag::Insert => Change::Insert,
+            similar::DiffTag::Replace => Change::Replace,
+        };
+        match hunks.last_mut() {
+            Some(last) if last.change != Change::Equal && change != Change::Equal => {
+                last.a.end = a_range.end;
+                last.b.end = b_range.end;
+                last.change = match (last.a.is_empty(), last.b.is_empty()) {
+                    (true, _) => Change::Insert,
+                    (_, true) => Change::Delete,
+                    _ => Change::Replace,
+                };
+            }
+            _ => hunks.push(Hunk {
+                change,
+                a: a_range,
+                b: b_range,
+            }),
+        }
+    }
+    hunks
+}
+
+pub fn summary(hunks: &[Hunk], a_len: usize, b_len: usize) -> String {
+    let changed: Vec<&Hunk> = hunks.iter().filter(|h| h.change != Change::Equal).collect();
+    let removed: usize = changed.iter().map(|h| h.a.len()).sum();
+    let added: usize = changed.iter().map(|h| h.b.len()).sum();
+    let mut report = format!(
+        "A: {} bytes\nB: {} bytes\n{} differences, {} bytes removed, {} bytes added\n\n",
+        a_len,
+        b_len,
+        changed.len(),
+        removed,
+        added
+    );
+    report.push_str("change\tA offset\tA length\tB offset\tB length\n");
+    for hunk in changed {
+        report.push_str(&format!(
+            "{}\t{:08X}\t{}\t{:08X}\t{}\n",
+            hunk.change.name(),
+            hunk.a.start,
+            hunk.a.len(),
+            hunk.b.start,
+            hunk.b.len()
+        ));
+    }
+    report
+}
+
+/// Creates an IPS patch turning `a` into `b`.
+///
+/// IPS overwrites bytes in place and cannot shift data, so this compares byte for byte
+/// instead of using the aligned diff. When `b` is shorter, the common truncation
+/// extension (3-byte length after "EOF") is appended.
+pub fn ips(a: &[u8], b: &[u8]) -> Result<Vec<u8>, String> {
+    const MAX_OFFSET: usize = 0xFFFFFF;
+    // a record at this offset would read as the "EOF" marker
+    const EOF_OFFSET: usize = 0x454F46;
+    // merging gaps shorter than a record header makes the patch smaller
+    const MERGE_GAP: usize = 5;
+    if b.len() > MAX_OFFSET + 1 {
+        return Err(format!(
+            "IPS addresses at most 16 MiB, target is {} bytes",
+            b.len()
+        ));
+    }
+    let differs = |i: usize| a.get(i) != Some(&b[i]);
+    let mut runs: Vec<Range<usize>> = Vec::new();
+    let mut i = 0;
+    while i < b.len() {
+        if !differs(i) {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while i < b.len() && differs(i) {
+            i += 1;
+        }
+        match runs.last_mut() {
+            Some(last) if start - last.end <= MERGE_GAP => last.end = i,
+            _ => runs.push(start..i),
+        }
+    }
+    let mut out = b"PATCH".to_vec();
+    for run in runs {
+        let mut start = run.start;
+        if start == EOF_OFFSET {
+            start -= 1;
+        }
+        while start < run.end {
+            let end = run.end.min(start + 0xFFFF);
+            out.extend_from_slice(&(start as u32).to_be_bytes()[1..]);
+            out.extend_from_slice(&((end - start) as u16).to_be_bytes());
+            out.extend_from_slice(&b[start..end]);
+            start = end;
+            if start == EOF_OFFSET && start < run.end {
+                // back up one byte so the next record does not start at "EOF"
+                start -= 1;
+            }
+        }
+    }
+    out.extend_from_slice(b"EOF");
+    if b.len() < a.len() {
+        out.extend_from_slice(&(b.len() as u32).to_be_bytes()[1..]);
+    }
+    Ok(out)
+}
+
+fn bps_number(out: &mut Vec<u8>, mut value: u64) {
+    loop {
+        let x = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            out.push(0x80 | x);
+            break;
+        }
+        out.push(x);
+        value -= 1;
+    }
+}
+
+fn crc32(input: &[u8]) -> u32 {
+    let mut crc = flate2::Crc::new();
+    crc.update(input);
+    crc.sum()
+}
+
+/// Creates a BPS patch turning `a` into `b` from the aligned hunks,
+/// so moved data is copied from the source instead of stored again.
+pub fn bps(a: &[u8], b: &[u8], hunks: &[Hunk]) -> Vec<u8> {
+    const SOURCE_READ: u64 = 0;
+    const TARGET_READ: u64 = 1;
+    const SOURCE_COPY: u64 = 2;
+    let mut out = b"BPS1".to_vec();
+    bps_number(&mut out, a.len() as u64);
+    bps_number(&mut out, b.len() as u64);
+    // no metadata
+    bps_number(&mut out, 0);
+    let mut source_relative = 0usize;
+    for hunk in hunks {
+        if hunk.b.is_empty() {
+            continue;
+        }
+        let length = hunk.b.len() as u64;
+        if hunk.change != Change::Equal {
+            bps_number(&mut out, ((length - 1) << 2) | TARGET_READ);
+            out.extend_from_slice(&b[hunk.b.clone()]);
+        } else if hunk.a.start == hunk.b.start {
+            bps_number(&mut out, ((length - 1) << 2) | SOURCE_READ);
+        } else {
+            bps_number(&mut out, ((length - 1) << 2) | SOURCE_COPY);
+            let delta = hunk.a.start as i64 - source_relative as i64;
+            bps_number(&mut out, (delta.unsigned_abs() << 1) | (delta < 0) as u64);
+            source_relative = hunk.a.end;
+        }
+    }
+    out.extend_from_slice(&crc32(a).to_le_bytes());
+    out.extend_from_slice(&crc32(b).to_le_bytes());
+    let patch_crc = crc32(&out);
+    out.extend_from_slice(&patch_crc.to_le_bytes());
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::Change;
+
+    fn apply_ips(source: &[u8], patch: &[u8]) -> Vec<u8> {
+        assert!(patch.starts_with(b"PATCH"));
+        let mut out = source.to_vec();
+        let mut i = 5;
+        while &patch[i..i + 3] != b"EOF" {
+            let offset = u32::from_be_bytes([0, patch[i], patch[i + 1], patch[i + 2]]) as usize;
+            let size = u16::from_be_bytes([patch[i + 3], patch[i + 4]]) as usize;
+            assert_ne!(size, 0, "RLE records are never written");
+            let data = &patch[i + 5..i + 5 + size];
+            if out.len() < offset + size {
+                out.resize(offset + size, 0);
+            }
+            out[offset..offset + size].copy_from_slice(data);
+            i += 5 + size;
+        }
+        if let Some(len) = patch.get(i + 3..i + 6) {
+            out.truncate(u32::from_be_bytes([0, len[0], len[1], len[2]]) as usize);
+        }
+        out
+    }
+
+    fn read_number(patch: &[u8], i: &mut usize) -> u64 {
+        let (mut data, mut shift) = (0u64, 1u64);
+        loop {
+            let x = patch[*i] as u64;
+            *i += 1;
+            data += (x & 0x7f) * shift;
+            if x & 0x80 != 0 {
+                return data;
+            }
+            shift <<= 7;
+            data += shift;
+        }
+    }
+
+    fn apply_bps(source: &[u8], patch: &[u8]) -> Vec<u8> {
+        assert!(patch.starts_with(b"BPS1"));
+        let mut i = 4;
+        assert_eq!(read_number(patch, &mut i) as usize, source.len());
+        let target_len = read_number(patch, &mut i) as usize;
+        i += read_number(patch, &mut i) as usize;
+        let mut out = Vec::new();
+        let (mut source_rel, mut target_rel) = (0i64, 0i64);
+        while i < patch.len() - 12 {
+            let data = read_number(patch, &mut i);
+            let length = (data >> 2) as usize + 1;
+            match data & 3 {
+                0 => out.extend_from_slice(&source[out.len()..out.len() + length]),
+                1 => {
+                    out.extend_from_slice(&patch[i..i + length]);
+                    i += length;
+                }
+                2 | 3 => {
+                    let n = read_number(patch, &mut i);
+                    let delta = if n & 1 == 1 {
+                        -((n >> 1) as i64)
+                    } else {
+                        (n >> 1) as i64
+                    };
+                    let from_source = data & 3 == 2;
+                    let rel = if from_source {
+                        &mut source_rel
+                    } else {
+                        &mut target_rel
+                    };
+                    *rel += delta;
+                    for _ in 0..length {
+                        let b = if from_source {
+                            source[*rel as usize]
+                        } else {
+                            out[*rel as usize]
+                        };
+                        out.push(b);
+                        *rel += 1;
+                    }
+                }
+                _ => unreachable!(),
+            }
+        }
+        assert_eq!(out.len(), target_len);
+        assert_eq!(
+            super::crc32(&patch[..patch.len() - 4]).to_le_bytes(),
+            patch[patch.len() - 4..]
+        );
+        out
+    }
+
+    fn sample() -> (Vec<u8>, Vec<u8>) {
+        let a: Vec<u8> = (0..2000u32).map(|i| (i * 7 % 251) as u8).collect();
+        let mut b = a.clone();
+        b.splice(100..100, b"inserted".iter().copied());
+        b.drain(900..950);
+        b[1500] ^= 0xff;
+        b.extend_from_slice(b"tail");
+        (a, b)
+    }
+
+    #[test]
+    fn test_alignment() {
+        let (a, b) = sample();
+        let hunks = super::diff(&a, &b, None);
+        let changed: Vec<&super::Hunk> =
+            hunks.iter().filter(|h| h.change != Change::Equal).collect();
+        assert_eq!(changed.len(), 4);
+        assert_eq!(changed[0].change, Change::Insert);
+        assert_eq!(changed[0].b, 100..108);
+        assert_eq!(changed[1].change, Change::Delete);
+        assert_eq!(changed[1].a.len(), 50);
+        assert_eq!(changed[3].change, Change::Insert);
+    }
+    #[test]
+    fn test_patches() {
+        let (a, b) = sample();
+        let hunks = super::diff(&a, &b, None);
+        assert_eq!(apply_bps(&a, &super::bps(&a, &b, &hunks)), b);
+        assert_eq!(apply_ips(&a, &super::ips(&a, &b).unwrap()), b);
+        let short = &a[..1000];
+        assert_eq!(apply_ips(&a, &super::ips(&a, short).unwrap()), short);
+        let hunks = super::diff(&b, &a, None);
+        assert_eq!(apply_bps(&b, &super::bps(&b, &a, &hunks)), a);
+    }
+}
@@ -0,0 +1,556 @@
+use base64::prelude::*;
+
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum Format {
+    CArray,
+    Rust,
+    Python,
+    Go,
+    Java,
+    XxdI,
+    Xxd,
+    Hexdump,
+    IntelHex,
+    SRecord,
+    Pem,
+}
+
+pub const FORMATS: [Format; 11] = [
+    Format::CArray,
+    Format::Rust,
+    Format::Python,
+    Format::Go,
+    Format::Java,
+    Format::XxdI,
+    Format::Xxd,
+    Format::Hexdump,
+    Format::IntelHex,
+    Format::SRecord,
+    Format::Pem,
+];
+
+impl Format {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Format::CArray => "C array",
+            Format::Rust => "Rust &[u8]",
+            Format::Python => "Python bytes",
+            Format::Go => "Go []byte",
+            Format::Java => "Java byte[]",
+            Format::XxdI => "xxd -i",
+            Format::Xxd => "xxd",
+            Format::Hexdump => "hexdump -C",
+            Format::IntelHex => "Intel HEX",
+            Format::SRecord => "Motorola S-record",
+            Format::Pem => "PEM",
+        }
+    }
+    /// Whether [`import`] can read the format back
+    pub fn importable(&self) -> bool {
+        matches!(
+            self,
+            Format::Xxd | Format::Hexdump | Format::IntelHex | Format::SRecord | Format::Pem
+        )
+    }
+}
+
+/// Renders the input in the format. `name` is the variable name for source code,
+/// and the label for PEM.
+pub fn export(input: &[u8], format: Format, name: &str) -> String {
+    match format {
+        Format::CArray => format!(
+            "const unsigned char {}[{}] = {{\n{}}};\n",
+            name,
+            input.len(),
+            byte_list(input, 16, "    ", |b| format!("0x{:02x}", b))
+        ),
+        Format::Rust => format!(
+            "pub const {}: &[u8] = &[\n{}];\n",
+            name.to_uppercase(),
+            byte_list(input, 16, "    ", |b| format!("0x{:02x}", b))
+        ),
+        Format::Python => {
+            let mut out = format!("{} = (\n", name);
+            for line in input.chunks(16) {
+                let escaped: String = line.iter().map(|b| format!("\\x{:02x}", b)).collect();
+                out.push_str(&format!("    b\"{}\"\n", escaped));
+            }
+            if input.is_empty() {
+                out.push_str("    b\"\"\n");
+            }
+            out.push_str(")\n");
+            out
+        }
+        Format::Go => format!(
+            "var {} = []byte{{\n{}}}\n",
+            name,
+            byte_list(input, 16, "\t", |b| format!("0x{:02x}", b))
+        ),
+        Format::Java => format!(
+            "byte[] {} = {{\n{}}};\n",
+            name,
+            // byte is signed in Java, so values above 0x7f need a cast
+            byte_list(input, 12, "    ", |b| if b > 0x7f {
+                format!("(byte) 0x{:02x}", b)
+            } else {
+                format!("0x{:02x}", b)
+            })
+        ),
+        Format::XxdI => format!(
+            "unsigned char {}[] = {{\n{}}};\nunsigned int {}_len = {};\n",
+            name,
+            byte_list(input, 12, "  ", |b| format!("0x{:02x}", b)),
+            name,
+            input.len()
+        ),
+        Format::Xxd => xxd(input),
+        Format::Hexdump => hexdump(input),
+        Format::IntelHex => intel_hex(input),
+        Format::SRecord => srecord(input, name),
+        Format::Pem => {
+            let label = name.to_uppercase();
+            let body = BASE64_STANDARD.encode(input);
+            let mut out = format!("-----BEGIN {}-----\n", label);
+            for line in body.as_bytes().chunks(64) {
+                out.push_str(&String::from_utf8_lossy(line));
+                out.push('\n');
+            }
+            out.push_str(&format!("-----END {}-----\n", label));
+            out
+        }
+    }
+}
+
+/// Comma separated bytes, `per_line` to a line, every line indented and ending in a comma
+fn byte_list(input: &[u8], per_line: usize, indent: &str, item: impl Fn(u8) -> String) -> String {
+    let mut out = String::new();
+    for line in input.chunks(per_line) {
+        out.push_str(indent);
+        out.push_str(
+            &line
+                .iter()
+                .map(|&b| item(b))
+                .collect::<Vec<String>>()
+                .join(", "),
+        );
+        out.push_str(",\n");
+    }
+    out
+}
+
+fn printable(b: u8) -> char {
+    if (0x20..=0x7e).contains(&b) {
+        b as char
+    } else {
+        '.'
+    }
+}
+
+fn xxd(input: &[u8]) -> String {
+    let mut out = String::new();
+    for (i, line) in input.chunks(16).enumerate() {
+        let hex: Vec<String> = line.chunks(2).map(hex::encode).collect();
+        out.push_str(&format!(
+            "{:08x}: {:<40} {}\n",
+            i * 16,
+            hex.join(" "),
+            line.iter().map(|&b| printable(b)).collect::<String>()
+        ));
+    }
+    out
+}
+
+fn hexdump(input: &[u8]) -> String {
+    let mut out = String::new();
+    let mut previous: Option<&[u8]> = None;
+    let mut squeezed = false;
+    for (i, line) in input.chunks(16).enumerate() {
+        // like hexdump, repeated full lines are replaced by a single "*"
+        if line.len() == 16 && previous == Some(line) {
+            if !squeezed {
+                out.push_str("*\n");
+                squeezed = true;
+            }
+            continue;
+        }
+        squeezed = false;
+        previous = Some(line);
+        let mut hex = String::new();
+        for (j, b) in line.iter().enumerate() {
+            if j == 8 {
+                hex.push(' ');
+            }
+            hex.push_str(&format!("{:02x} ", b));
+        }
+        out.push_str(&format!(
+            "{:08x}  {:<49} |{}|\n",
+            i * 16,
+            hex,
+            line.iter().map(|&b| printable(b)).collect::<String>()
+        ));
+    }
+    out.push_str(&format!("{:08x}\n", input.len()));
+    out
+}
+
+fn record_checksum(bytes: &[u8]) -> u8 {
+    bytes.iter().fold(0u8, |sum, b| sum.wrapping_add(*b))
+}
+
+fn intel_record(out: &mut String, address: u16, kind: u8, data: &[u8]) {
+    let mut record = vec![data.len() as u8];
+    record.extend_from_slice(&address.to_be_bytes());
+    record.push(kind);
+    record.extend_from_slice(data);
+    let checksum = record_checksum(&record).wrapping_neg();
+    out.push(':');
+    out.push_str(&hex::encode_upper(&record));
+    out.push_str(&format!("{:02X}\n", checksum));
+}
+
+fn intel_hex(input: &[u8]) -> String {
+    let mut out = String::new();
+    let mut upper = 0;
+    for (i, line) in input.chunks(16).enumerate() {
+        let address = i * 16;
+        // extended linear address record whenever the upper 16 bits change
+        if address >> 16 != upper {
+            upper = address >> 16;
+            intel_record(&mut out, 0, 4, &(upper as u16).to_be_bytes());
+        }
+        intel_record(&mut out, address as u16, 0, line);
+    }
+    intel_record(&mut out, 0, 1, &[]);
+    out
+}
+
+fn srecord_line(out: &mut String, kind: u8, address: u32, address_len: usize, data: &[u8]) {
+    let mut record = vec![(address_len + data.len() + 1) as u8];
+    record.extend_from_slice(&address.to_be_bytes()[4 - address_len..]);
+    record.extend_from_slice(data);
+    let checksum = !record_checksum(&record);
+    out.push_str(&format!("S{}", kind));
+    out.push_str(&hex::encode_upper(&record));
+    out.push_str(&format!("{:02X}\n", checksum));
+}
+
+fn srecord(input: &[u8], header: &str) -> String {
+    // the smallest address width that reaches the end of the data
+    let (data_kind, end_kind, address_len) = match input.len() {
+        0..=0x10000 => (1, 9, 2),
+        0x10001..=0x1000000 => (2, 8, 3),
+        _ => (3, 7, 4),
+    };
+    let mut out = String::new();
+    // a record holds at most 252 bytes after a 2 byte address, so a long header takes several
+    let header = header.as_bytes();
+    if header.is_empty() {
+        srecord_line(&mut out, 0, 0, 2, header);
+    }
+    for part in header.chunks(252) {
+        srecord_line(&mut out, 0, 0, 2, part);
+    }
+    let mut count = 0u32;
+    for (i, line) in input.chunks(16).enumerate() {
+        srecord_line(&mut out, data_kind, (i * 16) as u32, address_len, line);
+        count += 1;
+    }
+    if count <= 0xFFFF {
+        srecord_line(&mut out, 5, count, 2, &[]);
+    } else {
+        srecord_line(&mut out, 6, count, 3, &[]);
+    }
+    srecord_line(&mut out, end_kind, 0, address_len, &[]);
+    out
+}
+
+/// Bytes with their load address, as read from an address-based format
+pub struct Imported {
+    /// Address of the first byte, non-zero for firmware images that load elsewhere
+    pub base: u64,
+    pub data: Vec<u8>,
+}
+
+/// Largest buffer an import builds, gaps included
+const MAX_SPAN: u64 = 256 * 1024 * 1024;
+
+/// Collects (address, data) pieces into one buffer starting at the lowest address.
+/// Gaps are filled with 0xFF, the value of erased flash.
+fn assemble(pieces: Vec<(u64, Vec<u8>)>) -> Result<Imported, String> {
+    let base = pieces.iter().map(|p| p.0).min().unwrap_or(0);
+    let mut end = 0;
+    for (address, piece) in &pieces {
+        let piece_end = address.checked_add(piece.len() as u64).ok_or(format!(
+            "Data at 0x{:X} runs past the end of memory",
+            address
+        ))?;
+        end = end.max(piece_end);
+    }
+    if end - base > MAX_SPAN {
+        return Err(format!(
+            "Data spans 0x{:X} to 0x{:X}, which is too large to load",
+            base, end
+        ));
+    }
+    let mut data = vec![0xFF; (end - base) as usize];
+    for (address, piece) in pieces {
+        let start = (address - base) as usize;
+        data[start..start + piece.len()].copy_from_slice(&piece);
+    }
+    Ok(Imported { base, data })
+}
+
+/// Reads text produced by one of the importable formats back into bytes
+pub fn import(text: &str, format: Format) -> Result<Imported, String> {
+    match format {
+        Format::Xxd => import_xxd(text),
+        Format::Hexdump => import_hexdump(text),
+        Format::IntelHex => import_intel_hex(text),
+        Format::SRecord => import_srecord(text),
+        Format::Pem => import_pem(text),
+        _ => Err(format!("{} cannot be imported", format.name())),
+    }
+}
+
+/// Decodes the hex digits of a record line, checking them for the line number in errors
+fn record_bytes(hex_text: &str, number: usize) -> Result<Vec<u8>, String> {
+    hex::decode(hex_text).map_err(|e| format!("Line {}: {}", number, e))
+}
+
+fn import_intel_hex(text: &str) -> Result<Imported, String> {
+    let mut pieces = Vec::new();
+    let mut upper = 0u64;
+    for (i, line) in text.lines().enumerate() {
+        let number = i + 1;
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let record = line
+            .strip_prefix(':')
+            .ok_or(format!("Line {}: record does not start with ':'", number))?;
+        let bytes = record_bytes(record, number)?;
+        if bytes.len() < 5 || bytes.len() != bytes[0] as usize + 5 {
+            return Err(format!("Line {}: record length does not match", number));
+        }
+        if record_checksum(&bytes) != 0 {
+            return Err(format!("Line {}: checksum mismatch", number));
+        }
+        let address = u16::from_be_bytes([bytes[1], bytes[2]]) as u64;
+        let data = &bytes[4..bytes.len() - 1];
+        match bytes[3] {
+            0 => pieces.push((upper + address, data.to_vec())),
+            1 => break,
+            2 if data.len() == 2 => upper = (u16::from_be_bytes([data[0], data[1]]) as u64) << 4,
+            4 if data.len() == 2 => upper = (u16::from_be_bytes([data[0], data[1]]) as u64) << 16,
+            // start addresses do not affect the data
+            3 | 5 => {}
+            kind => {
+                return Err(format!(
+                    "Line {}: unsupported record type {:02X}",
+                    number, kind
+                ))
+            }
+        }
+    }
+    assemble(pieces)
+}
+
+fn import_srecord(text: &str) -> Result<Imported, String> {
+    let mut pieces = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let number = i + 1;
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let kind = line
+            .strip_prefix('S')
+            .and_then(|l| l.chars().next())
+            .ok_or(format!("Line {}: record does not start with 'S'", number))?;
+        let digits = line
+            .get(2..)
+            .ok_or(format!("Line {}: unknown record type S{}", number, kind))?;
+        let bytes = record_bytes(digits, number)?;
+        if bytes.is_empty() || bytes.len() != bytes[0] as usize + 1 {
+            return Err(format!("Line {}: record length does not match", number));
+        }
+        if record_checksum(&bytes) != 0xFF {
+            return Err(format!("Line {}: checksum mismatch", number));
+        }
+        let address_len = match kind {
+            '1' => 2,
+            '2' => 3,
+            '3' => 4,
+            '0' | '5' | '6' | '7' | '8' | '9' => continue,
+            _ => return Err(format!("Line {}: unknown record type S{}", number, kind)),
+        };
+        if bytes.len() < address_len + 2 {
+            return Err(format!("Line {}: record is too short", number));
+        }
+        let address = bytes[1..=address_len]
+            .iter()
+            .fold(0u64, |a, &b| a << 8 | b as u64);
+        pieces.push((address, bytes[address_len + 1..bytes.len() - 1].to_vec()));
+    }
+    assemble(pieces)
+}
+
+fn import_xxd(text: &str) -> Result<Imported, String> {
+    let mut pieces = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let number = i + 1;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let (offset, rest) = line
+            .split_once(':')
+            .ok_or(format!("Line {}: missing offset", number))?;
+        let offset = u64::from_str_radix(offset.trim(), 16)
+            .map_err(|e| format!("Line {}: bad offset: {}", number, e))?;
+        // the hex columns end where the two spaces before the text column start
+        let rest = rest.strip_prefix(' ').unwrap_or(rest);
+        let hex_part = rest.split("  ").next().unwrap_or_default();
+        let digits: String = hex_part.split_whitespace().collect();
+        pieces.push((offset, record_bytes(&digits, number)?));
+    }
+    // a dump made with `xxd -s` starts at its offset, which is kept as the base
+    assemble(pieces)
+}
+
+fn import_hexdump(text: &str) -> Result<Imported, String> {
+    let mut data: Vec<u8> = Vec::new();
+    let mut repeat: Option<Vec<u8>> = None;
+    let mut last_line: Vec<u8> = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let number = i + 1;
+        let line = line.trim_end();
+        if line.is_empty() {
+            continue;
+        }
+        if line == "*" {
+            repeat = Some(last_line.clone());
+            continue;
+        }
+        let hex_part = line.split('|').next().unwrap_or_default();
+        let mut tokens = hex_part.split_whitespace();
+        let offset = tokens
+            .next()
+            .and_then(|t| usize::from_str_radix(t, 16).ok())
+            .ok_or(format!("Line {}: missing offset", number))?;
+        // a "*" line stands for copies of the line before it, up to this offset
+        if let Some(pattern) = repeat.take() {
+            if pattern.is_empty() {
+                return Err(format!("Line {}: \"*\" without a line to repeat", number));
+            }
+            if offset as u64 > MAX_SPAN {
+                return Err(format!(
+                    "Line {}: offset {:08x} is too large to load",
+                    number, offset
+                ));
+            }
+            while data.len() < offset {
+                data.extend_from_slice(&pattern);
+            }
+            data.truncate(offset);
+        }
+        if offset != data.len() {
+            return Err(format!(
+                "Line {}: offset {:08x} does not follow {:08x}",
+                number,
+                offset,
+                data.len()
+            ));
+        }
+        let digits: String = tokens.collect();
+        last_line = record_bytes(&digits, number)?;
+        data.extend_from_slice(&last_line);
+    }
+    Ok(Imported { base: 0, data })
+}
+
+fn import_pem(text: &str) -> Result<Imported, String> {
+    let mut lines = text.lines().map(str::trim);
+    lines
+        .by_ref()
+        .find(|l| l.starts_with("-----BEGIN "))
+        .ok_or("Missing \"-----BEGIN\" line".to_string())?;
+    let mut body = String::new();
+    for line in lines {
+        if line.starts_with("-----END ") {
+            let data = BASE64_STANDARD.decode(&body).map_err(|e| e.to_string())?;
+            return Ok(Imported { base: 0, data });
+        }
+        // RFC 1421 headers such as Proc-Type are not part of the data
+        if !line.contains(':') {
+            body.push_str(line);
+        }
+    }
+    Err("Missing \"-----END\" line".to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::Format;
+
+    #[test]
+    fn test_formats() {
+        let data = b"Hello, World!\n\x00\xff";
+        assert_eq!(
+            super::export(data, Format::Xxd, "data"),
+            "00000000: 4865 6c6c 6f2c 2057 6f72 6c64 210a 00ff  Hello, World!...\n"
+        );
+        assert_eq!(
+            super::export(&data[..5], Format::IntelHex, "data"),
+            ":0500000048656C6C6F07\n:00000001FF\n"
+        );
+        assert_eq!(
+            super::export(&data[..5], Format::XxdI, "data"),
+            "unsigned char data[] = {\n  0x48, 0x65, 0x6c, 0x6c, 0x6f,\n};\nunsigned int data_len = 5;\n"
+        );
+        assert!(super::export(&data[..5], Format::SRecord, "HDR").starts_with("S00600004844521B\n"));
+    }
+    #[test]
+    fn test_roundtrip() {
+        let mut data: Vec<u8> = (0..=255u8).collect();
+        data.extend_from_slice(&[0x55; 64]);
+        data.extend_from_slice(b"tail");
+        for format in super::FORMATS.into_iter().filter(|f| f.importable()) {
+            let text = super::export(&data, format, "blob");
+            let imported = super::import(&text, format).unwrap();
+            assert_eq!(
+                (imported.base, imported.data),
+                (0, data.clone()),
+                "{}",
+                format.name()
+            );
+        }
+        let big = vec![0xA5; 0x10010];
+        let imported = super::import(&super::export(&big, Format::IntelHex, ""), Format::IntelHex);
+        assert_eq!(imported.unwrap().data, big);
+    }
+    #[test]
+    fn test_import_errors() {
+        assert!(super::import(":0500000048656C6C6FDF\n", Format::IntelHex).is_err());
+        let firmware = super::import(
+            ":020000040800F2\n:0100000042BD\n:00000001FF\n",
+            Format::IntelHex,
+        );
+        let firmware = firmware.unwrap();
+        assert_eq!((firmware.base, firmware.data), (0x08000000, vec![0x42]));
+        let skipped = super::import("7ffffff0: 4142  AB\n", Format::Xxd).unwrap();
+        assert_eq!((skipped.base, skipped.data), (0x7ffffff0, b"AB".to_vec()));
+        assert!(super::import("ffffffffffffffff: 41  A\n", Format::Xxd).is_err());
+        assert!(super::import("S\u{e9}0300FC\n", Format::SRecord).is_err());
+        let forged = "00000000  41 41  |AA|\n*\n7fffffff  41  |A|\n";
+        assert!(super::import(forged, Format::Hexdump).is_err());
+    }
+    #[test]
+    fn test_long_header() {
+        let header = "h".repeat(300);
+        let text = super::export(b"data", Format::SRecord, &header);
+        assert_eq!(text.lines().filter(|l| l.starts_with("S0")).count(), 2);
+        let imported = super::import(&text, Format::SRecord).unwrap();
+        assert_eq!(imported.data, b"data");
+    }
+}
@@ -0,0 +1,295 @@
+use super::{checksums, hashes};
+
+type DigestFn = fn(&[u8]) -> String;
+
+/// Digests of `hashes.rs`, named as on the Hash buttons
+const HASHES: [(&str, DigestFn); 55] = [
+    ("MD2", hashes::md2),
+    ("MD4", hashes::md4),
+    ("MD5", hashes::md5),
+    ("RIPEMD128", hashes::ripemd128),
+    ("RIPEMD160", hashes::ripemd160),
+    ("RIPEMD256", hashes::ripemd256),
+    ("RIPEMD320", hashes::ripemd320),
+    ("SHA1", hashes::sha1),
+    ("SHA224", hashes::sha224),
+    ("SHA256", hashes::sha256),
+    ("SHA384", hashes::sha384),
+    ("SHA512", hashes::sha512),
+    ("SHA512-224", hashes::sha512_224),
+    ("SHA512-256", hashes::sha512_256),
+    ("SHA3-224", hashes::sha3_224),
+    ("SHA3-256", hashes::sha3_256),
+    ("SHA3-384", hashes::sha3_384),
+    ("SHA3-512", hashes::sha3_512),
+    ("KECCAK224", hashes::keccak224),
+    ("KECCAK256", hashes::keccak256),
+    ("KECCAK384", hashes::keccak384),
+    ("KECCAK512", hashes::keccak512),
+    ("KECCAK256FULL", hashes::keccak256full),
+    ("SHABAL192", hashes::shabal192),
+    ("SHABAL224", hashes::shabal224),
+    ("SHABAL256", hashes::shabal256),
+    ("SHABAL384", hashes::shabal384),
+    ("SHABAL512", hashes::shabal512),
+    ("FSB160", hashes::fsb160),
+    ("FSB224", hashes::fsb224),
+    ("FSB256", hashes::fsb256),
+    ("FSB384", hashes::fsb384),
+    ("FSB512", hashes::fsb512),
+    ("GOST94-UA", hashes::gost94ua),
+    ("GOST94-CryptoPro", hashes::gost94cryptopro),
+    ("GOST94-Test", hashes::gost94test),
+    ("GOST94-S2015", hashes::gost94s2015),
+    ("GROESTL224", hashes::groestl224),
+    ("GROESTL256", hashes::groestl256),
+    ("GROESTL384", hashes::groestl384),
+    ("GROESTL512", hashes::groestl512),
+    ("JH224", hashes::jh224),
+    ("JH256", hashes::jh256),
+    ("JH384", hashes::jh384),
+    ("JH512", hashes::jh512),
+    ("Ascon", hashes::asconhash),
+    ("AsconA", hashes::asconahash),
+    ("Blake3", hashes::blake3),
+    ("Belt", hashes::belt),
+    ("SM3", hashes::sm3),
+    ("Tiger", hashes::tiger),
+    ("Tiger2", hashes::tiger2),
+    ("Streebog-256", hashes::streebog256),
+    ("Streebog-512", hashes::streebog512),
+    ("Whirlpool", hashes::whirlpool),
+];
+
+const CHECKSUMS: [(&str, DigestFn); 7] = [
+    ("Adler32", checksums::adler32),
+    ("Fletcher16", checksums::fletcher16),
+    ("Fletcher32", checksums::fletcher32),
+    ("FNV1-32", checksums::fnv1_32),
+    ("FNV1a-32", checksums::fnv1a_32),
+    ("FNV1-64", checksums::fnv1_64),
+    ("FNV1a-64", checksums::fnv1a_64),
+];
+
+const SEEDED: [(&str, checksums::SeededFn); 7] = [
+    ("xxHash32", checksums::xxh32),
+    ("xxHash64", checksums::xxh64),
+    ("XXH3-64", checksums::xxh3_64),
+    ("XXH3-128", checksums::xxh3_128),
+    ("Murmur3-32", checksums::murmur3_32),
+    ("Murmur3-x86-128", checksums::murmur3_x86_128),
+    ("Murmur3-x64-128", checksums::murmur3_x64_128),
+];
+
+/// Crypt(3) and PHC prefixes, longest first so `$2b$` is not read as a shorter prefix
+const CRYPT_PREFIXES: [(&str, &str); 16] = [
+    ("$argon2id$", "Argon2id (PHC)"),
+    ("$argon2i$", "Argon2i (PHC)"),
+    ("$argon2d$", "Argon2d (PHC)"),
+    ("$pbkdf2-sha256$", "PBKDF2-SHA256 (PHC)"),
+    ("$pbkdf2-sha512$", "PBKDF2-SHA512 (PHC)"),
+    ("$pbkdf2$", "PBKDF2-SHA1 (PHC)"),
+    ("$scrypt$", "scrypt (PHC)"),
+    ("$apr1$", "Apache MD5-crypt"),
+    ("$sha1$", "SHA1-crypt (NetBSD)"),
+    ("$md5", "Sun MD5-crypt"),
+    ("$gy$", "gost-yescrypt"),
+    ("$y$", "yescrypt"),
+    ("$7$", "scrypt (crypt)"),
+    ("$1$", "MD5-crypt"),
+    ("$5$", "SHA256-crypt"),
+    ("$6$", "SHA512-crypt"),
+];
+
+#[derive(Clone, Copy)]
+enum Algorithm {
+    Hash(DigestFn),
+    Crc(checksums::CrcParams),
+    /// Seeded hashes are tried with seed 0
+    Seeded(checksums::SeededFn),
+    /// MD4 of the UTF-16LE password
+    Ntlm,
+    /// SHA-1 of the binary SHA-1
+    MySql,
+    /// Crypt strings carry their own salt and parameters
+    Salted,
+}
+
+#[derive(Clone)]
+pub struct Candidate {
+    pub name: String,
+    algorithm: Algorithm,
+}
+
+impl Candidate {
+    fn new(name: &str, algorithm: Algorithm) -> Self {
+        Self {
+            name: name.to_string(),
+            algorithm,
+        }
+    }
+    /// Lowercase hex digest of the input
+    pub fn compute(&self, input: &[u8]) -> String {
+        match self.algorithm {
+            Algorithm::Hash(f) => f(input),
+            Algorithm::Crc(params) => checksums::crc_hex(input, &params),
+            Algorithm::Seeded(f) => f(input, 0),
+            Algorithm::Ntlm => {
+                let utf16: Vec<u8> = String::from_utf8_lossy(input)
+                    .encode_utf16()
+                    .flat_map(|c| c.to_le_bytes())
+                    .collect();
+                hashes::md4(&utf16)
+            }
+            Algorithm::MySql => {
+                let inner = hex::decode(hashes::sha1(input)).unwrap_or_default();
+                hashes::sha1(&inner)
+            }
+            Algorithm::Salted => String::new(),
+        }
+    }
+}
+
+/// Every algorithm that produces a bare digest
+fn catalogue() -> Vec<Candidate> {
+    let mut all: Vec<Candidate> = HASHES
+        .iter()
+        .chain(CHECKSUMS.iter())
+        .map(|(name, f)| Candidate::new(name, Algorithm::Hash(*f)))
+        .collect();
+    all.extend(
+        SEEDED
+            .iter()
+            .map(|(name, f)| Candidate::new(&format!("{} (seed 0)", name), Algorithm::Seeded(*f))),
+    );
+    all.extend(
+        checksums::CRC_CATALOGUE
+            .iter()
+            .map(|(name, params, _)| Candidate::new(name, Algorithm::Crc(*params))),
+    );
+    all.push(Candidate::new("NTLM", Algorithm::Ntlm));
+    all
+}
+
+pub struct Identified {
+    /// How the text was read, e.g. "hex, 64 digits"
+    pub format: String,
+    /// Lowercase hex digest to compare against, None for salted crypt strings
+    pub digest: Option<String>,
+    pub candidates: Vec<Candidate>,
+    pub note: Option<String>,
+}
+
+/// Lists algorithms that could have produced the text, by its format and length.
+///
+/// Hex is preferred over Base64 when the text is valid as both.
+pub fn identify(text: &str) -> Result<Identified, String> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Err("Nothing to identify".to_string());
+    }
+    if text.starts_with('$') {
+        return identify_crypt(text);
+    }
+    if let Some(rest) = text.strip_prefix('*') {
+        if rest.len() == 40 && hex::decode(rest).is_ok() {
+            return Ok(Identified {
+                format: "MySQL password hash".to_string(),
+                digest: Some(rest.to_lowercase()),
+                candidates: vec![Candidate::new("MySQL 4.1+", Algorithm::MySql)],
+                note: None,
+            });
+        }
+    }
+    let digits: String = text
+        .trim_start_matches("0x")
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != ':')
+        .collect::<String>()
+        .to_lowercase();
+    let (format, digest) = if !digits.is_empty() && digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        (format!("hex, {} digits", digits.len()), digits)
+    } else {
+        let bytes = super::basic::base64_decode(text)
+            .map_err(|_| "Not hex, Base64 or a crypt string".to_string())?;
+        (format!("Base64, {} bytes", bytes.len()), hex::encode(bytes))
+    };
+    let candidates: Vec<Candidate> = catalogue()
+        .into_iter()
+        .filter(|c| c.compute(b"").len() == digest.len())
+        .collect();
+    let note = candidates
+        .is_empty()
+        .then(|| "No known algorithm has this output length; it may be truncated".to_string());
+    Ok(Identified {
+        format,
+        digest: Some(digest),
+        candidates,
+        note,
+    })
+}
+
+fn identify_crypt(text: &str) -> Result<Identified, String> {
+    let name = if ["$2a$", "$2b$", "$2x$", "$2y$"]
+        .iter()
+        .any(|p| text.starts_with(p))
+    {
+        "bcrypt"
+    } else {
+        CRYPT_PREFIXES
+            .iter()
+            .find(|(prefix, _)| text.starts_with(prefix))
+            .map(|(_, name)| *name)
+            .ok_or("Unknown crypt prefix".to_string())?
+    };
+    let verifiable = name == "bcrypt" || name.ends_with("(PHC)");
+    Ok(Identified {
+        format: "crypt string".to_string(),
+        digest: None,
+        candidates: vec![Candidate::new(name, Algorithm::Salted)],
+        note: Some(if verifiable {
+            "Salted hash, check a password with Verify in the KDF category".to_string()
+        } else {
+            "Salted hash, not supported for verification".to_string()
+        }),
+    })
+}
+
+/// Names of the candidates whose digest of `input` equals the identified digest
+pub fn find_matches(input: &[u8], identified: &Identified) -> Vec<String> {
+    let Some(digest) = &identified.digest else {
+        return Vec::new();
+    };
+    identified
+        .candidates
+        .iter()
+        .filter(|c| &c.compute(input) == digest)
+        .map(|c| c.name.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn test_identify() {
+        let md5 = super::identify("5d41402abc4b2a76b9719d911017c592").unwrap();
+        let names: Vec<&str> = md5.candidates.iter().map(|c| c.name.as_str()).collect();
+        assert!(names.contains(&"MD5") && names.contains(&"NTLM") && names.contains(&"MD4"));
+        assert!(!names.contains(&"SHA1"));
+        assert_eq!(super::find_matches(b"hello", &md5), vec!["MD5"]);
+        // NTLM of "password"
+        let ntlm = super::identify("8846F7EAEE8FB117AD06BDD830B7586C").unwrap();
+        assert_eq!(super::find_matches(b"password", &ntlm), vec!["NTLM"]);
+        // SHA-256 of "hello" in Base64
+        let b64 = super::identify("LPJNul+wow4m6DsqxbninhsWHlwfp0JecwQzYpOLmCQ=").unwrap();
+        assert_eq!(b64.format, "Base64, 32 bytes");
+        assert_eq!(super::find_matches(b"hello", &b64), vec!["SHA256"]);
+        let crc = super::identify("cbf43926").unwrap();
+        assert!(super::find_matches(b"123456789", &crc).contains(&"CRC-32".to_string()));
+        let mysql = super::identify("*2470C0C06DEE42FD1618BB99005ADCA2EC9D1E19").unwrap();
+        assert_eq!(super::find_matches(b"password", &mysql), vec!["MySQL 4.1+"]);
+        let bcrypt = super::identify("$2b$12$abc").unwrap();
+        assert_eq!(bcrypt.candidates[0].name, "bcrypt");
+        assert!(super::identify("not a digest!").is_err());
+    }
+}
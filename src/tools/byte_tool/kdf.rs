@@ -0,0 +1,516 @@
+use argon2::password_hash::{
+    Decimal, Ident, Output, ParamsString, PasswordHash, PasswordVerifier, SaltString,
+};
+use digest::core_api::BlockSizeUser;
+use digest::Digest;
+
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum Kdf {
+    Pbkdf2,
+    Scrypt,
+    Argon2id,
+    Argon2i,
+    Argon2d,
+    Bcrypt,
+    Hkdf,
+}
+
+pub const KDFS: [Kdf; 7] = [
+    Kdf::Pbkdf2,
+    Kdf::Scrypt,
+    Kdf::Argon2id,
+    Kdf::Argon2i,
+    Kdf::Argon2d,
+    Kdf::Bcrypt,
+    Kdf::Hkdf,
+];
+
+impl Kdf {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Kdf::Pbkdf2 => "PBKDF2",
+            Kdf::Scrypt => "scrypt",
+            Kdf::Argon2id => "Argon2id",
+            Kdf::Argon2i => "Argon2i",
+            Kdf::Argon2d => "Argon2d",
+            Kdf::Bcrypt => "bcrypt",
+            Kdf::Hkdf => "HKDF",
+        }
+    }
+    /// Whether the KDF is built on a selectable digest
+    pub fn uses_digest(&self) -> bool {
+        matches!(self, Kdf::Pbkdf2 | Kdf::Hkdf)
+    }
+}
+
+/// Digests usable as the PBKDF2 PRF (through HMAC) and for HKDF
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum KdfDigest {
+    Md4,
+    Md5,
+    Sha1,
+    Sha224,
+    Sha256,
+    Sha384,
+    Sha512,
+    Sha512_224,
+    Sha512_256,
+    Sha3_224,
+    Sha3_256,
+    Sha3_384,
+    Sha3_512,
+    Keccak256,
+    Ripemd160,
+    Sm3,
+    Streebog256,
+    Streebog512,
+    Whirlpool,
+    Tiger,
+    Gost94CryptoPro,
+}
+
+pub const KDF_DIGESTS: [KdfDigest; 21] = [
+    KdfDigest::Md4,
+    KdfDigest::Md5,
+    KdfDigest::Sha1,
+    KdfDigest::Sha224,
+    KdfDigest::Sha256,
+    KdfDigest::Sha384,
+    KdfDigest::Sha512,
+    KdfDigest::Sha512_224,
+    KdfDigest::Sha512_256,
+    KdfDigest::Sha3_224,
+    KdfDigest::Sha3_256,
+    KdfDigest::Sha3_384,
+    KdfDigest::Sha3_512,
+    KdfDigest::Keccak256,
+    KdfDigest::Ripemd160,
+    KdfDigest::Sm3,
+    KdfDigest::Streebog256,
+    KdfDigest::Streebog512,
+    KdfDigest::Whirlpool,
+    KdfDigest::Tiger,
+    KdfDigest::Gost94CryptoPro,
+];
+
+impl KdfDigest {
+    pub fn name(&self) -> &'static str {
+        match self {
+            KdfDigest::Md4 => "MD4",
+            KdfDigest::Md5 => "MD5",
+            KdfDigest::Sha1 => "SHA-1",
+            KdfDigest::Sha224 => "SHA-224",
+            KdfDigest::Sha256 => "SHA-256",
+            KdfDigest::Sha384 => "SHA-384",
+            KdfDigest::Sha512 => "SHA-512",
+            KdfDigest::Sha512_224 => "SHA-512/224",
+            KdfDigest::Sha512_256 => "SHA-512/256",
+            KdfDigest::Sha3_224 => "SHA3-224",
+            KdfDigest::Sha3_256 => "SHA3-256",
+            KdfDigest::Sha3_384 => "SHA3-384",
+            KdfDigest::Sha3_512 => "SHA3-512",
+            KdfDigest::Keccak256 => "Keccak-256",
+            KdfDigest::Ripemd160 => "RIPEMD-160",
+            KdfDigest::Sm3 => "SM3",
+            KdfDigest::Streebog256 => "Streebog-256",
+            KdfDigest::Streebog512 => "Streebog-512",
+            KdfDigest::Whirlpool => "Whirlpool",
+            KdfDigest::Tiger => "Tiger",
+            KdfDigest::Gost94CryptoPro => "GOST R 34.11-94 CryptoPro",
+        }
+    }
+    /// PBKDF2 algorithm with a PHC identifier, if the digest has one
+    fn pbkdf2_phc(&self) -> Option<pbkdf2::Algorithm> {
+        match self {
+            KdfDigest::Sha1 => Some(pbkdf2::Algorithm::Pbkdf2Sha1),
+            KdfDigest::Sha256 => Some(pbkdf2::Algorithm::Pbkdf2Sha256),
+            KdfDigest::Sha512 => Some(pbkdf2::Algorithm::Pbkdf2Sha512),
+            _ => None,
+        }
+    }
+}
+
+/// Most memory a KDF may ask for; a failed allocation aborts the whole process
+pub const MAX_MEMORY: u64 = 4 << 30;
+
+/// Settings for every KDF; each one reads only the fields it uses
+#[derive(Clone)]
+pub struct KdfParams {
+    pub kdf: Kdf,
+    pub digest: KdfDigest,
+    pub salt: Vec<u8>,
+    /// PBKDF2 rounds
+    pub iterations: u32,
+    /// Argon2 passes over memory
+    pub passes: u32,
+    /// Argon2 memory in KiB
+    pub memory: u32,
+    /// Argon2 lanes and scrypt p
+    pub parallelism: u32,
+    /// scrypt CPU/memory cost as log2(N)
+    pub log_n: u8,
+    /// scrypt block size
+    pub block_size: u32,
+    /// bcrypt cost, 4 to 31
+    pub cost: u32,
+    /// HKDF context info
+    pub info: Vec<u8>,
+    pub output_len: usize,
+}
+
+impl KdfParams {
+    pub fn new() -> Self {
+        Self {
+            kdf: Kdf::Pbkdf2,
+            digest: KdfDigest::Sha256,
+            salt: Vec::new(),
+            iterations: 600_000,
+            passes: argon2::Params::DEFAULT_T_COST,
+            memory: argon2::Params::DEFAULT_M_COST,
+            parallelism: 1,
+            log_n: 17,
+            block_size: 8,
+            cost: 12,
+            info: Vec::new(),
+            output_len: 32,
+        }
+    }
+}
+
+/// Output of a KDF: the raw key, and the self-describing hash string when the
+/// KDF has one (PHC for PBKDF2, scrypt and Argon2, `$2b$` for bcrypt)
+pub struct Derived {
+    pub key: Vec<u8>,
+    pub encoded: Option<String>,
+}
+
+fn derive_with_digest<D>(password: &[u8], params: &KdfParams) -> Result<Vec<u8>, String>
+where
+    D: Digest + BlockSizeUser + Clone + Sync,
+{
+    let mut key = vec![0; params.output_len];
+    if params.kdf == Kdf::Hkdf {
+        hkdf::SimpleHkdf::<D>::new(Some(&params.salt), password)
+            .expand(&params.info, &mut key)
+            .map_err(|_| "HKDF output is limited to 255 digest lengths".to_string())?;
+    } else {
+        pbkdf2::pbkdf2::<hmac::SimpleHmac<D>>(password, &params.salt, params.iterations, &mut key)
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(key)
+}
+
+fn derive_digest(password: &[u8], params: &KdfParams) -> Result<Vec<u8>, String> {
+    match params.digest {
+        KdfDigest::Md4 => derive_with_digest::<md4::Md4>(password, params),
+        KdfDigest::Md5 => derive_with_digest::<md5::Md5>(password, params),
+        KdfDigest::Sha1 => derive_with_digest::<sha1::Sha1>(password, params),
+        KdfDigest::Sha224 => derive_with_digest::<sha2::Sha224>(password, params),
+        KdfDigest::Sha256 => derive_with_digest::<sha2::Sha256>(password, params),
+        KdfDigest::Sha384 => derive_with_digest::<sha2::Sha384>(password, params),
+        KdfDigest::Sha512 => derive_with_digest::<sha2::Sha512>(password, params),
+        KdfDigest::Sha512_224 => derive_with_digest::<sha2::Sha512_224>(password, params),
+        KdfDigest::Sha512_256 => derive_with_digest::<sha2::Sha512_256>(password, params),
+        KdfDigest::Sha3_224 => derive_with_digest::<sha3::Sha3_224>(password, params),
+        KdfDigest::Sha3_256 => derive_with_digest::<sha3::Sha3_256>(password, params),
+        KdfDigest::Sha3_384 => derive_with_digest::<sha3::Sha3_384>(password, params),
+        KdfDigest::Sha3_512 => derive_with_digest::<sha3::Sha3_512>(password, params),
+        KdfDigest::Keccak256 => derive_with_digest::<sha3::Keccak256>(password, params),
+        KdfDigest::Ripemd160 => derive_with_digest::<ripemd::Ripemd160>(password, params),
+        KdfDigest::Sm3 => derive_with_digest::<sm3::Sm3>(password, params),
+        KdfDigest::Streebog256 => derive_with_digest::<streebog::Streebog256>(password, params),
+        KdfDigest::Streebog512 => derive_with_digest::<streebog::Streebog512>(password, params),
+        KdfDigest::Whirlpool => derive_with_digest::<whirlpool::Whirlpool>(password, params),
+        KdfDigest::Tiger => derive_with_digest::<tiger::Tiger>(password, params),
+        KdfDigest::Gost94CryptoPro => {
+            derive_with_digest::<gost94::Gost94CryptoPro>(password, params)
+        }
+    }
+}
+
+/// Refuses parameters that need more than `MAX_MEMORY` before anything is allocated
+fn check_memory(params: &KdfParams) -> Result<(), String> {
+    let needed = match params.kdf {
+        Kdf::Scrypt => {
+            let (r, p) = (params.block_size as u128, params.parallelism as u128);
+            if r * p >= 1 << 30 {
+                return Err("scrypt needs r * p below 2^30".to_string());
+            }
+            // the V array of N blocks plus the p blocks of B
+            128 * r * ((1u128 << params.log_n.min(127)) + p)
+        }
+        Kdf::Argon2id | Kdf::Argon2i | Kdf::Argon2d => params.memory as u128 * 1024,
+        _ => 0,
+    };
+    if needed > MAX_MEMORY as u128 {
+        return Err(format!(
+            "Needs {} of memory, more than the limit of {}",
+            crate::format_mem(needed.min(usize::MAX as u128) as usize),
+            crate::format_mem(MAX_MEMORY as usize)
+        ));
+    }
+    Ok(())
+}
+
+fn phc_salt(salt: &[u8]) -> Result<SaltString, String> {
+    // PHC salts are 4 to 64 B64 characters
+    if !(3..=48).contains(&salt.len()) {
+        return Err(format!(
+            "PHC strings need a 3 to 48 byte salt, got {}",
+            salt.len()
+        ));
+    }
+    SaltString::encode_b64(salt).map_err(|e| format!("Salt: {}", e))
+}
+
+/// PHC string for a key that is already derived, so the KDF only runs once.
+/// None when the salt or key length can't be expressed in one.
+fn phc_string(
+    algorithm: Ident,
+    version: Option<Decimal>,
+    params: impl TryInto<ParamsString>,
+    salt: &[u8],
+    key: &[u8],
+) -> Option<String> {
+    let salt = phc_salt(salt).ok()?;
+    let hash = PasswordHash {
+        algorithm,
+        version,
+        params: params.try_into().ok()?,
+        salt: Some(salt.as_salt()),
+        hash: Some(Output::new(key).ok()?),
+    };
+    Some(hash.to_string())
+}
+
+fn argon2_algorithm(kdf: Kdf) -> argon2::Algorithm {
+    match kdf {
+        Kdf::Argon2i => argon2::Algorithm::Argon2i,
+        Kdf::Argon2d => argon2::Algorithm::Argon2d,
+        _ => argon2::Algorithm::Argon2id,
+    }
+}
+
+fn argon2_hasher(params: &KdfParams) -> Result<argon2::Argon2<'static>, String> {
+    let argon2_params = argon2::Params::new(
+        params.memory,
+        params.passes,
+        params.parallelism,
+        Some(params.output_len),
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(argon2::Argon2::new(
+        argon2_algorithm(params.kdf),
+        argon2::Version::V0x13,
+        argon2_params,
+    ))
+}
+
+/// Derives a key from the password.
+///
+/// PHC strings need a salt of 3 to 48 bytes; with other salts only the raw key is returned.
+pub fn derive(password: &[u8], params: &KdfParams) -> Result<Derived, String> {
+    check_memory(params)?;
+    match params.kdf {
+        Kdf::Pbkdf2 | Kdf::Hkdf => {
+            let key = derive_digest(password, params)?;
+            let encoded = match params.digest.pbkdf2_phc() {
+                Some(algorithm) if params.kdf == Kdf::Pbkdf2 => phc_string(
+                    algorithm.ident(),
+                    None,
+                    pbkdf2::Params {
+                        rounds: params.iterations,
+                        output_length: params.output_len,
+                    },
+                    &params.salt,
+                    &key,
+                ),
+                _ => None,
+            };
+            Ok(Derived { key, encoded })
+        }
+        Kdf::Scrypt => {
+            let scrypt_params = scrypt::Params::new(
+                params.log_n,
+                params.block_size,
+                params.parallelism,
+                params.output_len,
+            )
+            .map_err(|e| e.to_string())?;
+            let mut key = vec![0; params.output_len];
+            scrypt::scrypt(password, &params.salt, &scrypt_params, &mut key)
+                .map_err(|e| e.to_string())?;
+            let encoded = phc_string(scrypt::ALG_ID, None, scrypt_params, &params.salt, &key);
+            Ok(Derived { key, encoded })
+        }
+        Kdf::Argon2id | Kdf::Argon2i | Kdf::Argon2d => {
+            let hasher = argon2_hasher(params)?;
+            let mut key = vec![0; params.output_len];
+            hasher
+                .hash_password_into(password, &params.salt, &mut key)
+                .map_err(|e| e.to_string())?;
+            let encoded = phc_string(
+                argon2_algorithm(params.kdf).ident(),
+                Some(argon2::Version::V0x13.into()),
+                hasher.params(),
+                &params.salt,
+                &key,
+            );
+            Ok(Derived { key, encoded })
+        }
+        Kdf::Bcrypt => {
+            let salt: [u8; 16] =
+                params.salt.as_slice().try_into().map_err(|_| {
+                    format!("bcrypt salt must be 16 bytes, got {}", params.salt.len())
+                })?;
+            let parts =
+                bcrypt::hash_with_salt(password, params.cost, salt).map_err(|e| e.to_string())?;
+            let encoded = parts.format_for_version(bcrypt::Version::TwoB);
+            // the last 31 characters are the 23-byte hash in bcrypt's Base64 alphabet
+            let key = data_encoding::Specification {
+                symbols: "./ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789"
+                    .to_string(),
+                check_trailing_bits: false,
+                ..Default::default()
+            }
+            .encoding()
+            .map_err(|e| e.to_string())?
+            .decode(&encoded.as_bytes()[29..])
+            .map_err(|e| e.to_string())?;
+            Ok(Derived {
+                key,
+                encoded: Some(encoded),
+            })
+        }
+    }
+}
+
+/// Checks a password against a PHC string (`$argon2id$`, `$scrypt$`, `$pbkdf2-sha256$` ...)
+/// or a bcrypt hash (`$2a$`, `$2b$`, `$2y$`).
+///
+/// Returns whether it matches, with a description of the parsed parameters.
+pub fn verify(password: &[u8], encoded: &str) -> Result<(bool, String), String> {
+    let encoded = encoded.trim();
+    if ["$2a$", "$2b$", "$2x$", "$2y$"]
+        .iter()
+        .any(|p| encoded.starts_with(p))
+    {
+        let matched = bcrypt::verify(password, encoded).map_err(|e| e.to_string())?;
+        let cost = encoded.get(4..6).unwrap_or_default();
+        return Ok((matched, format!("bcrypt, cost {}", cost)));
+    }
+    let hash = PasswordHash::new(encoded).map_err(|e| format!("Not a PHC string: {}", e))?;
+    let mut description = hash.algorithm.to_string();
+    if let Some(version) = hash.version {
+        description.push_str(&format!(", v={}", version));
+    }
+    for (name, value) in hash.params.iter() {
+        description.push_str(&format!(", {}={}", name, value));
+    }
+    if let Some(output) = &hash.hash {
+        description.push_str(&format!(", {}-byte output", output.len()));
+    }
+    let verifiers: [&dyn PasswordVerifier; 3] =
+        [&argon2::Argon2::default(), &scrypt::Scrypt, &pbkdf2::Pbkdf2];
+    match hash.verify_password(&verifiers, password) {
+        Ok(()) => Ok((true, description)),
+        Err(argon2::password_hash::Error::Password) => Ok((false, description)),
+        Err(argon2::password_hash::Error::Algorithm) => Err(format!(
+            "Unsupported algorithm \"{}\", expected argon2, scrypt or pbkdf2",
+            hash.algorithm
+        )),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Kdf, KdfDigest, KdfParams};
+
+    fn params(kdf: Kdf, salt: &[u8], output_len: usize) -> KdfParams {
+        KdfParams {
+            kdf,
+            salt: salt.to_vec(),
+            output_len,
+            ..KdfParams::new()
+        }
+    }
+
+    #[test]
+    fn test_vectors() {
+        // RFC 6070
+        let mut p = params(Kdf::Pbkdf2, b"salt", 20);
+        p.digest = KdfDigest::Sha1;
+        p.iterations = 4096;
+        let derived = super::derive(b"password", &p).unwrap();
+        assert_eq!(
+            hex::encode(derived.key),
+            "4b007901b765489abead49d926f721d065a429c1"
+        );
+        assert!(derived.encoded.unwrap().starts_with("$pbkdf2$i=4096,l=20$"));
+        // RFC 5869 test case 1
+        let mut p = params(
+            Kdf::Hkdf,
+            &hex::decode("000102030405060708090a0b0c").unwrap(),
+            42,
+        );
+        p.info = hex::decode("f0f1f2f3f4f5f6f7f8f9").unwrap();
+        let derived = super::derive(&[0x0b; 22], &p).unwrap();
+        assert_eq!(
+            hex::encode(derived.key),
+            "3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf34007208d5b887185865"
+        );
+        // RFC 7914 section 12, first vector
+        let mut p = params(Kdf::Scrypt, b"", 64);
+        p.log_n = 4;
+        p.block_size = 1;
+        let derived = super::derive(b"", &p).unwrap();
+        assert!(hex::encode(derived.key).starts_with("77d6576238657b203b19ca42c18a0497"));
+        assert!(derived.encoded.is_none());
+        // refused before the allocation instead of aborting on it
+        p.log_n = 24;
+        p.block_size = 8;
+        assert!(super::derive(b"", &p).is_err_and(|e| e.contains("memory")));
+        let mut p = params(Kdf::Argon2id, b"somesalt", 32);
+        p.memory = u32::MAX;
+        assert!(super::derive(b"", &p).is_err_and(|e| e.contains("memory")));
+    }
+    #[test]
+    fn test_verify() {
+        let mut p = params(Kdf::Argon2id, b"somesalt", 32);
+        p.memory = 64;
+        p.passes = 2;
+        let encoded = super::derive(b"hunter2", &p).unwrap().encoded.unwrap();
+        assert!(encoded.starts_with("$argon2id$v=19$m=64,t=2,p=1$c29tZXNhbHQ$"));
+        assert!(super::verify(b"hunter2", &encoded).unwrap().0);
+        assert!(!super::verify(b"hunter3", &encoded).unwrap().0);
+        // the PHC string carries the same key as the raw output
+        let mut p = params(Kdf::Scrypt, b"somesalt", 32);
+        p.log_n = 4;
+        p.block_size = 1;
+        let mut pbkdf2 = params(Kdf::Pbkdf2, b"somesalt", 32);
+        pbkdf2.iterations = 1000;
+        for p in [p, pbkdf2] {
+            let derived = super::derive(b"hunter2", &p).unwrap();
+            let encoded = derived.encoded.unwrap();
+            let hash = super::PasswordHash::new(&encoded).unwrap().hash.unwrap();
+            assert_eq!(hash.as_bytes(), derived.key);
+            assert!(super::verify(b"hunter2", &encoded).unwrap().0);
+        }
+        let mut p = params(Kdf::Bcrypt, &[7; 16], 23);
+        p.cost = 4;
+        let derived = super::derive(b"hunter2", &p).unwrap();
+        assert_eq!(derived.key.len(), 23);
+        let encoded = derived.encoded.unwrap();
+        assert!(encoded.starts_with("$2b$04$"));
+        assert_eq!(
+            super::verify(b"hunter2", &encoded).unwrap(),
+            (true, "bcrypt, cost 04".to_string())
+        );
+        assert!(!super::verify(b"hunter3", &encoded).unwrap().0);
+        // crypt_blowfish test vector
+        let known = "$2a$05$CCCCCCCCCCCCCCCCCCCCC.E5YPO9kmyuRGyh0XouQYb4YMJKvyOeW";
+        assert!(super::verify(b"U*U", known).unwrap().0);
+        assert!(super::verify(b"x", "$md5$abc").is_err());
+    }
+}
@@ -0,0 +1,496 @@
+use digest::Digest;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum Algorithm {
+    Md5,
+    Sha1,
+    Sha256,
+    Sha512,
+    Sha3_256,
+    Blake3,
+    Sm3,
+}
+
+pub const ALGORITHMS: [Algorithm; 7] = [
+    Algorithm::Md5,
+    Algorithm::Sha1,
+    Algorithm::Sha256,
+    Algorithm::Sha512,
+    Algorithm::Sha3_256,
+    Algorithm::Blake3,
+    Algorithm::Sm3,
+];
+
+impl Algorithm {
+    /// Name as written in BSD-style tags and the JSON manifest
+    pub fn name(&self) -> &'static str {
+        match self {
+            Algorithm::Md5 => "MD5",
+            Algorithm::Sha1 => "SHA1",
+            Algorithm::Sha256 => "SHA256",
+            Algorithm::Sha512 => "SHA512",
+            Algorithm::Sha3_256 => "SHA3-256",
+            Algorithm::Blake3 => "BLAKE3",
+            Algorithm::Sm3 => "SM3",
+        }
+    }
+    fn hex_len(&self) -> usize {
+        match self {
+            Algorithm::Md5 => 32,
+            Algorithm::Sha1 => 40,
+            Algorithm::Sha512 => 128,
+            _ => 64,
+        }
+    }
+    fn from_name(name: &str) -> Option<Self> {
+        let name = name.to_uppercase().replace('_', "-");
+        ALGORITHMS
+            .into_iter()
+            .find(|a| a.name() == name || a.name() == name.replace("SHA-", "SHA"))
+    }
+}
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum Format {
+    /// `digest  path`, as written by sha256sum and b3sum
+    Sum,
+    /// `SHA256 (path) = digest`, as written by `sha256sum --tag`
+    Bsd,
+    Json,
+}
+
+pub const FORMATS: [Format; 3] = [Format::Sum, Format::Bsd, Format::Json];
+
+impl Format {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Format::Sum => "sha256sum / b3sum",
+            Format::Bsd => "BSD tag",
+            Format::Json => "JSON",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Entry {
+    /// Relative to the manifest directory, with '/' separators
+    pub path: String,
+    /// Lowercase hex
+    pub digest: String,
+}
+
+fn stream<D: Digest>(reader: &mut impl Read) -> std::io::Result<Vec<u8>> {
+    let mut hasher = D::new();
+    let mut buffer = vec![0; 64 * 1024];
+    loop {
+        let n = reader.read(&mut buffer)?;
+        if n == 0 {
+            return Ok(hasher.finalize().to_vec());
+        }
+        hasher.update(&buffer[..n]);
+    }
+}
+
+/// Hashes a file without reading it into memory at once
+pub fn hash_file(path: &Path, algorithm: Algorithm) -> Result<String, String> {
+    let mut file = std::fs::File::open(path).map_err(|e| e.to_string())?;
+    let digest = match algorithm {
+        Algorithm::Md5 => stream::<md5::Md5>(&mut file),
+        Algorithm::Sha1 => stream::<sha1::Sha1>(&mut file),
+        Algorithm::Sha256 => stream::<sha2::Sha256>(&mut file),
+        Algorithm::Sha512 => stream::<sha2::Sha512>(&mut file),
+        Algorithm::Sha3_256 => stream::<sha3::Sha3_256>(&mut file),
+        Algorithm::Sm3 => stream::<sm3::Sm3>(&mut file),
+        Algorithm::Blake3 => {
+            let mut hasher = blake3::Hasher::new();
+            std::io::copy(&mut file, &mut hasher).map(|_| hasher.finalize().as_bytes().to_vec())
+        }
+    };
+    digest.map(hex::encode).map_err(|e| e.to_string())
+}
+
+/// Hashes the files on one worker per core, keeping the order of `files`
+fn hash_all(files: &[PathBuf], algorithm: Algorithm) -> Vec<Result<String, String>> {
+    let workers = std::thread::available_parallelism()
+        .map_or(1, |n| n.get())
+        .min(files.len().max(1));
+    let next = AtomicUsize::new(0);
+    let mut results: Vec<Result<String, String>> = files
+        .iter()
+        .map(|_| Err("Not hashed".to_string()))
+        .collect();
+    std::thread::scope(|s| {
+        let handles: Vec<_> = (0..workers)
+            .map(|_| {
+                s.spawn(|| {
+                    let mut done = Vec::new();
+                    loop {
+                        let i = next.fetch_add(1, Ordering::Relaxed);
+                        if i >= files.len() {
+                            return done;
+                        }
+                        done.push((i, hash_file(&files[i], algorithm)));
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            for (i, result) in handle.join().unwrap_or_default() {
+                results[i] = result;
+            }
+        }
+    });
+    results
+}
+
+fn relative(dir: &Path, path: &Path) -> String {
+    let rel = path.strip_prefix(dir).unwrap_or(path);
+    rel.components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Files under `dir` as sorted relative paths, leaving out `skip` (the manifest itself)
+fn list_files(
+    dir: &Path,
+    recursive: bool,
+    skip: Option<&Path>,
+) -> Result<Vec<(String, PathBuf)>, String> {
+    let mut files = Vec::new();
+    crate::tools::batch_process::scan_dir(dir, recursive, &mut files)?;
+    let skip = skip.and_then(|p| p.canonicalize().ok());
+    let mut files: Vec<(String, PathBuf)> = files
+        .into_iter()
+        .filter(|p| skip.is_none() || p.canonicalize().ok() != skip)
+        .map(|p| (relative(dir, &p), p))
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+/// Hashes every file under `dir`. Files that cannot be read are returned as errors.
+pub fn generate(
+    dir: &Path,
+    recursive: bool,
+    algorithm: Algorithm,
+    skip: Option<&Path>,
+) -> Result<(Vec<Entry>, Vec<String>), String> {
+    let files = list_files(dir, recursive, skip)?;
+    let paths: Vec<PathBuf> = files.iter().map(|(_, p)| p.clone()).collect();
+    let mut entries = Vec::new();
+    let mut errors = Vec::new();
+    for ((path, _), result) in files.into_iter().zip(hash_all(&paths, algorithm)) {
+        match result {
+            Ok(digest) => entries.push(Entry { path, digest }),
+            Err(e) => errors.push(format!("{}: {}", path, e)),
+        }
+    }
+    Ok((entries, errors))
+}
+
+pub fn write(entries: &[Entry], algorithm: Algorithm, format: Format) -> String {
+    match format {
+        Format::Sum => entries
+            .iter()
+            .map(|e| {
+                // GNU coreutils marks escaped names with a leading backslash
+                if e.path.contains(['\\', '\n']) {
+                    let path = e.path.replace('\\', "\\\\").replace('\n', "\\n");
+                    format!("\\{}  {}\n", e.digest, path)
+                } else {
+                    format!("{}  {}\n", e.digest, e.path)
+                }
+            })
+            .collect(),
+        Format::Bsd => entries
+            .iter()
+            .map(|e| format!("{} ({}) = {}\n", algorithm.name(), e.path, e.digest))
+            .collect(),
+        Format::Json => {
+            let files: serde_json::Map<String, serde_json::Value> = entries
+                .iter()
+                .map(|e| (e.path.clone(), serde_json::Value::from(e.digest.as_str())))
+                .collect();
+            let json = serde_json::json!({ "algorithm": algorithm.name(), "files": files });
+            serde_json::to_string_pretty(&json).unwrap_or_default()
+        }
+    }
+}
+
+fn unescape(path: &str) -> String {
+    let mut out = String::new();
+    let mut chars = path.chars();
+    while let Some(c) = chars.next() {
+        match (c, chars.clone().next()) {
+            ('\\', Some('\\')) => {
+                out.push('\\');
+                chars.next();
+            }
+            ('\\', Some('n')) => {
+                out.push('\n');
+                chars.next();
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+fn check_digest(digest: &str, line: usize) -> Result<String, String> {
+    if digest.is_empty() || !digest.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!("Line {}: invalid digest", line));
+    }
+    Ok(digest.to_lowercase())
+}
+
+/// Reads a manifest in any of the formats.
+///
+/// Plain sum files do not name their algorithm, so it is taken from the digest length,
+/// preferring `fallback` when it fits (SHA256, SHA3-256, BLAKE3 and SM3 all give 64 digits).
+pub fn parse(text: &str, fallback: Algorithm) -> Result<(Algorithm, Vec<Entry>), String> {
+    let text = text.trim();
+    if text.starts_with('{') {
+        let json: serde_json::Value = serde_json::from_str(text).map_err(|e| e.to_string())?;
+        let algorithm = json["algorithm"]
+            .as_str()
+            .map(|name| Algorithm::from_name(name).ok_or(format!("Unknown algorithm {}", name)))
+            .transpose()?
+            .unwrap_or(fallback);
+        let files = json["files"]
+            .as_object()
+            .ok_or("Missing \"files\" object".to_string())?;
+        let mut entries = Vec::new();
+        for (path, digest) in files {
+            let digest = digest
+                .as_str()
+                .ok_or(format!("{}: digest is not a string", path))?;
+            entries.push(Entry {
+                path: path.clone(),
+                digest: check_digest(digest, 0).map_err(|_| format!("{}: invalid digest", path))?,
+            });
+        }
+        return Ok((algorithm, entries));
+    }
+    let mut algorithm = None;
+    let mut entries = Vec::new();
+    for (n, line) in text.lines().enumerate() {
+        let n = n + 1;
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (name, path, digest) = if let Some((head, digest)) = line
+            .rsplit_once(") = ")
+            .filter(|(head, _)| head.contains(" ("))
+        {
+            let (name, path) = head.split_once(" (").unwrap_or_default();
+            let name = Algorithm::from_name(name)
+                .ok_or(format!("Line {}: unknown algorithm {}", n, name))?;
+            (Some(name), path.to_string(), digest)
+        } else {
+            let (escaped, line) = match line.strip_prefix('\\') {
+                Some(rest) => (true, rest),
+                None => (false, line),
+            };
+            let (digest, path) = line
+                .split_once(' ')
+                .ok_or(format!("Line {}: expected \"digest  path\"", n))?;
+            // the second separator character is '*' for binary mode
+            let path = path
+                .strip_prefix(' ')
+                .or(path.strip_prefix('*'))
+                .ok_or(format!("Line {}: expected \"digest  path\"", n))?;
+            let path = if escaped {
+                unescape(path)
+            } else {
+                path.to_string()
+            };
+            (None, path, digest)
+        };
+        let digest = check_digest(digest.trim(), n)?;
+        let guessed = name.unwrap_or_else(|| {
+            if fallback.hex_len() == digest.len() {
+                fallback
+            } else {
+                ALGORITHMS
+                    .into_iter()
+                    .find(|a| a.hex_len() == digest.len())
+                    .unwrap_or(fallback)
+            }
+        });
+        if guessed.hex_len() != digest.len() {
+            return Err(format!(
+                "Line {}: digest length does not fit {}",
+                n,
+                guessed.name()
+            ));
+        }
+        match algorithm {
+            None => algorithm = Some(guessed),
+            Some(a) if a != guessed => {
+                return Err(format!("Line {}: mixed algorithms are not supported", n))
+            }
+            _ => {}
+        }
+        entries.push(Entry { path, digest });
+    }
+    if entries.is_empty() {
+        return Err("No entries in manifest".to_string());
+    }
+    Ok((algorithm.unwrap_or(fallback), entries))
+}
+
+#[derive(Default)]
+pub struct Report {
+    pub ok: usize,
+    pub modified: Vec<String>,
+    pub missing: Vec<String>,
+    pub extra: Vec<String>,
+    /// Files present but not readable, with the error
+    pub unreadable: Vec<String>,
+}
+
+impl Report {
+    pub fn passed(&self) -> bool {
+        self.modified.is_empty()
+            && self.missing.is_empty()
+            && self.extra.is_empty()
+            && self.unreadable.is_empty()
+    }
+    pub fn summary(&self) -> String {
+        format!(
+            "{} OK, {} modified, {} missing, {} extra, {} unreadable",
+            self.ok,
+            self.modified.len(),
+            self.missing.len(),
+            self.extra.len(),
+            self.unreadable.len()
+        )
+    }
+}
+
+/// Checks the files under `dir` against a manifest.
+///
+/// Subdirectories are walked when `recursive` is set or the manifest lists nested paths.
+pub fn verify(
+    dir: &Path,
+    manifest: &str,
+    fallback: Algorithm,
+    recursive: bool,
+    skip: Option<&Path>,
+) -> Result<(Algorithm, Report), String> {
+    let (algorithm, entries) = parse(manifest, fallback)?;
+    let recursive = recursive || entries.iter().any(|e| e.path.contains('/'));
+    let found = list_files(dir, recursive, skip)?;
+    let mut report = Report::default();
+    let listed: std::collections::HashSet<&str> = entries.iter().map(|e| e.path.as_str()).collect();
+    report.extra = found
+        .iter()
+        .filter(|(rel, _)| !listed.contains(rel.as_str()))
+        .map(|(rel, _)| rel.clone())
+        .collect();
+    let by_path: std::collections::HashMap<&str, &PathBuf> = found
+        .iter()
+        .map(|(rel, path)| (rel.as_str(), path))
+        .collect();
+    let mut present = Vec::new();
+    for entry in &entries {
+        match by_path.get(entry.path.as_str()) {
+            Some(&path) => present.push((entry, path.clone())),
+            None => report.missing.push(entry.path.clone()),
+        }
+    }
+    let paths: Vec<PathBuf> = present.iter().map(|(_, p)| p.clone()).collect();
+    for ((entry, _), result) in present.iter().zip(hash_all(&paths, algorithm)) {
+        match result {
+            Ok(digest) if digest == entry.digest => report.ok += 1,
+            Ok(_) => report.modified.push(entry.path.clone()),
+            Err(e) => report.unreadable.push(format!("{}: {}", entry.path, e)),
+        }
+    }
+    Ok((algorithm, report))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_manifest() {
+        let dir = std::env::temp_dir().join(format!("manifest_test_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("sub")).unwrap();
+        std::fs::write(dir.join("a.txt"), b"abc").unwrap();
+        std::fs::write(dir.join("sub").join("b.txt"), b"hello").unwrap();
+        std::fs::write(dir.join("sub").join("c.txt"), b"").unwrap();
+
+        let (entries, errors) = generate(&dir, true, Algorithm::Sha256, None).unwrap();
+        assert!(errors.is_empty());
+        assert_eq!(
+            entries[0],
+            Entry {
+                path: "a.txt".to_string(),
+                digest: "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+                    .to_string()
+            }
+        );
+        assert_eq!(entries[1].path, "sub/b.txt");
+        let (top, _) = generate(&dir, false, Algorithm::Sha256, None).unwrap();
+        assert_eq!(top.len(), 1);
+
+        for format in FORMATS {
+            let text = write(&entries, Algorithm::Sha256, format);
+            assert_eq!(
+                parse(&text, Algorithm::Md5).unwrap(),
+                (Algorithm::Sha256, entries.clone())
+            );
+            let (_, report) = verify(&dir, &text, Algorithm::Sha256, false, None).unwrap();
+            assert!(report.passed() && report.ok == 3);
+        }
+        // a 64-digit sum file is ambiguous, so the chosen algorithm is used
+        let (b3, _) = generate(&dir, true, Algorithm::Blake3, None).unwrap();
+        let text = write(&b3, Algorithm::Blake3, Format::Sum);
+        let (_, report) = verify(&dir, &text, Algorithm::Blake3, false, None).unwrap();
+        assert!(report.passed());
+
+        let manifest = dir.join("SHA256SUMS");
+        std::fs::write(&manifest, write(&entries, Algorithm::Sha256, Format::Sum)).unwrap();
+        std::fs::write(dir.join("a.txt"), b"abd").unwrap();
+        std::fs::remove_file(dir.join("sub").join("c.txt")).unwrap();
+        std::fs::write(dir.join("sub").join("d.txt"), b"new").unwrap();
+        let text = std::fs::read_to_string(&manifest).unwrap();
+        let (_, report) = verify(&dir, &text, Algorithm::Sha256, false, Some(&manifest)).unwrap();
+        assert_eq!(report.ok, 1);
+        assert_eq!(report.modified, vec!["a.txt"]);
+        assert_eq!(report.missing, vec!["sub/c.txt"]);
+        assert_eq!(report.extra, vec!["sub/d.txt"]);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_parse() {
+        let entries = vec![Entry {
+            path: "back\\slash\nname".to_string(),
+            digest: "d41d8cd98f00b204e9800998ecf8427e".to_string(),
+        }];
+        let text = write(&entries, Algorithm::Md5, Format::Sum);
+        assert!(text.starts_with('\\'));
+        assert_eq!(
+            parse(&text, Algorithm::Sha256).unwrap(),
+            (Algorithm::Md5, entries)
+        );
+        let (_, binary) = parse(
+            "D41D8CD98F00B204E9800998ECF8427E *empty.bin",
+            Algorithm::Sha256,
+        )
+        .unwrap();
+        assert_eq!(binary[0].path, "empty.bin");
+        assert_eq!(binary[0].digest, "d41d8cd98f00b204e9800998ecf8427e");
+        assert!(parse("SHA-999 (x) = 00", Algorithm::Md5).is_err());
+        assert!(parse("zz  file", Algorithm::Md5).is_err());
+        assert!(parse("", Algorithm::Md5).is_err());
+    }
+}
@@ -2,17 +2,49 @@ use super::*;
 use eframe::egui;
 
 mod basic;
+pub mod binascii;
+mod checksums;
+mod compress;
+mod diff;
+mod export;
 mod hashes;
+mod identify;
+mod kdf;
+mod manifest;
+pub mod pubkey;
+mod scan;
+mod strings;
 mod summarize;
+mod symmetric;
+mod template;
+mod xor;
 
 const LOAD_FILE: u8 = 0;
 const SAVE_FILE: u8 = 1;
+const EXTRACT_DIR: u8 = 2;
+const LOAD_COMPARE: u8 = 3;
+const SAVE_IPS: u8 = 4;
+const SAVE_BPS: u8 = 5;
+const MANIFEST_DIR: u8 = 6;
+const LOAD_MANIFEST: u8 = 7;
+const SAVE_MANIFEST: u8 = 8;
 
 #[derive(PartialEq, Eq, Hash, Clone)]
 enum Catagory {
     Basic,
     Hash(HashType),
     Summarize,
+    Strings,
+    Scan,
+    Xor,
+    Compress,
+    Compare,
+    Template,
+    Export,
+    Cipher,
+    Kdf,
+    Sign,
+    Manifest,
 }
 
 #[derive(PartialEq, Eq, Hash, Clone)]
@@ -26,9 +58,11 @@ pub enum HashType {
     GROESTL,
     JH,
     OTHER,
+    Crc,
+    Checksum,
+    Identify,
 }
 
-#[derive(PartialEq, Eq, Hash, Clone)]
 pub struct ByteTool {
     input: Vec<u8>,
     display_cache: String,
@@ -36,12 +70,534 @@ pub struct ByteTool {
     selected_catagory: Catagory,
     msg: Option<Msg>,
     file_op: SLState,
+    crc_form: CrcForm,
+    checksum_seed: String,
+    entropy_window: usize,
+    analysis: Option<Analysis>,
+    preview_offset: usize,
+    strings_view: StringsView,
+    scan_hits: Vec<scan::Hit>,
+    scan_selected: Vec<bool>,
+    xor_max_keylen: usize,
+    xor_keylen: usize,
+    xor_plain: String,
+    xor_offset: usize,
+    codec: compress::Codec,
+    codec_level: u32,
+    compare: Vec<u8>,
+    diff_view: Option<DiffView>,
+    diff_recv: Option<std::sync::mpsc::Receiver<Vec<diff::Hunk>>>,
+    templates: Vec<template::Template>,
+    /// Whether loading was tried, so an empty template list isn't reloaded every frame
+    templates_loaded: bool,
+    template_selected: usize,
+    template_fields: Vec<template::Field>,
+    template_error: Option<String>,
+    scheme: binascii::Scheme,
+    export_format: export::Format,
+    export_name: String,
+    cipher_form: CipherForm,
+    kdf_form: KdfForm,
+    sign_form: SignForm,
+    manifest_form: ManifestForm,
+    identified: Option<identify::Identified>,
+    /// Candidates that reproduce the identified digest from Input
+    identify_matches: Option<Vec<String>>,
+}
+
+struct DiffView {
+    hunks: Vec<diff::Hunk>,
+    /// (hunk index, byte offset into the hunk) of every 16-byte row of the side-by-side view
+    rows: Vec<(usize, usize)>,
+    /// Index into `hunks` of the difference selected with Prev/Next
+    current: Option<usize>,
+    scroll_to: Option<usize>,
+}
+
+impl DiffView {
+    const ROW_BYTES: usize = 16;
+
+    fn new(hunks: Vec<diff::Hunk>) -> Self {
+        let mut rows = Vec::new();
+        for (i, hunk) in hunks.iter().enumerate() {
+            let len = hunk.a.len().max(hunk.b.len());
+            rows.extend((0..len.div_ceil(Self::ROW_BYTES)).map(|r| (i, r * Self::ROW_BYTES)));
+        }
+        Self {
+            hunks,
+            rows,
+            current: None,
+            scroll_to: None,
+        }
+    }
+    fn changes(&self) -> usize {
+        self.hunks
+            .iter()
+            .filter(|h| h.change != diff::Change::Equal)
+            .count()
+    }
+    /// Selects the next (or previous) difference and scrolls to it
+    fn step(&mut self, forward: bool) {
+        let is_change = |i: &usize| self.hunks[*i].change != diff::Change::Equal;
+        let found = match (self.current, forward) {
+            (None, true) => (0..self.hunks.len()).find(is_change),
+            (None, false) => (0..self.hunks.len()).rev().find(is_change),
+            (Some(c), true) => (c + 1..self.hunks.len()).find(is_change),
+            (Some(c), false) => (0..c).rev().find(is_change),
+        };
+        if let Some(i) = found {
+            self.current = Some(i);
+            self.scroll_to = self.rows.iter().position(|r| r.0 == i);
+        }
+    }
+}
+
+#[derive(Clone)]
+struct Analysis {
+    entropy: Vec<[f64; 2]>,
+    histogram: Vec<usize>,
+}
+
+#[derive(PartialEq, Eq, Hash, Clone)]
+struct CrcForm {
+    preset: usize,
+    width: u8,
+    poly: String,
+    init: String,
+    xorout: String,
+    refin: bool,
+    refout: bool,
+}
+
+impl CrcForm {
+    fn from_preset(preset: usize) -> Self {
+        let (_, params, _) = checksums::CRC_CATALOGUE[preset];
+        let digits = params.hex_digits();
+        Self {
+            preset,
+            width: params.width,
+            poly: format!("{:0width$X}", params.poly, width = digits),
+            init: format!("{:0width$X}", params.init, width = digits),
+            xorout: format!("{:0width$X}", params.xorout, width = digits),
+            refin: params.refin,
+            refout: params.refout,
+        }
+    }
+    /// The catalogue entry called `name`, or the first one
+    fn named(name: &str) -> Self {
+        let preset = checksums::CRC_CATALOGUE
+            .iter()
+            .position(|(n, _, _)| *n == name)
+            .unwrap_or(0);
+        Self::from_preset(preset)
+    }
+    fn params(&self) -> Result<checksums::CrcParams, String> {
+        let params = checksums::CrcParams {
+            width: self.width,
+            poly: checksums::parse_crc_value(&self.poly)?,
+            init: checksums::parse_crc_value(&self.init)?,
+            refin: self.refin,
+            refout: self.refout,
+            xorout: checksums::parse_crc_value(&self.xorout)?,
+        };
+        params.validate()?;
+        Ok(params)
+    }
+}
+
+struct CipherForm {
+    algorithm: symmetric::Algorithm,
+    mode: symmetric::Mode,
+    padding: symmetric::Padding,
+    key: String,
+    key_encoding: symmetric::KeyEncoding,
+    iv: String,
+    iv_encoding: symmetric::KeyEncoding,
+    aad: String,
+}
+
+impl CipherForm {
+    fn new() -> Self {
+        Self {
+            algorithm: symmetric::Algorithm::Aes,
+            mode: symmetric::Mode::Cbc,
+            padding: symmetric::Padding::Pkcs7,
+            key: String::new(),
+            key_encoding: symmetric::KeyEncoding::Hex,
+            iv: String::new(),
+            iv_encoding: symmetric::KeyEncoding::Hex,
+            aad: String::new(),
+        }
+    }
+    fn params<'a>(&self, key: &'a [u8], iv: &'a [u8]) -> symmetric::Params<'a> {
+        symmetric::Params {
+            algorithm: self.algorithm,
+            mode: self.mode,
+            padding: self.padding,
+            key,
+            iv,
+            aad: &[],
+        }
+    }
+    fn run(&self, input: &[u8], encrypt: bool) -> Result<Vec<u8>, String> {
+        let key = self
+            .key_encoding
+            .parse(&self.key)
+            .map_err(|e| format!("Key: {}", e))?;
+        let iv = self
+            .iv_encoding
+            .parse(&self.iv)
+            .map_err(|e| format!("IV: {}", e))?;
+        let mut params = self.params(&key, &iv);
+        params.aad = self.aad.as_bytes();
+        if encrypt {
+            symmetric::encrypt(input, &params)
+        } else {
+            symmetric::decrypt(input, &params)
+        }
+    }
+}
+
+struct KdfForm {
+    params: kdf::KdfParams,
+    salt: String,
+    salt_encoding: symmetric::KeyEncoding,
+    info: String,
+    /// PHC or bcrypt string to verify against
+    encoded: String,
+    recv: Option<std::sync::mpsc::Receiver<(Option<String>, Msg)>>,
+}
+
+impl KdfForm {
+    fn new() -> Self {
+        Self {
+            params: kdf::KdfParams::new(),
+            salt: String::new(),
+            salt_encoding: symmetric::KeyEncoding::Text,
+            info: String::new(),
+            encoded: String::new(),
+            recv: None,
+        }
+    }
+    /// Runs the KDF on a thread, since strong parameters take seconds
+    fn start(&mut self, password: Vec<u8>, verify: bool) -> Result<(), String> {
+        let mut params = self.params.clone();
+        params.salt = self
+            .salt_encoding
+            .parse(&self.salt)
+            .map_err(|e| format!("Salt: {}", e))?;
+        params.info = self.info.as_bytes().to_vec();
+        let encoded = self.encoded.clone();
+        let (send, recv) = std::sync::mpsc::channel();
+        self.recv = Some(recv);
+        std::thread::spawn(move || {
+            let result = if verify {
+                match kdf::verify(&password, &encoded) {
+                    Ok((true, description)) => (
+                        Some(description),
+                        Msg::new("Password matches".to_string(), MsgType::Info),
+                    ),
+                    Ok((false, description)) => (
+                        Some(description),
+                        Msg::new("Password does not match".to_string(), MsgType::Warning),
+                    ),
+                    Err(e) => (None, Msg::new(format!("Error: {}", e), MsgType::Error)),
+                }
+            } else {
+                match kdf::derive(&password, &params) {
+                    Ok(derived) => {
+                        let mut output = hex::encode(&derived.key);
+                        if let Some(encoded) = derived.encoded {
+                            output.push('\n');
+                            output.push_str(&encoded);
+                        }
+                        (
+                            Some(output),
+                            Msg::new(format!("Derived with {}", params.kdf.name()), MsgType::Info),
+                        )
+                    }
+                    Err(e) => (None, Msg::new(format!("Error: {}", e), MsgType::Error)),
+                }
+            };
+            // the receiver is gone if the tool was closed meanwhile
+            let _ = send.send(result);
+        });
+        Ok(())
+    }
+}
+
+struct SignForm {
+    key_type: pubkey::KeyType,
+    rsa_bits: usize,
+    format: pubkey::KeyFormat,
+    padding: pubkey::RsaPadding,
+    private: String,
+    public: String,
+    /// Description and fingerprints of the key shown
+    fingerprints: Vec<(&'static str, String)>,
+    recv: Option<std::sync::mpsc::Receiver<Result<pubkey::PrivateKey, String>>>,
+}
+
+impl SignForm {
+    fn new() -> Self {
+        Self {
+            key_type: pubkey::KeyType::Ed25519,
+            rsa_bits: 2048,
+            format: pubkey::KeyFormat::Pem,
+            padding: pubkey::RsaPadding::Pkcs1v15,
+            private: String::new(),
+            public: String::new(),
+            fingerprints: Vec::new(),
+            recv: None,
+        }
+    }
+    /// Generates on a thread, since large RSA keys take seconds
+    fn generate(&mut self) {
+        let (key_type, bits) = (self.key_type, self.rsa_bits);
+        let (send, recv) = std::sync::mpsc::channel();
+        self.recv = Some(recv);
+        std::thread::spawn(move || {
+            // the receiver is gone if the tool was closed meanwhile
+            let _ = send.send(pubkey::generate(key_type, bits));
+        });
+    }
+    /// Fills the key fields in the chosen format
+    fn show(
+        &mut self,
+        private: Option<&pubkey::PrivateKey>,
+        public: &pubkey::PublicKey,
+    ) -> Result<(), String> {
+        self.private = match private {
+            Some(key) => pubkey::export_private(key, self.format)?,
+            None => String::new(),
+        };
+        self.public = pubkey::export_public(public, self.format)?;
+        self.key_type = public.key_type();
+        self.fingerprints = vec![("Key", public.description())];
+        self.fingerprints.extend(pubkey::fingerprints(public));
+        Ok(())
+    }
+    /// Re-encodes the key fields in the chosen format
+    fn convert(&mut self) -> Result<(), String> {
+        if self.private.trim().is_empty() {
+            let public = pubkey::import_public(&self.public)?;
+            self.show(None, &public)
+        } else {
+            let private = pubkey::import_private(&self.private)?;
+            self.show(Some(&private), &private.public())
+        }
+    }
+    fn public_key(&self) -> Result<pubkey::PublicKey, String> {
+        if self.public.trim().is_empty() {
+            pubkey::import_public(&self.private)
+        } else {
+            pubkey::import_public(&self.public)
+        }
+    }
+}
+
+/// Manifest text for Output, verification report, message
+type ManifestResult = (Option<String>, Option<manifest::Report>, Msg);
+
+struct ManifestForm {
+    algorithm: manifest::Algorithm,
+    format: manifest::Format,
+    recursive: bool,
+    dir: Option<path::PathBuf>,
+    /// Last manifest file loaded or saved, left out when it lies in `dir`
+    file: Option<path::PathBuf>,
+    report: Option<manifest::Report>,
+    recv: Option<std::sync::mpsc::Receiver<ManifestResult>>,
+}
+
+impl ManifestForm {
+    fn new() -> Self {
+        Self {
+            algorithm: manifest::Algorithm::Sha256,
+            format: manifest::Format::Sum,
+            recursive: true,
+            dir: None,
+            file: None,
+            report: None,
+            recv: None,
+        }
+    }
+    /// Hashes the directory on a thread and returns the manifest text,
+    /// or verifies it against `text` when given
+    fn start(&mut self, text: Option<String>) -> Result<(), String> {
+        let dir = self
+            .dir
+            .clone()
+            .ok_or("Choose a directory first".to_string())?;
+        let (algorithm, format, recursive) = (self.algorithm, self.format, self.recursive);
+        let skip = self.file.clone();
+        let (send, recv) = std::sync::mpsc::channel();
+        self.recv = Some(recv);
+        self.report = None;
+        std::thread::spawn(move || {
+            let skip = skip.as_deref();
+            let result = match text {
+                Some(text) => match manifest::verify(&dir, &text, algorithm, recursive, skip) {
+                    Ok((algorithm, report)) => {
+                        let msg = Msg::new(
+                            format!("{}: {}", algorithm.name(), report.summary()),
+                            if report.passed() {
+                                MsgType::Info
+                            } else {
+                                MsgType::Warning
+                            },
+                        );
+                        (None, Some(report), msg)
+                    }
+                    Err(e) => (
+                        None,
+                        None,
+                        Msg::new(format!("Error: {}", e), MsgType::Error),
+                    ),
+                },
+                None => match manifest::generate(&dir, recursive, algorithm, skip) {
+                    Ok((entries, errors)) if errors.is_empty() => {
+                        let msg =
+                            Msg::new(format!("Hashed {} files", entries.len()), MsgType::Info);
+                        (
+                            Some(manifest::write(&entries, algorithm, format)),
+                            None,
+                            msg,
+                        )
+                    }
+                    Ok((entries, errors)) => {
+                        let msg = Msg::new(
+                            format!(
+                                "Hashed {} files, skipped:\n{}",
+                                entries.len(),
+                                errors.join("\n")
+                            ),
+                            MsgType::Warning,
+                        );
+                        (
+                            Some(manifest::write(&entries, algorithm, format)),
+                            None,
+                            msg,
+                        )
+                    }
+                    Err(e) => (
+                        None,
+                        None,
+                        Msg::new(format!("Error: {}", e), MsgType::Error),
+                    ),
+                },
+            };
+            // the receiver is gone if the tool was closed meanwhile
+            let _ = send.send(result);
+        });
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+struct StringsView {
+    min_len: usize,
+    ascii: bool,
+    utf8: bool,
+    utf16le: bool,
+    utf16be: bool,
+    /// Accept any BMP character in UTF-16 strings, not only Latin-1
+    utf16_any: bool,
+    results: Vec<strings::FoundString>,
+    filter: String,
+    use_regex: bool,
+    /// Indices into `results` that pass the filter
+    visible: Vec<usize>,
+    filter_error: Option<String>,
+}
+
+impl StringsView {
+    fn new() -> Self {
+        Self {
+            min_len: 4,
+            ascii: true,
+            utf8: false,
+            utf16le: true,
+            utf16be: false,
+            utf16_any: false,
+            results: Vec::new(),
+            filter: String::new(),
+            use_regex: false,
+            visible: Vec::new(),
+            filter_error: None,
+        }
+    }
+    fn encodings(&self) -> Vec<strings::StrEncoding> {
+        let mut list = Vec::new();
+        if self.ascii {
+            list.push(strings::StrEncoding::Ascii);
+        }
+        if self.utf8 {
+            list.push(strings::StrEncoding::Utf8);
+        }
+        if self.utf16le {
+            list.push(strings::StrEncoding::Utf16LE);
+        }
+        if self.utf16be {
+            list.push(strings::StrEncoding::Utf16BE);
+        }
+        list
+    }
+    fn apply_filter(&mut self) {
+        self.visible.clear();
+        self.filter_error = None;
+        if self.filter.is_empty() {
+            self.visible.extend(0..self.results.len());
+        } else if self.use_regex {
+            let re = match regex::Regex::new(&self.filter) {
+                Ok(re) => re,
+                Err(e) => {
+                    // an incomplete pattern is normal while typing, so show it inline
+                    self.filter_error = e.to_string().lines().last().map(str::to_string);
+                    return;
+                }
+            };
+            self.visible.extend(
+                self.results
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, s)| re.is_match(&s.text))
+                    .map(|(i, _)| i),
+            );
+        } else {
+            let filter = self.filter.to_lowercase();
+            self.visible.extend(
+                self.results
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, s)| s.text.to_lowercase().contains(&filter))
+                    .map(|(i, _)| i),
+            );
+        }
+    }
+    fn to_text(&self) -> String {
+        self.visible
+            .iter()
+            .map(|&i| {
+                let s = &self.results[i];
+                format!("{:08X}\t{}\t{}", s.offset, s.encoding.name(), s.text)
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
 }
 
 #[derive(PartialEq, Eq, Hash, Clone)]
 enum SLState {
     Load,
     Save,
+    ExtractDir,
+    LoadCompare,
+    SaveIps,
+    SaveBps,
+    ManifestDir,
+    LoadManifest,
+    SaveManifest,
     None,
 }
 
@@ -54,21 +610,205 @@ impl ByteTool {
             selected_catagory: Catagory::Basic,
             msg: None,
             file_op: SLState::None,
+            // the one used by zip, png and gzip
+            crc_form: CrcForm::named("CRC-32"),
+            checksum_seed: String::new(),
+            entropy_window: 1024,
+            analysis: None,
+            preview_offset: 0,
+            strings_view: StringsView::new(),
+            scan_hits: Vec::new(),
+            scan_selected: Vec::new(),
+            xor_max_keylen: 40,
+            xor_keylen: 0,
+            xor_plain: String::new(),
+            xor_offset: 0,
+            codec: compress::Codec::Gzip,
+            codec_level: 6,
+            compare: Vec::new(),
+            diff_view: None,
+            diff_recv: None,
+            templates: Vec::new(),
+            templates_loaded: false,
+            template_selected: 0,
+            template_fields: Vec::new(),
+            template_error: None,
+            scheme: binascii::Scheme::Base32,
+            export_format: export::Format::CArray,
+            export_name: "data".to_string(),
+            cipher_form: CipherForm::new(),
+            kdf_form: KdfForm::new(),
+            sign_form: SignForm::new(),
+            manifest_form: ManifestForm::new(),
+            identified: None,
+            identify_matches: None,
         }
     }
     fn set_input(&mut self, input: Vec<u8>) {
         self.input = input;
-        const MAX_DISPLAY: usize = 64;
-        if self.input.len() > MAX_DISPLAY {
-            self.display_cache = Self::byte2display(&self.input[0..MAX_DISPLAY]) + "...";
-        } else {
-            self.display_cache = Self::byte2display(&self.input);
-        }
+        self.analysis = None;
+        self.preview_offset = 0;
+        self.strings_view.results.clear();
+        self.strings_view.visible.clear();
+        self.scan_hits.clear();
+        self.scan_selected.clear();
+        self.reset_diff();
+        self.template_fields.clear();
+        self.template_error = None;
+        self.refresh_display();
     }
     fn delete_input(&mut self) {
         self.input.clear();
+        self.analysis = None;
+        self.preview_offset = 0;
+        self.strings_view.results.clear();
+        self.strings_view.visible.clear();
+        self.scan_hits.clear();
+        self.scan_selected.clear();
+        self.reset_diff();
+        self.template_fields.clear();
+        self.template_error = None;
         self.display_cache.clear();
     }
+    /// Drops the diff and any diff still running, whose hunks would not fit changed buffers
+    fn reset_diff(&mut self) {
+        self.diff_view = None;
+        self.diff_recv = None;
+    }
+    fn refresh_display(&mut self) {
+        const MAX_DISPLAY: usize = 64;
+        let start = self.preview_offset.min(self.input.len());
+        let end = (start + MAX_DISPLAY).min(self.input.len());
+        self.display_cache = Self::byte2display(&self.input[start..end]);
+        if start > 0 {
+            self.display_cache = format!("@{:08X}: ", start) + &self.display_cache;
+        }
+        if end < self.input.len() {
+            self.display_cache.push_str("...");
+        }
+    }
+    fn jump_to(&mut self, offset: usize) {
+        self.preview_offset = offset.min(self.input.len());
+        self.refresh_display();
+    }
+    fn extract_hits(&self, dir: &path::Path) -> Result<usize, String> {
+        let mut count = 0;
+        for (i, hit) in self.scan_hits.iter().enumerate() {
+            if !self.scan_selected[i] {
+                continue;
+            }
+            let file = dir.join(format!("{:08X}.{}", hit.offset, hit.ext));
+            std::fs::write(&file, scan::carve(&self.input, &self.scan_hits, i))
+                .map_err(|e| format!("Failed to write {}: {}", file.to_string_lossy(), e))?;
+            count += 1;
+        }
+        Ok(count)
+    }
+    fn start_diff(&mut self) {
+        let a = self.input.clone();
+        let b = self.compare.clone();
+        let (send, recv) = std::sync::mpsc::channel();
+        self.diff_recv = Some(recv);
+        self.diff_view = None;
+        std::thread::spawn(move || {
+            // very different buffers fall back to a coarser alignment instead of hanging
+            let deadline = std::time::Instant::now() + std::time::Duration::from_secs(10);
+            // the receiver is gone if the tool was closed meanwhile
+            let _ = send.send(diff::diff(&a, &b, Some(deadline)));
+        });
+    }
+    /// One side of a side-by-side row: offset, hex and ASCII columns
+    fn diff_row_job(
+        data: &[u8],
+        range: std::ops::Range<usize>,
+        color: Option<egui::Color32>,
+        ui: &egui::Ui,
+    ) -> egui::text::LayoutJob {
+        let font_id = egui::FontId::monospace(12.0);
+        let normal = egui::TextFormat::simple(font_id.clone(), ui.visuals().text_color());
+        let highlight = egui::TextFormat {
+            color: ui.visuals().strong_text_color(),
+            background: color.unwrap_or_default(),
+            ..egui::TextFormat::simple(font_id, ui.visuals().text_color())
+        };
+        let format = if color.is_some() { &highlight } else { &normal };
+        let mut job = egui::text::LayoutJob::default();
+        if range.is_empty() {
+            job.append(&" ".repeat(9 + DiffView::ROW_BYTES * 4), 0.0, normal);
+            return job;
+        }
+        job.append(&format!("{:08X} ", range.start), 0.0, normal.clone());
+        let bytes = &data[range];
+        let hex: String = bytes.iter().map(|b| format!("{:02x} ", b)).collect();
+        job.append(&hex, 0.0, format.clone());
+        let padding = "   ".repeat(DiffView::ROW_BYTES - bytes.len());
+        job.append(&padding, 0.0, normal.clone());
+        let ascii: String = bytes
+            .iter()
+            .map(|&b| {
+                if (0x20..=0x7e).contains(&b) {
+                    b as char
+                } else {
+                    '.'
+                }
+            })
+            .collect();
+        job.append(&ascii, 0.0, format.clone());
+        job.append(&" ".repeat(DiffView::ROW_BYTES - bytes.len()), 0.0, normal);
+        job
+    }
+    fn load_templates(&mut self) {
+        let (templates, errors) = template::templates();
+        self.templates = templates;
+        self.templates_loaded = true;
+        self.template_selected = 0;
+        if !errors.is_empty() {
+            self.msg = Some(Msg::new(errors.join("\n"), MsgType::Warning));
+        }
+    }
+    fn apply_template(&mut self) {
+        if let Some(t) = self.templates.get(self.template_selected) {
+            (self.template_fields, self.template_error) = template::run(t, &self.input);
+        }
+    }
+    /// Draws a field tree, returning the offset of a clicked field
+    fn field_tree(ui: &mut egui::Ui, fields: &[template::Field], id: egui::Id) -> Option<usize> {
+        let mut jump = None;
+        for (i, field) in fields.iter().enumerate() {
+            let id = id.with(i);
+            if field.children.is_empty() {
+                ui.horizontal(|ui| {
+                    if ui
+                        .link(format!("{:08X}", field.offset))
+                        .on_hover_text(format!("{} bytes, jump to offset in preview", field.length))
+                        .clicked()
+                    {
+                        jump = Some(field.offset);
+                    }
+                    ui.label(&field.name);
+                    ui.separator();
+                    ui.monospace(&field.value);
+                });
+            } else {
+                let header = format!(
+                    "{:08X} {} ({})",
+                    field.offset,
+                    field.name,
+                    crate::format_mem(field.length)
+                );
+                let response = egui::CollapsingHeader::new(header)
+                    .id_salt(id)
+                    .show(ui, |ui| Self::field_tree(ui, &field.children, id));
+                if response.header_response.secondary_clicked() {
+                    jump = Some(field.offset);
+                }
+                if let Some(Some(offset)) = response.body_returned {
+                    jump = Some(offset);
+                }
+            }
+        }
+        jump
+    }
     fn byte2display(input: &[u8]) -> String {
         let output_str = hex::encode(input);
         let output_bytes = output_str.as_bytes();
@@ -85,6 +825,46 @@ impl ByteTool {
 
 impl ToolComponent for ByteTool {
     fn paint_ui(&mut self, ui: &mut egui::Ui, _ctx: &egui::Context) {
+        if let Some(recv) = &self.diff_recv {
+            if let Ok(hunks) = recv.try_recv() {
+                let view = DiffView::new(hunks);
+                self.msg = Some(Msg::new(
+                    format!("Found {} differences", view.changes()),
+                    MsgType::Info,
+                ));
+                self.diff_view = Some(view);
+                self.diff_recv = None;
+            }
+        }
+        if let Some(recv) = &self.kdf_form.recv {
+            if let Ok((output, msg)) = recv.try_recv() {
+                if let Some(output) = output {
+                    self.output = output;
+                }
+                self.msg = Some(msg);
+                self.kdf_form.recv = None;
+            }
+        }
+        if let Some(recv) = &self.sign_form.recv {
+            if let Ok(result) = recv.try_recv() {
+                self.sign_form.recv = None;
+                let result = result.and_then(|key| self.sign_form.show(Some(&key), &key.public()));
+                self.msg = Some(match result {
+                    Ok(_) => Msg::new("Key pair generated".to_string(), MsgType::Info),
+                    Err(e) => Msg::new(format!("Error: {}", e), MsgType::Error),
+                });
+            }
+        }
+        if let Some(recv) = &self.manifest_form.recv {
+            if let Ok((output, report, msg)) = recv.try_recv() {
+                if let Some(output) = output {
+                    self.output = output;
+                }
+                self.manifest_form.report = report;
+                self.msg = Some(msg);
+                self.manifest_form.recv = None;
+            }
+        }
         ui.horizontal(|ui| {
             ui.radio_value(&mut self.selected_catagory, Catagory::Basic, "Basic");
             ui.radio_value(
@@ -97,6 +877,17 @@ impl ToolComponent for ByteTool {
                 Catagory::Summarize,
                 "Summarize",
             );
+            ui.radio_value(&mut self.selected_catagory, Catagory::Strings, "Strings");
+            ui.radio_value(&mut self.selected_catagory, Catagory::Scan, "Scan");
+            ui.radio_value(&mut self.selected_catagory, Catagory::Xor, "XOR");
+            ui.radio_value(&mut self.selected_catagory, Catagory::Compress, "Compress");
+            ui.radio_value(&mut self.selected_catagory, Catagory::Cipher, "Cipher");
+            ui.radio_value(&mut self.selected_catagory, Catagory::Kdf, "KDF");
+            ui.radio_value(&mut self.selected_catagory, Catagory::Sign, "Sign");
+            ui.radio_value(&mut self.selected_catagory, Catagory::Compare, "Compare");
+            ui.radio_value(&mut self.selected_catagory, Catagory::Template, "Template");
+            ui.radio_value(&mut self.selected_catagory, Catagory::Export, "Export");
+            ui.radio_value(&mut self.selected_catagory, Catagory::Manifest, "Manifest");
         });
         match self.selected_catagory {
             Catagory::Basic => {
@@ -124,6 +915,50 @@ impl ToolComponent for ByteTool {
                         }
                     }
                 });
+                ui.horizontal(|ui| {
+                    egui::ComboBox::from_id_salt("tool/byte_tool/binascii")
+                        .selected_text(self.scheme.name())
+                        .show_ui(ui, |ui| {
+                            for scheme in binascii::SCHEMES {
+                                ui.selectable_value(&mut self.scheme, scheme, scheme.name());
+                            }
+                        });
+                    if ui.button("Encode").clicked() {
+                        match binascii::encode(&self.input, self.scheme) {
+                            Ok(v) => self.output = v,
+                            Err(e) => {
+                                self.msg = Some(Msg::new(format!("Error: {}", e), MsgType::Error))
+                            }
+                        }
+                    }
+                    if ui.button("Decode").clicked() {
+                        match binascii::decode(&self.output, self.scheme) {
+                            Ok(v) => self.set_input(v),
+                            Err(e) => {
+                                self.msg = Some(Msg::new(format!("Error: {}", e), MsgType::Error))
+                            }
+                        }
+                    }
+                    if ui
+                        .button("Auto Decode")
+                        .on_hover_text("Try every scheme on Output and keep the first that decodes")
+                        .clicked()
+                    {
+                        match binascii::decode_auto(&self.output) {
+                            Ok((scheme, v)) => {
+                                self.scheme = scheme;
+                                self.msg = Some(Msg::new(
+                                    format!("Decoded as {}", scheme.name()),
+                                    MsgType::Info,
+                                ));
+                                self.set_input(v);
+                            }
+                            Err(e) => {
+                                self.msg = Some(Msg::new(format!("Error: {}", e), MsgType::Error))
+                            }
+                        }
+                    }
+                });
                 ui.horizontal(|ui| {
                     if ui.button("Try ASCII").clicked() {
                         self.output = basic::try_ascii(&self.input);
@@ -141,6 +976,9 @@ impl ToolComponent for ByteTool {
                     ui.radio_value(ht, HashType::GROESTL, "GROESTL");
                     ui.radio_value(ht, HashType::JH, "JH");
                     ui.radio_value(ht, HashType::OTHER, "Other");
+                    ui.radio_value(ht, HashType::Crc, "CRC");
+                    ui.radio_value(ht, HashType::Checksum, "Checksum");
+                    ui.radio_value(ht, HashType::Identify, "Identify");
                 });
                 ui.horizontal(|ui| match ht {
                     HashType::MDSome => {
@@ -326,7 +1164,228 @@ impl ToolComponent for ByteTool {
                             self.output = hashes::whirlpool(&self.input);
                         }
                     }
+                    HashType::Crc => {
+                        let form = &mut self.crc_form;
+                        let before = form.preset;
+                        egui::ComboBox::from_id_salt("tool/byte_tool/crc_preset")
+                            .selected_text(checksums::CRC_CATALOGUE[form.preset].0)
+                            .show_ui(ui, |ui| {
+                                for (i, (name, _, _)) in checksums::CRC_CATALOGUE.iter().enumerate()
+                                {
+                                    ui.selectable_value(&mut form.preset, i, *name);
+                                }
+                            });
+                        if form.preset != before {
+                            *form = CrcForm::from_preset(form.preset);
+                        }
+                        ui.label("Width:");
+                        ui.add(egui::DragValue::new(&mut form.width).range(1..=64));
+                        ui.checkbox(&mut form.refin, "RefIn");
+                        ui.checkbox(&mut form.refout, "RefOut");
+                        if ui
+                            .button("Calculate")
+                            .on_hover_text("Calculate CRC of Input with the parameters below")
+                            .clicked()
+                        {
+                            match form.params() {
+                                Ok(params) => {
+                                    self.output = checksums::crc_hex(&self.input, &params)
+                                }
+                                Err(e) => {
+                                    self.msg =
+                                        Some(Msg::new(format!("Error: {}", e), MsgType::Error))
+                                }
+                            }
+                        }
+                        if ui
+                            .button("Check")
+                            .on_hover_text("CRC of \"123456789\", to compare with specifications")
+                            .clicked()
+                        {
+                            match form.params() {
+                                Ok(params) => {
+                                    self.output = checksums::crc_hex(b"123456789", &params)
+                                }
+                                Err(e) => {
+                                    self.msg =
+                                        Some(Msg::new(format!("Error: {}", e), MsgType::Error))
+                                }
+                            }
+                        }
+                    }
+                    HashType::Checksum => {
+                        if ui.button("Adler32").clicked() {
+                            self.output = checksums::adler32(&self.input);
+                        }
+                        if ui.button("Fletcher16").clicked() {
+                            self.output = checksums::fletcher16(&self.input);
+                        }
+                        if ui.button("Fletcher32").clicked() {
+                            self.output = checksums::fletcher32(&self.input);
+                        }
+                        if ui.button("Fletcher64").clicked() {
+                            self.output = checksums::fletcher64(&self.input);
+                        }
+                        if ui.button("FNV1-32").clicked() {
+                            self.output = checksums::fnv1_32(&self.input);
+                        }
+                        if ui.button("FNV1a-32").clicked() {
+                            self.output = checksums::fnv1a_32(&self.input);
+                        }
+                        if ui.button("FNV1-64").clicked() {
+                            self.output = checksums::fnv1_64(&self.input);
+                        }
+                        if ui.button("FNV1a-64").clicked() {
+                            self.output = checksums::fnv1a_64(&self.input);
+                        }
+                    }
+                    HashType::Identify => {
+                        if ui
+                            .button("Identify Output")
+                            .on_hover_text(
+                                "List algorithms that could produce the hex, Base64 or crypt string in Output",
+                            )
+                            .clicked()
+                        {
+                            self.identify_matches = None;
+                            match identify::identify(&self.output) {
+                                Ok(identified) => self.identified = Some(identified),
+                                Err(e) => {
+                                    self.identified = None;
+                                    self.msg =
+                                        Some(Msg::new(format!("Error: {}", e), MsgType::Error))
+                                }
+                            }
+                        }
+                        if ui
+                            .add_enabled(
+                                self.identified
+                                    .as_ref()
+                                    .is_some_and(|i| i.digest.is_some()),
+                                egui::Button::new("Match Input"),
+                            )
+                            .on_hover_text("Hash Input with every candidate and compare")
+                            .clicked()
+                        {
+                            if let Some(identified) = &self.identified {
+                                self.identify_matches =
+                                    Some(identify::find_matches(&self.input, identified));
+                            }
+                        }
+                    }
                 });
+                match ht {
+                    HashType::Crc => {
+                        let form = &mut self.crc_form;
+                        ui.horizontal(|ui| {
+                            ui.label("Poly:");
+                            ui.add(egui::TextEdit::singleline(&mut form.poly).desired_width(140.0));
+                            ui.label("Init:");
+                            ui.add(egui::TextEdit::singleline(&mut form.init).desired_width(140.0));
+                            ui.label("XorOut:");
+                            ui.add(
+                                egui::TextEdit::singleline(&mut form.xorout).desired_width(140.0),
+                            );
+                        });
+                    }
+                    HashType::Checksum => {
+                        ui.horizontal(|ui| {
+                            ui.label("Seed/Key (HEX):")
+                                .on_hover_text(
+                                    "Seed for xxHash/Murmur3, or 16-byte key for SipHash. Empty means zero.",
+                                );
+                            ui.add(
+                                egui::TextEdit::singleline(&mut self.checksum_seed)
+                                    .desired_width(280.0),
+                            );
+                        });
+                        ui.horizontal(|ui| {
+                            let seeded: [(&str, checksums::SeededFn); 7] = [
+                                ("xxHash32", checksums::xxh32),
+                                ("xxHash64", checksums::xxh64),
+                                ("XXH3-64", checksums::xxh3_64),
+                                ("XXH3-128", checksums::xxh3_128),
+                                ("Murmur3-32", checksums::murmur3_32),
+                                ("Murmur3-x86-128", checksums::murmur3_x86_128),
+                                ("Murmur3-x64-128", checksums::murmur3_x64_128),
+                            ];
+                            for (name, func) in seeded {
+                                if ui.button(name).clicked() {
+                                    match checksums::parse_seed(&self.checksum_seed) {
+                                        Ok(seed) => self.output = func(&self.input, seed),
+                                        Err(e) => {
+                                            self.msg = Some(Msg::new(
+                                                format!("Error: {}", e),
+                                                MsgType::Error,
+                                            ))
+                                        }
+                                    }
+                                }
+                            }
+                        });
+                        ui.horizontal(|ui| {
+                            let keyed: [(&str, checksums::KeyedFn); 3] = [
+                                ("SipHash-2-4", checksums::siphash24),
+                                ("SipHash-1-3", checksums::siphash13),
+                                ("SipHash-2-4-128", checksums::siphash24_128),
+                            ];
+                            for (name, func) in keyed {
+                                if ui.button(name).clicked() {
+                                    match checksums::parse_sip_key(&self.checksum_seed) {
+                                        Ok(key) => self.output = func(&self.input, &key),
+                                        Err(e) => {
+                                            self.msg = Some(Msg::new(
+                                                format!("Error: {}", e),
+                                                MsgType::Error,
+                                            ))
+                                        }
+                                    }
+                                }
+                            }
+                        });
+                    }
+                    HashType::Identify => {
+                        if let Some(identified) = &self.identified {
+                            ui.label(format!(
+                                "Format: {}, {} candidates",
+                                identified.format,
+                                identified.candidates.len()
+                            ));
+                            if let Some(note) = &identified.note {
+                                ui.label(note);
+                            }
+                            if let Some(matches) = &self.identify_matches {
+                                if matches.is_empty() {
+                                    ui.colored_label(
+                                        ui.visuals().warn_fg_color,
+                                        "No candidate reproduces the digest from Input",
+                                    );
+                                } else {
+                                    ui.colored_label(
+                                        egui::Color32::from_rgb(0x2e, 0xa0, 0x43),
+                                        format!("Matches: {}", matches.join(", ")),
+                                    );
+                                }
+                            }
+                            egui::ScrollArea::vertical()
+                                .id_salt("tool/byte_tool/identify")
+                                .max_height(120.0)
+                                .show(ui, |ui| {
+                                    ui.horizontal_wrapped(|ui| {
+                                        for candidate in &identified.candidates {
+                                            let _ = ui.selectable_label(
+                                                self.identify_matches
+                                                    .as_ref()
+                                                    .is_some_and(|m| m.contains(&candidate.name)),
+                                                &candidate.name,
+                                            );
+                                        }
+                                    });
+                                });
+                        }
+                    }
+                    _ => {}
+                }
             }
             Catagory::Summarize => {
                 ui.horizontal(|ui| {
@@ -432,53 +1491,1160 @@ impl ToolComponent for ByteTool {
                         self.output = summarize::format_guess(&self.input);
                     }
                 });
-            }
-        }
-        ui.separator();
-        let width = ui.available_width();
-        ui.horizontal(|ui| {
-            let mut cursor = ui.cursor();
-            cursor.set_width(width / 2.0 - 5.0);
-            ui.allocate_new_ui(egui::UiBuilder::new().max_rect(cursor), |ui| {
-                ui.vertical(|ui| {
-                    ui.horizontal(|ui| {
-                        if ui.button("Clear").clicked() {
-                            self.delete_input();
-                        }
-                        if ui.button("Load").clicked() {
-                            self.file_op = SLState::Load;
-                        }
-                        if ui.button("Save").clicked() {
-                            self.file_op = SLState::Save;
-                        }
-                    });
-                    ui.label("Preview HEX");
-                    ui.add_enabled(
-                        false,
-                        egui::TextEdit::multiline(&mut self.display_cache)
-                            .desired_width(f32::INFINITY)
-                            .desired_rows(9),
+                ui.horizontal(|ui| {
+                    if ui
+                        .button("Entropy")
+                        .on_hover_text("Shannon entropy of the whole input, in bits per byte")
+                        .clicked()
+                    {
+                        self.output = format!("{:.6}", summarize::shannon_entropy(&self.input));
+                    }
+                    if ui
+                        .button("Randomness")
+                        .on_hover_text("Chi-square, mean, Monte Carlo Pi and serial correlation")
+                        .clicked()
+                    {
+                        self.output =
+                            summarize::format_stats(&summarize::randomness_stats(&self.input));
+                    }
+                    ui.label("Window:");
+                    ui.add(
+                        egui::DragValue::new(&mut self.entropy_window)
+                            .speed(16.0)
+                            .range(16..=1048576),
                     );
+                    if ui
+                        .button("Chart")
+                        .on_hover_text("Plot sliding-window entropy and byte histogram")
+                        .clicked()
+                    {
+                        // half-window step, but no more than ~4096 points to keep the plot fast
+                        let step = (self.entropy_window / 2)
+                            .max(self.input.len() / 4096)
+                            .max(1);
+                        self.analysis = Some(Analysis {
+                            entropy: summarize::sliding_entropy(
+                                &self.input,
+                                self.entropy_window,
+                                step,
+                            )
+                            .into_iter()
+                            .map(|(offset, e)| [offset as f64, e])
+                            .collect(),
+                            histogram: summarize::histogram(&self.input),
+                        });
+                    }
+                    if self.analysis.is_some() && ui.button("Hide Chart").clicked() {
+                        self.analysis = None;
+                    }
                 });
-            });
-            cursor = ui.cursor();
-            cursor.set_width(width / 2.0 - 5.0);
-            ui.allocate_new_ui(egui::UiBuilder::new().max_rect(cursor), |ui| {
-                ui.vertical(|ui| {
+                if let Some(analysis) = &self.analysis {
+                    let width = ui.available_width();
                     ui.horizontal(|ui| {
-                        ui.label("Output");
-                        if ui.button("Clear").clicked() {
-                            self.output.clear();
-                        }
-                        if ui.button("Copy").clicked() {
-                            ui.output_mut(|w| {
-                                w.copied_text = self.output.clone();
+                        egui_plot::Plot::new("tool/byte_tool/entropy")
+                            .width(width / 2.0 - 5.0)
+                            .height(180.0)
+                            .include_y(0.0)
+                            .include_y(8.0)
+                            .allow_scroll(false)
+                            .x_axis_label("Offset")
+                            .y_axis_label("Entropy")
+                            .label_formatter(|_, point| {
+                                format!("0x{:X}\n{:.3}", point.x.max(0.0) as usize, point.y)
+                            })
+                            .show(ui, |plot_ui| {
+                                plot_ui.line(egui_plot::Line::new(egui_plot::PlotPoints::from(
+                                    analysis.entropy.clone(),
+                                )));
+                            });
+                        egui_plot::Plot::new("tool/byte_tool/histogram")
+                            .width(width / 2.0 - 5.0)
+                            .height(180.0)
+                            .include_x(0.0)
+                            .include_x(255.0)
+                            .allow_scroll(false)
+                            .x_axis_label("Byte")
+                            .y_axis_label("Count")
+                            .show(ui, |plot_ui| {
+                                let bars = analysis
+                                    .histogram
+                                    .iter()
+                                    .enumerate()
+                                    .map(|(b, &c)| {
+                                        egui_plot::Bar::new(b as f64, c as f64)
+                                            .width(1.0)
+                                            .name(format!("{:02X}", b))
+                                    })
+                                    .collect();
+                                plot_ui.bar_chart(egui_plot::BarChart::new(bars));
                             });
-                        }
                     });
-                    ui.add(
-                        egui::TextEdit::multiline(&mut self.output)
-                            .desired_width(f32::INFINITY)
+                }
+            }
+            Catagory::Strings => {
+                let view = &mut self.strings_view;
+                let mut jump = None;
+                ui.horizontal(|ui| {
+                    ui.label("Min Length:");
+                    ui.add(egui::DragValue::new(&mut view.min_len).range(1..=256));
+                    ui.checkbox(&mut view.ascii, "ASCII");
+                    ui.checkbox(&mut view.utf8, "UTF-8");
+                    ui.checkbox(&mut view.utf16le, "UTF-16LE");
+                    ui.checkbox(&mut view.utf16be, "UTF-16BE");
+                    ui.checkbox(&mut view.utf16_any, "Any BMP").on_hover_text(
+                        "Let UTF-16 strings use any character of the BMP, e.g. CJK, \
+                         not only Latin-1; also reads misaligned ASCII as noise",
+                    );
+                    if ui.button("Extract").clicked() {
+                        view.results = strings::extract(
+                            &self.input,
+                            view.min_len,
+                            &view.encodings(),
+                            view.utf16_any,
+                        );
+                        view.apply_filter();
+                    }
+                    if ui
+                        .button("To Output")
+                        .on_hover_text("Write the filtered list to Output")
+                        .clicked()
+                    {
+                        self.output = view.to_text();
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Filter:");
+                    let changed = ui.text_edit_singleline(&mut view.filter).changed()
+                        | ui.checkbox(&mut view.use_regex, "Regex").changed();
+                    if changed {
+                        view.apply_filter();
+                    }
+                    ui.label(format!("{} / {}", view.visible.len(), view.results.len()));
+                    if let Some(err) = &view.filter_error {
+                        ui.colored_label(ui.visuals().error_fg_color, err);
+                    }
+                });
+                const ROW_HEIGHT: f32 = 18.0;
+                egui::ScrollArea::vertical()
+                    .id_salt("tool/byte_tool/strings")
+                    .max_height(ROW_HEIGHT * 12.0)
+                    .auto_shrink([false, true])
+                    .show_rows(ui, ROW_HEIGHT, view.visible.len(), |ui, range| {
+                        for &i in &view.visible[range] {
+                            let found = &view.results[i];
+                            ui.horizontal(|ui| {
+                                if ui
+                                    .link(format!("{:08X}", found.offset))
+                                    .on_hover_text("Jump to offset in preview")
+                                    .clicked()
+                                {
+                                    jump = Some(found.offset);
+                                }
+                                ui.label(found.encoding.name());
+                                ui.separator();
+                                ui.label(&found.text);
+                            });
+                        }
+                    });
+                if let Some(offset) = jump {
+                    self.jump_to(offset);
+                }
+            }
+            Catagory::Scan => {
+                ui.horizontal(|ui| {
+                    if ui
+                        .button("Scan")
+                        .on_hover_text("Look for known file signatures in the input")
+                        .clicked()
+                    {
+                        self.scan_hits = scan::scan(&self.input);
+                        self.scan_selected = vec![false; self.scan_hits.len()];
+                        self.msg = Some(Msg::new(
+                            format!("Found {} signatures", self.scan_hits.len()),
+                            MsgType::Info,
+                        ));
+                    }
+                    if ui.button("Select All").clicked() {
+                        self.scan_selected.iter_mut().for_each(|s| *s = true);
+                    }
+                    if ui.button("Select None").clicked() {
+                        self.scan_selected.iter_mut().for_each(|s| *s = false);
+                    }
+                    if ui
+                        .button("Extract")
+                        .on_hover_text(
+                            "Write selected hits to a directory. Hits of unknown length run until the next hit.",
+                        )
+                        .clicked()
+                    {
+                        if self.scan_selected.iter().any(|s| *s) {
+                            self.file_op = SLState::ExtractDir;
+                        } else {
+                            self.msg = Some(Msg::new(
+                                "Nothing selected".to_string(),
+                                MsgType::Warning,
+                            ));
+                        }
+                    }
+                    if ui
+                        .button("To Output")
+                        .on_hover_text("Write the hit list to Output")
+                        .clicked()
+                    {
+                        self.output = self
+                            .scan_hits
+                            .iter()
+                            .map(|h| {
+                                format!(
+                                    "{:08X}\t{}\t{}",
+                                    h.offset,
+                                    h.length.map_or("?".to_string(), |l| l.to_string()),
+                                    h.name
+                                )
+                            })
+                            .collect::<Vec<String>>()
+                            .join("\n");
+                    }
+                });
+                let mut jump = None;
+                const ROW_HEIGHT: f32 = 18.0;
+                egui::ScrollArea::vertical()
+                    .id_salt("tool/byte_tool/scan")
+                    .max_height(ROW_HEIGHT * 12.0)
+                    .auto_shrink([false, true])
+                    .show_rows(ui, ROW_HEIGHT, self.scan_hits.len(), |ui, range| {
+                        for i in range {
+                            let hit = &self.scan_hits[i];
+                            ui.horizontal(|ui| {
+                                ui.checkbox(&mut self.scan_selected[i], "");
+                                if ui
+                                    .link(format!("{:08X}", hit.offset))
+                                    .on_hover_text("Jump to offset in preview")
+                                    .clicked()
+                                {
+                                    jump = Some(hit.offset);
+                                }
+                                ui.label(match hit.length {
+                                    Some(length) => crate::format_mem(length),
+                                    None => "?".to_string(),
+                                });
+                                ui.separator();
+                                ui.label(hit.name);
+                            });
+                        }
+                    });
+                if let Some(offset) = jump {
+                    self.jump_to(offset);
+                }
+            }
+            Catagory::Xor => {
+                ui.horizontal(|ui| {
+                    if ui
+                        .button("Single Byte")
+                        .on_hover_text("Try all 256 keys, ranked by English score")
+                        .clicked()
+                    {
+                        self.output = xor::single_byte_report(&self.input, 16);
+                    }
+                    if ui
+                        .button("Key Length")
+                        .on_hover_text(
+                            "Rank repeating key lengths by Hamming distance, with Kasiski votes",
+                        )
+                        .clicked()
+                    {
+                        self.output = xor::keylen_report(&self.input, self.xor_max_keylen);
+                    }
+                    ui.label("Max:");
+                    ui.add(egui::DragValue::new(&mut self.xor_max_keylen).range(2..=256));
+                    if ui
+                        .button("Solve")
+                        .on_hover_text("Solve a repeating key column by column")
+                        .clicked()
+                    {
+                        match xor::solve_report(&self.input, self.xor_keylen, self.xor_max_keylen) {
+                            Ok(v) => self.output = v,
+                            Err(e) => {
+                                self.msg = Some(Msg::new(format!("Error: {}", e), MsgType::Error))
+                            }
+                        }
+                    }
+                    ui.label("Key Length:");
+                    ui.add(egui::DragValue::new(&mut self.xor_keylen).range(0..=256))
+                        .on_hover_text("0 means use the best estimate");
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Known Plaintext:");
+                    ui.text_edit_singleline(&mut self.xor_plain);
+                    ui.label("Offset:");
+                    ui.add(
+                        egui::DragValue::new(&mut self.xor_offset)
+                            .range(0..=self.input.len())
+                            .hexadecimal(1, false, true),
+                    );
+                    if ui
+                        .button("Recover Key")
+                        .on_hover_text("XOR the known plaintext at the offset to recover the key")
+                        .clicked()
+                    {
+                        match xor::known_plaintext(
+                            &self.input,
+                            self.xor_plain.as_bytes(),
+                            self.xor_offset,
+                        ) {
+                            Ok(v) => self.output = v,
+                            Err(e) => {
+                                self.msg = Some(Msg::new(format!("Error: {}", e), MsgType::Error))
+                            }
+                        }
+                    }
+                    if ui
+                        .button("Crib Drag")
+                        .on_hover_text("List offsets where the plaintext gives a printable key")
+                        .clicked()
+                    {
+                        self.output = xor::crib_drag(&self.input, self.xor_plain.as_bytes());
+                    }
+                });
+            }
+            Catagory::Compress => {
+                ui.horizontal(|ui| {
+                    let before = self.codec;
+                    egui::ComboBox::from_id_salt("tool/byte_tool/codec")
+                        .selected_text(self.codec.name())
+                        .show_ui(ui, |ui| {
+                            for codec in compress::CODECS {
+                                ui.selectable_value(&mut self.codec, codec, codec.name());
+                            }
+                        });
+                    if self.codec != before {
+                        if let Some((_, _, default)) = self.codec.levels() {
+                            self.codec_level = default;
+                        }
+                    }
+                    if let Some((min, max, _)) = self.codec.levels() {
+                        ui.label("Level:");
+                        ui.add(egui::DragValue::new(&mut self.codec_level).range(min..=max));
+                    }
+                    if ui
+                        .button("Compress")
+                        .on_hover_text("Replace Input with its compressed form")
+                        .clicked()
+                    {
+                        let before = self.input.len();
+                        match compress::compress(&self.input, self.codec, self.codec_level) {
+                            Ok(v) => {
+                                self.msg = Some(Msg::new(
+                                    format!(
+                                        "{} -> {}",
+                                        crate::format_mem(before),
+                                        crate::format_mem(v.len())
+                                    ),
+                                    MsgType::Info,
+                                ));
+                                self.set_input(v);
+                            }
+                            Err(e) => {
+                                self.msg = Some(Msg::new(format!("Error: {}", e), MsgType::Error))
+                            }
+                        }
+                    }
+                    if ui
+                        .button("Decompress")
+                        .on_hover_text("Replace Input with its decompressed form")
+                        .clicked()
+                    {
+                        let before = self.input.len();
+                        let limit = compress::DECOMPRESS_LIMIT;
+                        match compress::decompress(&self.input, self.codec, limit) {
+                            Ok((v, truncated)) => {
+                                let sizes = format!(
+                                    "{} -> {}",
+                                    crate::format_mem(before),
+                                    crate::format_mem(v.len())
+                                );
+                                self.msg = Some(if truncated {
+                                    Msg::new(
+                                        format!("{}, cut off at the limit", sizes),
+                                        MsgType::Warning,
+                                    )
+                                } else {
+                                    Msg::new(sizes, MsgType::Info)
+                                });
+                                self.set_input(v);
+                            }
+                            Err(e) => {
+                                self.msg = Some(Msg::new(format!("Error: {}", e), MsgType::Error))
+                            }
+                        }
+                    }
+                    if ui
+                        .button("Detect")
+                        .on_hover_text("Suggest a decompressor from the stream header")
+                        .clicked()
+                    {
+                        let found = compress::detect(&self.input);
+                        match found.first() {
+                            Some(codec) => {
+                                self.codec = *codec;
+                                self.output = found
+                                    .iter()
+                                    .map(|c| c.name())
+                                    .collect::<Vec<&str>>()
+                                    .join("\n");
+                            }
+                            None => {
+                                self.msg = Some(Msg::new(
+                                    "No known compressed stream detected".to_string(),
+                                    MsgType::Warning,
+                                ))
+                            }
+                        }
+                    }
+                });
+            }
+            Catagory::Compare => {
+                ui.horizontal(|ui| {
+                    if ui
+                        .button("Load B")
+                        .on_hover_text("Load the file to compare Input (A) with")
+                        .clicked()
+                    {
+                        self.file_op = SLState::LoadCompare;
+                    }
+                    ui.label(format!(
+                        "A: {}  B: {}",
+                        crate::format_mem(self.input.len()),
+                        crate::format_mem(self.compare.len())
+                    ));
+                    if self.diff_recv.is_some() {
+                        ui.spinner();
+                    } else if ui
+                        .button("Diff")
+                        .on_hover_text("Align B against A, allowing inserted and deleted bytes")
+                        .clicked()
+                    {
+                        self.start_diff();
+                    }
+                    if let Some(view) = &mut self.diff_view {
+                        if ui.button("< Prev").clicked() {
+                            view.step(false);
+                        }
+                        if ui.button("Next >").clicked() {
+                            view.step(true);
+                        }
+                        if let Some(current) = view.current {
+                            let hunk = &view.hunks[current];
+                            ui.label(format!(
+                                "{} A {:08X}+{} B {:08X}+{}",
+                                hunk.change.name(),
+                                hunk.a.start,
+                                hunk.a.len(),
+                                hunk.b.start,
+                                hunk.b.len()
+                            ));
+                        }
+                    }
+                });
+                ui.horizontal(|ui| {
+                    if let Some(view) = &self.diff_view {
+                        if ui
+                            .button("Summary")
+                            .on_hover_text("Write the list of differences to Output")
+                            .clicked()
+                        {
+                            self.output =
+                                diff::summary(&view.hunks, self.input.len(), self.compare.len());
+                        }
+                        if ui
+                            .button("Save IPS")
+                            .on_hover_text(
+                                "Patch turning A into B. IPS cannot shift data, so it is a byte for byte patch.",
+                            )
+                            .clicked()
+                        {
+                            self.file_op = SLState::SaveIps;
+                        }
+                        if ui
+                            .button("Save BPS")
+                            .on_hover_text("Patch turning A into B, using the aligned diff")
+                            .clicked()
+                        {
+                            self.file_op = SLState::SaveBps;
+                        }
+                    }
+                });
+                if let Some(view) = &mut self.diff_view {
+                    const ROW_HEIGHT: f32 = 16.0;
+                    let mut area = egui::ScrollArea::vertical()
+                        .id_salt("tool/byte_tool/diff")
+                        .max_height(ROW_HEIGHT * 16.0)
+                        .auto_shrink([false, true]);
+                    if let Some(row) = view.scroll_to.take() {
+                        let spacing = ui.spacing().item_spacing.y;
+                        area = area.vertical_scroll_offset(row as f32 * (ROW_HEIGHT + spacing));
+                    }
+                    let (removed, added) = if ui.visuals().dark_mode {
+                        (
+                            egui::Color32::from_rgb(0x70, 0x20, 0x20),
+                            egui::Color32::from_rgb(0x20, 0x60, 0x20),
+                        )
+                    } else {
+                        (
+                            egui::Color32::from_rgb(0xff, 0xc0, 0xc0),
+                            egui::Color32::from_rgb(0xc0, 0xf0, 0xc0),
+                        )
+                    };
+                    area.show_rows(ui, ROW_HEIGHT, view.rows.len(), |ui, range| {
+                        for &(index, skip) in &view.rows[range] {
+                            let hunk = &view.hunks[index];
+                            let changed = hunk.change != diff::Change::Equal;
+                            let a = (hunk.a.start + skip).min(hunk.a.end)
+                                ..(hunk.a.start + skip + DiffView::ROW_BYTES).min(hunk.a.end);
+                            let b = (hunk.b.start + skip).min(hunk.b.end)
+                                ..(hunk.b.start + skip + DiffView::ROW_BYTES).min(hunk.b.end);
+                            ui.horizontal(|ui| {
+                                let marker = if view.current == Some(index) {
+                                    ">"
+                                } else {
+                                    " "
+                                };
+                                ui.monospace(marker);
+                                ui.label(Self::diff_row_job(
+                                    &self.input,
+                                    a,
+                                    changed.then_some(removed),
+                                    ui,
+                                ));
+                                ui.separator();
+                                ui.label(Self::diff_row_job(
+                                    &self.compare,
+                                    b,
+                                    changed.then_some(added),
+                                    ui,
+                                ));
+                            });
+                        }
+                    });
+                }
+            }
+            Catagory::Template => {
+                if !self.templates_loaded {
+                    self.load_templates();
+                }
+                ui.horizontal(|ui| {
+                    let selected = self
+                        .templates
+                        .get(self.template_selected)
+                        .map_or("", |t| t.name.as_str());
+                    egui::ComboBox::from_id_salt("tool/byte_tool/template")
+                        .selected_text(selected)
+                        .show_ui(ui, |ui| {
+                            for (i, t) in self.templates.iter().enumerate() {
+                                ui.selectable_value(
+                                    &mut self.template_selected,
+                                    i,
+                                    format!("{} ({})", t.name, t.origin),
+                                );
+                            }
+                        });
+                    if ui
+                        .button("Detect")
+                        .on_hover_text("Pick the template whose magic matches the input")
+                        .clicked()
+                    {
+                        match template::detect(&self.templates, &self.input) {
+                            Some(i) => {
+                                self.template_selected = i;
+                                self.apply_template();
+                            }
+                            None => {
+                                self.msg = Some(Msg::new(
+                                    "No template matches the input".to_string(),
+                                    MsgType::Warning,
+                                ))
+                            }
+                        }
+                    }
+                    if ui.button("Apply").clicked() {
+                        self.apply_template();
+                    }
+                    let dir = crate::sub_path(template::TEMPLATE_DIR)
+                        .map(|p| p.to_string_lossy().into_owned())
+                        .unwrap_or_default();
+                    if ui
+                        .button("Reload")
+                        .on_hover_text(format!("Reload user templates (*.lua) from {}", dir))
+                        .clicked()
+                    {
+                        self.load_templates();
+                    }
+                });
+                if let Some(err) = &self.template_error {
+                    ui.colored_label(ui.visuals().error_fg_color, err);
+                }
+                let jump = egui::ScrollArea::vertical()
+                    .id_salt("tool/byte_tool/template_tree")
+                    .max_height(300.0)
+                    .auto_shrink([false, true])
+                    .show(ui, |ui| {
+                        Self::field_tree(
+                            ui,
+                            &self.template_fields,
+                            egui::Id::new("tool/byte_tool/template_field"),
+                        )
+                    })
+                    .inner;
+                if let Some(offset) = jump {
+                    self.jump_to(offset);
+                }
+            }
+            Catagory::Export => {
+                ui.horizontal(|ui| {
+                    egui::ComboBox::from_id_salt("tool/byte_tool/export")
+                        .selected_text(self.export_format.name())
+                        .show_ui(ui, |ui| {
+                            for format in export::FORMATS {
+                                ui.selectable_value(&mut self.export_format, format, format.name());
+                            }
+                        });
+                    ui.label("Name:");
+                    ui.add(egui::TextEdit::singleline(&mut self.export_name).desired_width(100.0))
+                        .on_hover_text("Variable name for source code, S-record header, PEM label");
+                    if ui
+                        .button("Export")
+                        .on_hover_text("Write Input to Output in the selected format")
+                        .clicked()
+                    {
+                        self.output =
+                            export::export(&self.input, self.export_format, &self.export_name);
+                    }
+                    if ui
+                        .add_enabled(self.export_format.importable(), egui::Button::new("Import"))
+                        .on_hover_text("Read Output in the selected format back into Input")
+                        .clicked()
+                    {
+                        match export::import(&self.output, self.export_format) {
+                            Ok(imported) => {
+                                if imported.base != 0 {
+                                    self.msg = Some(Msg::new(
+                                        format!("Data starts at address 0x{:X}", imported.base),
+                                        MsgType::Info,
+                                    ));
+                                }
+                                self.set_input(imported.data);
+                            }
+                            Err(e) => {
+                                self.msg = Some(Msg::new(format!("Error: {}", e), MsgType::Error))
+                            }
+                        }
+                    }
+                });
+            }
+            Catagory::Cipher => {
+                let form = &mut self.cipher_form;
+                let mut run = None;
+                ui.horizontal(|ui| {
+                    egui::ComboBox::from_id_salt("tool/byte_tool/cipher_algorithm")
+                        .selected_text(form.algorithm.name())
+                        .show_ui(ui, |ui| {
+                            for algorithm in symmetric::ALGORITHMS {
+                                ui.selectable_value(
+                                    &mut form.algorithm,
+                                    algorithm,
+                                    algorithm.name(),
+                                );
+                            }
+                        });
+                    let modes = form.algorithm.modes();
+                    if !modes.is_empty() {
+                        if !modes.contains(&form.mode) {
+                            form.mode = modes[0];
+                        }
+                        egui::ComboBox::from_id_salt("tool/byte_tool/cipher_mode")
+                            .selected_text(form.mode.name())
+                            .show_ui(ui, |ui| {
+                                for mode in modes {
+                                    ui.selectable_value(&mut form.mode, *mode, mode.name());
+                                }
+                            });
+                    }
+                    let params = form.params(&[], &[]);
+                    if params.padded() {
+                        ui.label("Padding:");
+                        egui::ComboBox::from_id_salt("tool/byte_tool/cipher_padding")
+                            .selected_text(form.padding.name())
+                            .show_ui(ui, |ui| {
+                                for padding in symmetric::PADDINGS {
+                                    ui.selectable_value(&mut form.padding, padding, padding.name());
+                                }
+                            });
+                    }
+                    if ui
+                        .button("Encrypt")
+                        .on_hover_text("Replace Input with its ciphertext")
+                        .clicked()
+                    {
+                        run = Some(true);
+                    }
+                    if ui
+                        .button("Decrypt")
+                        .on_hover_text("Replace Input with its plaintext")
+                        .clicked()
+                    {
+                        run = Some(false);
+                    }
+                });
+                let params = form.params(&[], &[]);
+                let (uses_iv, uses_aad) = (params.uses_iv(), params.uses_aad());
+                let mut fields = vec![("Key:", &mut form.key, &mut form.key_encoding, "key")];
+                if uses_iv {
+                    let label =
+                        if form.mode == symmetric::Mode::Gcm || form.algorithm.modes().is_empty() {
+                            "Nonce:"
+                        } else {
+                            "IV:"
+                        };
+                    fields.push((label, &mut form.iv, &mut form.iv_encoding, "iv"));
+                }
+                for (label, text, encoding, salt) in fields {
+                    ui.horizontal(|ui| {
+                        ui.label(label);
+                        egui::ComboBox::from_id_salt(format!("tool/byte_tool/cipher_{}", salt))
+                            .width(70.0)
+                            .selected_text(encoding.name())
+                            .show_ui(ui, |ui| {
+                                for e in symmetric::KEY_ENCODINGS {
+                                    ui.selectable_value(encoding, e, e.name());
+                                }
+                            });
+                        ui.add(egui::TextEdit::singleline(text).desired_width(f32::INFINITY));
+                    });
+                }
+                if uses_aad {
+                    ui.horizontal(|ui| {
+                        ui.label("AAD (text):");
+                        ui.add(
+                            egui::TextEdit::singleline(&mut form.aad).desired_width(f32::INFINITY),
+                        );
+                    });
+                }
+                let mut hint = format!(
+                    "{} key: {}.",
+                    form.algorithm.name(),
+                    form.algorithm.key_sizes()
+                );
+                if uses_aad {
+                    hint.push_str(" The 16-byte tag follows the ciphertext.");
+                }
+                ui.label(hint);
+                if let Some(encrypt) = run {
+                    match self.cipher_form.run(&self.input, encrypt) {
+                        Ok(v) => self.set_input(v),
+                        Err(e) => {
+                            self.msg = Some(Msg::new(format!("Error: {}", e), MsgType::Error))
+                        }
+                    }
+                }
+            }
+            Catagory::Kdf => {
+                let form = &mut self.kdf_form;
+                let mut start = None;
+                ui.horizontal(|ui| {
+                    egui::ComboBox::from_id_salt("tool/byte_tool/kdf")
+                        .selected_text(form.params.kdf.name())
+                        .show_ui(ui, |ui| {
+                            for kdf in kdf::KDFS {
+                                ui.selectable_value(&mut form.params.kdf, kdf, kdf.name());
+                            }
+                        });
+                    if form.params.kdf.uses_digest() {
+                        egui::ComboBox::from_id_salt("tool/byte_tool/kdf_digest")
+                            .selected_text(form.params.digest.name())
+                            .show_ui(ui, |ui| {
+                                for digest in kdf::KDF_DIGESTS {
+                                    ui.selectable_value(
+                                        &mut form.params.digest,
+                                        digest,
+                                        digest.name(),
+                                    );
+                                }
+                            });
+                    }
+                    let params = &mut form.params;
+                    match params.kdf {
+                        kdf::Kdf::Pbkdf2 => {
+                            ui.label("Iterations:");
+                            ui.add(
+                                egui::DragValue::new(&mut params.iterations).range(1..=u32::MAX),
+                            );
+                        }
+                        kdf::Kdf::Scrypt => {
+                            ui.label("log2(N):");
+                            ui.add(egui::DragValue::new(&mut params.log_n).range(1..=24));
+                            ui.label("r:");
+                            ui.add(egui::DragValue::new(&mut params.block_size).range(1..=1 << 20));
+                            ui.label("p:");
+                            ui.add(
+                                egui::DragValue::new(&mut params.parallelism).range(1..=1 << 20),
+                            );
+                        }
+                        kdf::Kdf::Argon2id | kdf::Kdf::Argon2i | kdf::Kdf::Argon2d => {
+                            ui.label("Iterations:");
+                            ui.add(egui::DragValue::new(&mut params.passes).range(1..=u32::MAX));
+                            ui.label("Memory (KiB):");
+                            ui.add(
+                                egui::DragValue::new(&mut params.memory)
+                                    .range(8..=(kdf::MAX_MEMORY >> 10) as u32),
+                            );
+                            ui.label("Parallelism:");
+                            ui.add(
+                                egui::DragValue::new(&mut params.parallelism).range(1..=0xFFFFFF),
+                            );
+                        }
+                        kdf::Kdf::Bcrypt => {
+                            ui.label("Cost:");
+                            ui.add(egui::DragValue::new(&mut params.cost).range(4..=31));
+                        }
+                        kdf::Kdf::Hkdf => {}
+                    }
+                    if params.kdf != kdf::Kdf::Bcrypt {
+                        ui.label("Length:");
+                        ui.add(egui::DragValue::new(&mut params.output_len).range(1..=1024));
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Salt:");
+                    egui::ComboBox::from_id_salt("tool/byte_tool/kdf_salt")
+                        .width(70.0)
+                        .selected_text(form.salt_encoding.name())
+                        .show_ui(ui, |ui| {
+                            for e in symmetric::KEY_ENCODINGS {
+                                ui.selectable_value(&mut form.salt_encoding, e, e.name());
+                            }
+                        });
+                    ui.add(egui::TextEdit::singleline(&mut form.salt).desired_width(f32::INFINITY));
+                });
+                if form.params.kdf == kdf::Kdf::Hkdf {
+                    ui.horizontal(|ui| {
+                        ui.label("Info (text):");
+                        ui.add(
+                            egui::TextEdit::singleline(&mut form.info).desired_width(f32::INFINITY),
+                        );
+                    });
+                }
+                ui.horizontal(|ui| {
+                    ui.label("Hash:");
+                    ui.add(
+                        egui::TextEdit::singleline(&mut form.encoded)
+                            .hint_text("$argon2id$..., $scrypt$..., $pbkdf2-sha256$..., $2b$...")
+                            .desired_width(f32::INFINITY),
+                    );
+                });
+                ui.horizontal(|ui| {
+                    if form.recv.is_some() {
+                        ui.spinner();
+                        return;
+                    }
+                    if ui
+                        .button("Derive")
+                        .on_hover_text("Derive a key from Input as the password")
+                        .clicked()
+                    {
+                        start = Some(false);
+                    }
+                    if ui
+                        .button("Verify")
+                        .on_hover_text("Check Input as the password against Hash")
+                        .clicked()
+                    {
+                        start = Some(true);
+                    }
+                });
+                if let Some(verify) = start {
+                    if let Err(e) = self.kdf_form.start(self.input.clone(), verify) {
+                        self.msg = Some(Msg::new(format!("Error: {}", e), MsgType::Error));
+                    }
+                }
+            }
+            Catagory::Sign => {
+                let form = &mut self.sign_form;
+                let mut result = None;
+                ui.horizontal(|ui| {
+                    egui::ComboBox::from_id_salt("tool/byte_tool/sign_key_type")
+                        .selected_text(form.key_type.name())
+                        .show_ui(ui, |ui| {
+                            for key_type in pubkey::KEY_TYPES {
+                                ui.selectable_value(&mut form.key_type, key_type, key_type.name());
+                            }
+                        });
+                    if form.key_type == pubkey::KeyType::Rsa {
+                        egui::ComboBox::from_id_salt("tool/byte_tool/sign_rsa_bits")
+                            .width(60.0)
+                            .selected_text(form.rsa_bits.to_string())
+                            .show_ui(ui, |ui| {
+                                for bits in [2048, 3072, 4096] {
+                                    ui.selectable_value(&mut form.rsa_bits, bits, bits.to_string());
+                                }
+                            });
+                    }
+                    if form.recv.is_some() {
+                        ui.spinner();
+                    } else if ui.button("Generate").clicked() {
+                        form.generate();
+                    }
+                    ui.separator();
+                    egui::ComboBox::from_id_salt("tool/byte_tool/sign_format")
+                        .selected_text(form.format.name())
+                        .show_ui(ui, |ui| {
+                            for format in pubkey::KEY_FORMATS {
+                                ui.selectable_value(&mut form.format, format, format.name());
+                            }
+                        });
+                    if ui
+                        .button("Convert")
+                        .on_hover_text("Import the keys below and write them in this format")
+                        .clicked()
+                    {
+                        result = Some(form.convert().map(|_| "Key converted".to_string()));
+                    }
+                });
+                ui.columns(2, |columns| {
+                    columns[0].label("Private Key:");
+                    columns[0].add(
+                        egui::TextEdit::multiline(&mut form.private)
+                            .font(egui::TextStyle::Monospace)
+                            .desired_rows(6)
+                            .desired_width(f32::INFINITY),
+                    );
+                    columns[1].label("Public Key:");
+                    columns[1].add(
+                        egui::TextEdit::multiline(&mut form.public)
+                            .font(egui::TextStyle::Monospace)
+                            .desired_rows(6)
+                            .desired_width(f32::INFINITY),
+                    );
+                });
+                ui.horizontal(|ui| {
+                    if form.key_type == pubkey::KeyType::Rsa {
+                        egui::ComboBox::from_id_salt("tool/byte_tool/sign_padding")
+                            .selected_text(form.padding.name())
+                            .show_ui(ui, |ui| {
+                                for padding in pubkey::RSA_PADDINGS {
+                                    ui.selectable_value(&mut form.padding, padding, padding.name());
+                                }
+                            });
+                    }
+                    if ui
+                        .button("Sign")
+                        .on_hover_text(
+                            "Sign Input with the private key, signature as hex in Output",
+                        )
+                        .clicked()
+                    {
+                        match pubkey::import_private(&form.private)
+                            .and_then(|key| pubkey::sign(&key, &self.input, form.padding))
+                        {
+                            Ok(signature) => {
+                                self.output = hex::encode(signature);
+                                result = Some(Ok("Signed".to_string()));
+                            }
+                            Err(e) => result = Some(Err(e)),
+                        }
+                    }
+                    if ui
+                        .button("Verify Output")
+                        .on_hover_text("Check the hex or Base64 signature in Output against Input")
+                        .clicked()
+                    {
+                        let signature = basic::hex_decode(&self.output)
+                            .or_else(|_| basic::base64_decode(&self.output))
+                            .map_err(|_| "Output is not a hex or Base64 signature".to_string());
+                        let verified = signature.and_then(|signature| {
+                            pubkey::verify(
+                                &form.public_key()?,
+                                &self.input,
+                                &signature,
+                                form.padding,
+                            )
+                        });
+                        match verified {
+                            Ok(true) => {
+                                self.msg =
+                                    Some(Msg::new("Signature is valid".to_string(), MsgType::Info))
+                            }
+                            Ok(false) => {
+                                self.msg = Some(Msg::new(
+                                    "Signature is NOT valid".to_string(),
+                                    MsgType::Warning,
+                                ))
+                            }
+                            Err(e) => result = Some(Err(e)),
+                        }
+                    }
+                    ui.label("ECDSA and RSA sign SHA-256, SM2 signs SM3 with the default ID");
+                });
+                for (name, fingerprint) in &form.fingerprints {
+                    ui.horizontal(|ui| {
+                        ui.label(format!("{}:", name));
+                        ui.monospace(fingerprint);
+                    });
+                }
+                match result {
+                    Some(Ok(text)) => self.msg = Some(Msg::new(text, MsgType::Info)),
+                    Some(Err(e)) => {
+                        self.msg = Some(Msg::new(format!("Error: {}", e), MsgType::Error))
+                    }
+                    None => {}
+                }
+            }
+            Catagory::Manifest => {
+                let form = &mut self.manifest_form;
+                let mut start = None;
+                ui.horizontal(|ui| {
+                    if ui.button("Choose Dir").clicked() {
+                        self.file_op = SLState::ManifestDir;
+                    }
+                    ui.checkbox(&mut form.recursive, "Recursive");
+                    match &form.dir {
+                        Some(dir) => ui.label(dir.to_string_lossy()),
+                        None => ui.label("No directory chosen"),
+                    };
+                });
+                ui.horizontal(|ui| {
+                    egui::ComboBox::from_id_salt("tool/byte_tool/manifest_algorithm")
+                        .selected_text(form.algorithm.name())
+                        .show_ui(ui, |ui| {
+                            for algorithm in manifest::ALGORITHMS {
+                                ui.selectable_value(
+                                    &mut form.algorithm,
+                                    algorithm,
+                                    algorithm.name(),
+                                );
+                            }
+                        });
+                    egui::ComboBox::from_id_salt("tool/byte_tool/manifest_format")
+                        .selected_text(form.format.name())
+                        .show_ui(ui, |ui| {
+                            for format in manifest::FORMATS {
+                                ui.selectable_value(&mut form.format, format, format.name());
+                            }
+                        });
+                    if form.recv.is_some() {
+                        ui.spinner();
+                        return;
+                    }
+                    if ui
+                        .button("Generate")
+                        .on_hover_text("Hash every file of the directory into Output")
+                        .clicked()
+                    {
+                        start = Some(None);
+                    }
+                    if ui
+                        .button("Verify Output")
+                        .on_hover_text("Check the directory against the manifest in Output")
+                        .clicked()
+                    {
+                        start = Some(Some(self.output.clone()));
+                    }
+                    if ui.button("Load Manifest").clicked() {
+                        self.file_op = SLState::LoadManifest;
+                    }
+                    if ui.button("Save Manifest").clicked() {
+                        self.file_op = SLState::SaveManifest;
+                    }
+                });
+                if let Some(report) = &form.report {
+                    egui::ScrollArea::vertical()
+                        .id_salt("tool/byte_tool/manifest_report")
+                        .max_height(160.0)
+                        .show(ui, |ui| {
+                            ui.label(format!("{} files OK", report.ok));
+                            for (title, paths) in [
+                                ("Modified", &report.modified),
+                                ("Missing", &report.missing),
+                                ("Extra", &report.extra),
+                                ("Unreadable", &report.unreadable),
+                            ] {
+                                if paths.is_empty() {
+                                    continue;
+                                }
+                                ui.colored_label(
+                                    ui.visuals().warn_fg_color,
+                                    format!("{} ({}):", title, paths.len()),
+                                );
+                                for path in paths {
+                                    ui.label(format!("    {}", path));
+                                }
+                            }
+                        });
+                }
+                if let Some(text) = start {
+                    if let Err(e) = self.manifest_form.start(text) {
+                        self.msg = Some(Msg::new(format!("Error: {}", e), MsgType::Error));
+                    }
+                }
+            }
+        }
+        ui.separator();
+        let width = ui.available_width();
+        ui.horizontal(|ui| {
+            let mut cursor = ui.cursor();
+            cursor.set_width(width / 2.0 - 5.0);
+            ui.allocate_new_ui(egui::UiBuilder::new().max_rect(cursor), |ui| {
+                ui.vertical(|ui| {
+                    ui.horizontal(|ui| {
+                        if ui.button("Clear").clicked() {
+                            self.delete_input();
+                        }
+                        if ui.button("Load").clicked() {
+                            self.file_op = SLState::Load;
+                        }
+                        if ui.button("Save").clicked() {
+                            self.file_op = SLState::Save;
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Preview HEX");
+                        ui.label("Offset:");
+                        if ui
+                            .add(
+                                egui::DragValue::new(&mut self.preview_offset)
+                                    .range(0..=self.input.len())
+                                    .hexadecimal(8, false, true),
+                            )
+                            .changed()
+                        {
+                            self.refresh_display();
+                        }
+                    });
+                    ui.add_enabled(
+                        false,
+                        egui::TextEdit::multiline(&mut self.display_cache)
+                            .desired_width(f32::INFINITY)
+                            .desired_rows(9),
+                    );
+                });
+            });
+            cursor = ui.cursor();
+            cursor.set_width(width / 2.0 - 5.0);
+            ui.allocate_new_ui(egui::UiBuilder::new().max_rect(cursor), |ui| {
+                ui.vertical(|ui| {
+                    ui.horizontal(|ui| {
+                        ui.label("Output");
+                        if ui.button("Clear").clicked() {
+                            self.output.clear();
+                        }
+                        if ui.button("Copy").clicked() {
+                            ui.output_mut(|w| {
+                                w.copied_text = self.output.clone();
+                            });
+                        }
+                    });
+                    ui.add(
+                        egui::TextEdit::multiline(&mut self.output)
+                            .desired_width(f32::INFINITY)
                             .desired_rows(10),
                     );
                 });
@@ -509,6 +2675,83 @@ impl ToolComponent for ByteTool {
                     SAVE_FILE,
                 ))
             }
+            SLState::ExtractDir => {
+                self.file_op = SLState::None;
+                Some((
+                    FileOp {
+                        title: "Extract To".to_string(),
+                        filter: Vec::new(),
+                        mode: FileOpMode::Dir,
+                    },
+                    EXTRACT_DIR,
+                ))
+            }
+            SLState::LoadCompare => {
+                self.file_op = SLState::None;
+                Some((
+                    FileOp {
+                        title: "Load B".to_string(),
+                        filter: Vec::new(),
+                        mode: FileOpMode::Open,
+                    },
+                    LOAD_COMPARE,
+                ))
+            }
+            SLState::SaveIps => {
+                self.file_op = SLState::None;
+                Some((
+                    FileOp {
+                        title: "Save IPS Patch".to_string(),
+                        filter: vec!["ips".to_string()],
+                        mode: FileOpMode::Save,
+                    },
+                    SAVE_IPS,
+                ))
+            }
+            SLState::SaveBps => {
+                self.file_op = SLState::None;
+                Some((
+                    FileOp {
+                        title: "Save BPS Patch".to_string(),
+                        filter: vec!["bps".to_string()],
+                        mode: FileOpMode::Save,
+                    },
+                    SAVE_BPS,
+                ))
+            }
+            SLState::ManifestDir => {
+                self.file_op = SLState::None;
+                Some((
+                    FileOp {
+                        title: "Manifest Directory".to_string(),
+                        filter: Vec::new(),
+                        mode: FileOpMode::Dir,
+                    },
+                    MANIFEST_DIR,
+                ))
+            }
+            SLState::LoadManifest => {
+                self.file_op = SLState::None;
+                Some((
+                    FileOp {
+                        title: "Load Manifest".to_string(),
+                        filter: Vec::new(),
+                        mode: FileOpMode::Open,
+                    },
+                    LOAD_MANIFEST,
+                ))
+            }
+            SLState::SaveManifest => {
+                self.file_op = SLState::None;
+                Some((
+                    FileOp {
+                        title: "Save Manifest".to_string(),
+                        filter: Vec::new(),
+                        mode: FileOpMode::Save,
+                    },
+                    SAVE_MANIFEST,
+                ))
+            }
             SLState::None => None,
         }
     }
@@ -535,6 +2778,65 @@ impl ToolComponent for ByteTool {
                         self.msg = Some(Msg::new(format!("Error: {}", e), MsgType::Error));
                     }
                 },
+                EXTRACT_DIR => match self.extract_hits(&fp) {
+                    Ok(count) => {
+                        self.msg = Some(Msg::new(
+                            format!("Extracted {} files", count),
+                            MsgType::Info,
+                        ));
+                    }
+                    Err(e) => {
+                        self.msg = Some(Msg::new(format!("Error: {}", e), MsgType::Error));
+                    }
+                },
+                LOAD_COMPARE => match std::fs::read(fp) {
+                    Ok(v) => {
+                        self.compare = v;
+                        self.reset_diff();
+                        self.msg = Some(Msg::new("Load Success".to_string(), MsgType::Info));
+                    }
+                    Err(e) => {
+                        self.msg = Some(Msg::new(format!("Error: {}", e), MsgType::Error));
+                    }
+                },
+                SAVE_IPS | SAVE_BPS => {
+                    let patch = match &self.diff_view {
+                        Some(_) if id == SAVE_IPS => diff::ips(&self.input, &self.compare),
+                        Some(view) => Ok(diff::bps(&self.input, &self.compare, &view.hunks)),
+                        None => Err("Run Diff first".to_string()),
+                    };
+                    match patch.and_then(|p| std::fs::write(fp, p).map_err(|e| e.to_string())) {
+                        Ok(_) => {
+                            self.msg = Some(Msg::new("Save Success".to_string(), MsgType::Info));
+                        }
+                        Err(e) => {
+                            self.msg = Some(Msg::new(format!("Error: {}", e), MsgType::Error));
+                        }
+                    }
+                }
+                MANIFEST_DIR => {
+                    self.manifest_form.dir = Some(fp);
+                    self.manifest_form.report = None;
+                }
+                LOAD_MANIFEST => match std::fs::read_to_string(&fp) {
+                    Ok(v) => {
+                        self.output = v;
+                        self.manifest_form.file = Some(fp);
+                        self.msg = Some(Msg::new("Load Success".to_string(), MsgType::Info));
+                    }
+                    Err(e) => {
+                        self.msg = Some(Msg::new(format!("Error: {}", e), MsgType::Error));
+                    }
+                },
+                SAVE_MANIFEST => match std::fs::write(&fp, &self.output) {
+                    Ok(_) => {
+                        self.manifest_form.file = Some(fp);
+                        self.msg = Some(Msg::new("Save Success".to_string(), MsgType::Info));
+                    }
+                    Err(e) => {
+                        self.msg = Some(Msg::new(format!("Error: {}", e), MsgType::Error));
+                    }
+                },
                 _ => {}
             }
         }
@@ -0,0 +1,500 @@
+use crate::{find_index_from, rfind_index};
+
+#[derive(Clone, Debug)]
+pub struct Signature {
+    pub name: &'static str,
+    pub magic: &'static [u8],
+    /// Position of the magic relative to the start of the file
+    pub magic_offset: usize,
+    pub ext: &'static str,
+    /// Validates the candidate and estimates its length.
+    /// Receives the buffer from the file start; `Err` rejects the hit,
+    /// `Ok(None)` accepts it without a known length.
+    pub estimate: fn(&[u8]) -> Result<Option<usize>, ()>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Hit {
+    pub offset: usize,
+    pub name: &'static str,
+    pub ext: &'static str,
+    pub length: Option<usize>,
+}
+
+pub const SIGNATURES: &[Signature] = &[
+    sig("ZIP archive", b"PK\x03\x04", 0, "zip", zip_len),
+    sig("PNG image", b"\x89PNG\r\n\x1a\n", 0, "png", png_len),
+    sig("JPEG image", b"\xff\xd8\xff", 0, "jpg", jpeg_len),
+    sig("GIF image", b"GIF87a", 0, "gif", gif_len),
+    sig("GIF image", b"GIF89a", 0, "gif", gif_len),
+    sig("BMP image", b"BM", 0, "bmp", bmp_len),
+    sig("RIFF (WAV/AVI/WebP)", b"RIFF", 0, "riff", riff_len),
+    sig("PDF document", b"%PDF-", 0, "pdf", pdf_len),
+    sig("gzip", b"\x1f\x8b\x08", 0, "gz", gzip_len),
+    sig("bzip2", b"BZh", 0, "bz2", bzip2_len),
+    sig("xz", b"\xfd7zXZ\x00", 0, "xz", xz_len),
+    sig("LZMA", b"\x5d\x00\x00", 0, "lzma", lzma_len),
+    sig("Zstandard", b"\x28\xb5\x2f\xfd", 0, "zst", unknown_len),
+    sig(
+        "7-Zip archive",
+        b"7z\xbc\xaf\x27\x1c",
+        0,
+        "7z",
+        sevenzip_len,
+    ),
+    sig("RAR archive", b"Rar!\x1a\x07", 0, "rar", unknown_len),
+    sig("POSIX tar", b"ustar", 257, "tar", unknown_len),
+    sig("cpio (newc)", b"070701", 0, "cpio", unknown_len),
+    sig("squashfs (LE)", b"hsqs", 0, "squashfs", squashfs_le_len),
+    sig("squashfs (BE)", b"sqsh", 0, "squashfs", squashfs_be_len),
+    sig("cramfs", b"\x45\x3d\xcd\x28", 0, "cramfs", cramfs_len),
+    sig(
+        "U-Boot uImage",
+        b"\x27\x05\x19\x56",
+        0,
+        "uimage",
+        uimage_len,
+    ),
+    sig("ELF executable", b"\x7fELF", 0, "elf", elf_len),
+    sig("PE executable", b"MZ", 0, "exe", pe_len),
+    sig("Java class", b"\xca\xfe\xba\xbe", 0, "class", unknown_len),
+    sig("Android DEX", b"dex\n035\x00", 0, "dex", dex_len),
+    sig(
+        "SQLite database",
+        b"SQLite format 3\x00",
+        0,
+        "sqlite",
+        sqlite_len,
+    ),
+];
+
+const fn sig(
+    name: &'static str,
+    magic: &'static [u8],
+    magic_offset: usize,
+    ext: &'static str,
+    estimate: fn(&[u8]) -> Result<Option<usize>, ()>,
+) -> Signature {
+    Signature {
+        name,
+        magic,
+        magic_offset,
+        ext,
+        estimate,
+    }
+}
+
+/// Scans the whole buffer for every known signature, sorted by offset
+pub fn scan(input: &[u8]) -> Vec<Hit> {
+    let mut hits = Vec::new();
+    for signature in SIGNATURES {
+        let mut from = signature.magic_offset;
+        while let Some(found) = find_index_from(input, signature.magic, from) {
+            from = found + 1;
+            let offset = found - signature.magic_offset;
+            if let Ok(length) = (signature.estimate)(&input[offset..]) {
+                // estimates come from untrusted headers and may be anything
+                let length = length
+                    .filter(|&l| l > 0 && offset.checked_add(l).is_some_and(|e| e <= input.len()));
+                hits.push(Hit {
+                    offset,
+                    name: signature.name,
+                    ext: signature.ext,
+                    length,
+                });
+                // every member of a ZIP starts with a local header, they're not archives
+                // of their own; one without an end record runs to the end of the input
+                if signature.ext == "zip" {
+                    from = length.map_or(input.len(), |l| offset + l);
+                }
+            }
+        }
+    }
+    hits.sort_by_key(|h| h.offset);
+    hits
+}
+
+/// The bytes of a hit. When its length is unknown, it runs until the next hit or the end.
+pub fn carve<'a>(input: &'a [u8], hits: &[Hit], index: usize) -> &'a [u8] {
+    let hit = &hits[index];
+    let end = match hit.length {
+        Some(length) => hit.offset.saturating_add(length),
+        None => hits[index + 1..]
+            .iter()
+            .map(|h| h.offset)
+            .find(|&o| o > hit.offset)
+            .unwrap_or(input.len()),
+    };
+    &input[hit.offset..end.min(input.len())]
+}
+
+fn u16_le(data: &[u8], offset: usize) -> Result<u16, ()> {
+    let b = data.get(offset..offset + 2).ok_or(())?;
+    Ok(u16::from_le_bytes([b[0], b[1]]))
+}
+
+fn u32_le(data: &[u8], offset: usize) -> Result<u32, ()> {
+    let b = data.get(offset..offset + 4).ok_or(())?;
+    Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+fn u32_be(data: &[u8], offset: usize) -> Result<u32, ()> {
+    let b = data.get(offset..offset + 4).ok_or(())?;
+    Ok(u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+fn u64_le(data: &[u8], offset: usize) -> Result<u64, ()> {
+    let b = data.get(offset..offset + 8).ok_or(())?;
+    let mut buf = [0u8; 8];
+    buf.copy_from_slice(b);
+    Ok(u64::from_le_bytes(buf))
+}
+
+fn u64_be(data: &[u8], offset: usize) -> Result<u64, ()> {
+    let b = data.get(offset..offset + 8).ok_or(())?;
+    let mut buf = [0u8; 8];
+    buf.copy_from_slice(b);
+    Ok(u64::from_be_bytes(buf))
+}
+
+fn unknown_len(_: &[u8]) -> Result<Option<usize>, ()> {
+    Ok(None)
+}
+
+fn zip_len(data: &[u8]) -> Result<Option<usize>, ()> {
+    // version needed to extract is small, and the compression method is a known one
+    if u16_le(data, 4)? > 63 || u16_le(data, 8)? > 99 {
+        return Err(());
+    }
+    // an archive ends with the end of central directory record plus its comment
+    Ok(find_index_from(data, b"PK\x05\x06", 0).and_then(|eocd| {
+        let comment = u16_le(data, eocd + 20).ok()? as usize;
+        Some(eocd + 22 + comment)
+    }))
+}
+
+fn png_len(data: &[u8]) -> Result<Option<usize>, ()> {
+    let chunks = match crate::tools::polyglot_png::png_chunks(data) {
+        Ok(chunks) => chunks,
+        // truncated or damaged after the header, still worth reporting
+        Err(_) => return Ok(None),
+    };
+    let ihdr = &chunks[0];
+    if &ihdr.chunk_type != b"IHDR" {
+        return Err(());
+    }
+    let mut crc = flate2::Crc::new();
+    crc.update(&data[ihdr.offset + 4..ihdr.end() - 4]);
+    if crc.sum() != ihdr.crc {
+        return Err(());
+    }
+    Ok(chunks.last().map(|c| c.end()))
+}
+
+fn jpeg_len(data: &[u8]) -> Result<Option<usize>, ()> {
+    // walk the marker segments up to the start of scan, then look for the end of image
+    let mut offset = 2;
+    loop {
+        if *data.get(offset).ok_or(())? != 0xff {
+            return Err(());
+        }
+        let marker = *data.get(offset + 1).ok_or(())?;
+        let length = u16::from_be_bytes([
+            *data.get(offset + 2).ok_or(())?,
+            *data.get(offset + 3).ok_or(())?,
+        ]) as usize;
+        offset += 2 + length;
+        if marker == 0xda {
+            break;
+        }
+    }
+    Ok(find_index_from(data, b"\xff\xd9", offset).map(|end| end + 2))
+}
+
+fn gif_len(data: &[u8]) -> Result<Option<usize>, ()> {
+    // the trailer is a single 0x3B, too common to search for reliably
+    u16_le(data, 6)?;
+    Ok(None)
+}
+
+fn bmp_len(data: &[u8]) -> Result<Option<usize>, ()> {
+    let size = u32_le(data, 2)? as usize;
+    let reserved = u32_le(data, 6)?;
+    let pixel_offset = u32_le(data, 10)? as usize;
+    let header_size = u32_le(data, 14)?;
+    if reserved != 0
+        || size < 26
+        || pixel_offset >= size
+        || ![12, 40, 52, 56, 64, 108, 124].contains(&header_size)
+    {
+        return Err(());
+    }
+    Ok(Some(size))
+}
+
+fn riff_len(data: &[u8]) -> Result<Option<usize>, ()> {
+    let form = data.get(8..12).ok_or(())?;
+    if !form.iter().all(|b| b.is_ascii_alphanumeric() || *b == b' ') {
+        return Err(());
+    }
+    Ok(Some(u32_le(data, 4)? as usize + 8))
+}
+
+fn pdf_len(data: &[u8]) -> Result<Option<usize>, ()> {
+    // incremental updates append more %%EOF markers, so take the last one
+    Ok(rfind_index(data, b"%%EOF").map(|end| {
+        let mut end = end + 5;
+        while end < data.len() && (data[end] == b'\r' || data[end] == b'\n') {
+            end += 1;
+        }
+        end
+    }))
+}
+
+fn gzip_len(data: &[u8]) -> Result<Option<usize>, ()> {
+    // decompress to find out where the stream really ends
+    let mut decoder = flate2::bufread::GzDecoder::new(data);
+    std::io::copy(&mut decoder, &mut std::io::sink()).map_err(|_| ())?;
+    Ok(Some(data.len() - decoder.into_inner().len()))
+}
+
+fn bzip2_len(data: &[u8]) -> Result<Option<usize>, ()> {
+    let level = *data.get(3).ok_or(())?;
+    if !(b'1'..=b'9').contains(&level) || data.get(4..10) != Some(b"1AY&SY") {
+        return Err(());
+    }
+    Ok(None)
+}
+
+fn xz_len(data: &[u8]) -> Result<Option<usize>, ()> {
+    // the stream footer ends with "YZ", and streams are padded to 4 bytes
+    Ok(find_index_from(data, b"YZ", 12)
+        .map(|end| end + 2)
+        .filter(|end| end % 4 == 0))
+}
+
+fn lzma_len(data: &[u8]) -> Result<Option<usize>, ()> {
+    let dict_size = u32_le(data, 1)?;
+    let unpacked = u64_le(data, 5)?;
+    if !dict_size.is_power_of_two() || dict_size < 4096 {
+        return Err(());
+    }
+    if unpacked != u64::MAX && unpacked > 1 << 40 {
+        return Err(());
+    }
+    Ok(None)
+}
+
+fn sevenzip_len(data: &[u8]) -> Result<Option<usize>, ()> {
+    let next_offset = u64_le(data, 12)? as usize;
+    let next_size = u64_le(data, 20)? as usize;
+    Ok(next_offset
+        .checked_add(next_size)
+        .and_then(|l| l.checked_add(32)))
+}
+
+fn squashfs_le_len(data: &[u8]) -> Result<Option<usize>, ()> {
+    if u16_le(data, 28)? != 4 {
+        return Err(());
+    }
+    Ok(Some(u64_le(data, 40)? as usize))
+}
+
+fn squashfs_be_len(data: &[u8]) -> Result<Option<usize>, ()> {
+    if data.get(28..30) != Some(&[0, 4]) {
+        return Err(());
+    }
+    Ok(Some(u64_be(data, 40)? as usize))
+}
+
+fn cramfs_len(data: &[u8]) -> Result<Option<usize>, ()> {
+    if data.get(16..32) != Some(b"Compressed ROMFS") {
+        return Err(());
+    }
+    Ok(Some(u32_le(data, 4)? as usize))
+}
+
+fn uimage_len(data: &[u8]) -> Result<Option<usize>, ()> {
+    Ok(Some(64 + u32_be(data, 12)? as usize))
+}
+
+fn elf_len(data: &[u8]) -> Result<Option<usize>, ()> {
+    let class = *data.get(4).ok_or(())?;
+    let endian = *data.get(5).ok_or(())?;
+    if !(1..=2).contains(&class) || !(1..=2).contains(&endian) {
+        return Err(());
+    }
+    let big = endian == 2;
+    let read = |offset: usize, size: usize| -> Result<usize, ()> {
+        let b = data
+            .get(offset..offset.checked_add(size).ok_or(())?)
+            .ok_or(())?;
+        let mut value = 0usize;
+        for i in 0..size {
+            let byte = if big { b[i] } else { b[size - 1 - i] };
+            value = (value << 8) | byte as usize;
+        }
+        Ok(value)
+    };
+    // the section header table is normally the last thing in the file
+    let (shoff, shentsize, shnum, phoff, phentsize, phnum) = if class == 1 {
+        (
+            read(0x20, 4)?,
+            read(0x2e, 2)?,
+            read(0x30, 2)?,
+            read(0x1c, 4)?,
+            read(0x2a, 2)?,
+            read(0x2c, 2)?,
+        )
+    } else {
+        (
+            read(0x28, 8)?,
+            read(0x3a, 2)?,
+            read(0x3c, 2)?,
+            read(0x20, 8)?,
+            read(0x36, 2)?,
+            read(0x38, 2)?,
+        )
+    };
+    let mut end = shoff.saturating_add(shentsize.saturating_mul(shnum));
+    for i in 0..phnum {
+        // offsets past the address space can't come from a real ELF
+        let entry = i
+            .checked_mul(phentsize)
+            .and_then(|o| o.checked_add(phoff))
+            .filter(|e| e.checked_add(40).is_some())
+            .ok_or(())?;
+        let (file_offset, file_size) = if class == 1 {
+            (read(entry + 4, 4), read(entry + 16, 4))
+        } else {
+            (read(entry + 8, 8), read(entry + 32, 8))
+        };
+        if let (Ok(o), Ok(s)) = (file_offset, file_size) {
+            end = end.max(o.saturating_add(s));
+        }
+    }
+    Ok(Some(end))
+}
+
+fn pe_len(data: &[u8]) -> Result<Option<usize>, ()> {
+    let pe = u32_le(data, 0x3c)? as usize;
+    if data.get(pe..pe + 4) != Some(b"PE\x00\x00") {
+        return Err(());
+    }
+    let sections = u16_le(data, pe + 6)? as usize;
+    let optional_size = u16_le(data, pe + 20)? as usize;
+    let table = pe + 24 + optional_size;
+    let mut end = table + sections * 40;
+    for i in 0..sections {
+        let section = table + i * 40;
+        let raw_size = u32_le(data, section + 16)? as usize;
+        let raw_offset = u32_le(data, section + 20)? as usize;
+        end = end.max(raw_offset + raw_size);
+    }
+    Ok(Some(end))
+}
+
+fn dex_len(data: &[u8]) -> Result<Option<usize>, ()> {
+    Ok(Some(u32_le(data, 32)? as usize))
+}
+
+fn sqlite_len(data: &[u8]) -> Result<Option<usize>, ()> {
+    let page_size = match u16::from_be_bytes([*data.get(16).ok_or(())?, *data.get(17).ok_or(())?]) {
+        1 => 65536,
+        n if n.is_power_of_two() && n >= 512 => n as usize,
+        _ => return Err(()),
+    };
+    let pages = u32_be(data, 28)? as usize;
+    Ok(Some(page_size * pages).filter(|&l| l > 0))
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::{Read, Write};
+
+    fn gunzip(data: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        flate2::read::GzDecoder::new(data)
+            .read_to_end(&mut out)
+            .unwrap();
+        out
+    }
+
+    #[test]
+    fn test_scan_gzip() {
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::best());
+        encoder.write_all(b"hello hello hello hello").unwrap();
+        let gz = encoder.finish().unwrap();
+        let mut data = vec![0u8; 100];
+        data.extend_from_slice(&gz);
+        data.extend_from_slice(&[0u8; 50]);
+
+        let hits = super::scan(&data);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].offset, 100);
+        assert_eq!(hits[0].length, Some(gz.len()));
+        let carved = super::carve(&data, &hits, 0);
+        assert_eq!(gunzip(carved), b"hello hello hello hello");
+    }
+    #[test]
+    fn test_scan_png() {
+        let png = include_bytes!("../../../assets/shitools.png");
+        let mut data = b"junk".to_vec();
+        data.extend_from_slice(png);
+        data.extend_from_slice(b"trailing");
+        let hits = super::scan(&data);
+        let hit = hits.iter().find(|h| h.ext == "png").unwrap();
+        assert_eq!(hit.offset, 4);
+        assert_eq!(hit.length, Some(png.len()));
+    }
+    #[test]
+    fn test_scan_zip() {
+        // three stored members, then the end of central directory record
+        let mut zip = Vec::new();
+        for _ in 0..3 {
+            let mut header = b"PK\x03\x04\x14\x00".to_vec();
+            header.resize(30, 0);
+            zip.extend_from_slice(&header);
+        }
+        let mut eocd = b"PK\x05\x06".to_vec();
+        eocd.resize(22, 0);
+        zip.extend_from_slice(&eocd);
+        let mut data = vec![0u8; 10];
+        data.extend_from_slice(&zip);
+        data.extend_from_slice(&zip[..30]);
+
+        let hits = super::scan(&data);
+        assert_eq!(hits.len(), 2);
+        assert_eq!((hits[0].offset, hits[0].length), (10, Some(zip.len())));
+        // a trailing member without an end record is one archive up to the end
+        assert_eq!((hits[1].offset, hits[1].length), (10 + zip.len(), None));
+    }
+    #[test]
+    fn test_scan_rejects_noise() {
+        // "MZ" and "BM" without valid headers must not be reported
+        let data = b"xxMZxxxxBMxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxx";
+        assert!(super::scan(data).is_empty());
+        // an ELF64 header whose program headers would start past the address space
+        let mut elf = b"\x7fELF\x02\x01\x01".to_vec();
+        elf.resize(64, 0);
+        elf[0x20..0x28].copy_from_slice(&u64::MAX.to_le_bytes());
+        elf[0x36..0x38].copy_from_slice(&0x38u16.to_le_bytes());
+        elf[0x38..0x3a].copy_from_slice(&2u16.to_le_bytes());
+        assert!(super::elf_len(&elf).is_err());
+        // a squashfs size that would run past the end of the address space
+        let mut data = vec![0u8; 8];
+        let mut squashfs = b"hsqs".to_vec();
+        squashfs.resize(64, 0);
+        squashfs[28..30].copy_from_slice(&4u16.to_le_bytes());
+        squashfs[40..48].copy_from_slice(&u64::MAX.to_le_bytes());
+        data.extend_from_slice(&squashfs);
+        let hits = super::scan(&data);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].length, None);
+        let hits = vec![super::Hit {
+            length: Some(usize::MAX),
+            ..hits[0].clone()
+        }];
+        assert_eq!(super::carve(&data, &hits, 0).len(), 64);
+    }
+}
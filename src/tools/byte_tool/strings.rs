@@ -0,0 +1,217 @@
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum StrEncoding {
+    Ascii,
+    Utf8,
+    Utf16LE,
+    Utf16BE,
+}
+
+impl StrEncoding {
+    pub fn name(&self) -> &'static str {
+        match self {
+            StrEncoding::Ascii => "ASCII",
+            StrEncoding::Utf8 => "UTF-8",
+            StrEncoding::Utf16LE => "UTF-16LE",
+            StrEncoding::Utf16BE => "UTF-16BE",
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Hash, Clone, Debug)]
+pub struct FoundString {
+    pub offset: usize,
+    pub encoding: StrEncoding,
+    pub text: String,
+}
+
+fn printable(c: char) -> bool {
+    c == '\t' || !c.is_control()
+}
+
+/// Finds printable runs of at least `min_len` characters, like the `strings` utility.
+///
+/// When both ASCII and UTF-8 are requested, UTF-8 only reports runs that contain
+/// non-ASCII characters so the same string is not listed twice. UTF-16 runs are
+/// Latin-1 unless `any_bmp` also allows the rest of the BMP, such as CJK, at the
+/// cost of reading misaligned ASCII as noise.
+pub fn extract(
+    input: &[u8],
+    min_len: usize,
+    encodings: &[StrEncoding],
+    any_bmp: bool,
+) -> Vec<FoundString> {
+    let min_len = min_len.max(1);
+    let mut result = Vec::new();
+    for &encoding in encodings {
+        match encoding {
+            StrEncoding::Ascii => extract_ascii(input, min_len, &mut result),
+            StrEncoding::Utf8 => {
+                let skip_ascii = encodings.contains(&StrEncoding::Ascii);
+                extract_utf8(input, min_len, skip_ascii, &mut result)
+            }
+            StrEncoding::Utf16LE | StrEncoding::Utf16BE => {
+                extract_utf16(input, min_len, encoding, any_bmp, &mut result)
+            }
+        }
+    }
+    result.sort_by_key(|s| s.offset);
+    result
+}
+
+fn extract_ascii(input: &[u8], min_len: usize, result: &mut Vec<FoundString>) {
+    let mut start = 0;
+    for i in 0..=input.len() {
+        let is_text = i < input.len() && (input[i] == b'\t' || (0x20..=0x7e).contains(&input[i]));
+        if is_text {
+            continue;
+        }
+        if i - start >= min_len {
+            result.push(FoundString {
+                offset: start,
+                encoding: StrEncoding::Ascii,
+                text: String::from_utf8_lossy(&input[start..i]).into_owned(),
+            });
+        }
+        start = i + 1;
+    }
+}
+
+/// Decodes one UTF-8 character at the start of `input`, returning it with its length
+fn decode_utf8_char(input: &[u8]) -> Option<(char, usize)> {
+    let len = match input.first()? {
+        0x00..=0x7f => 1,
+        0xc2..=0xdf => 2,
+        0xe0..=0xef => 3,
+        0xf0..=0xf4 => 4,
+        _ => return None,
+    };
+    let s = std::str::from_utf8(input.get(..len)?).ok()?;
+    s.chars().next().map(|c| (c, len))
+}
+
+fn extract_utf8(input: &[u8], min_len: usize, skip_ascii: bool, result: &mut Vec<FoundString>) {
+    let mut i = 0;
+    while i < input.len() {
+        let start = i;
+        let mut text = String::new();
+        let mut chars = 0;
+        while let Some((c, len)) = decode_utf8_char(&input[i..]) {
+            if !printable(c) {
+                break;
+            }
+            text.push(c);
+            chars += 1;
+            i += len;
+        }
+        if chars >= min_len && !(skip_ascii && text.is_ascii()) {
+            result.push(FoundString {
+                offset: start,
+                encoding: StrEncoding::Utf8,
+                text,
+            });
+        }
+        if i == start {
+            i += 1;
+        }
+    }
+}
+
+fn extract_utf16(
+    input: &[u8],
+    min_len: usize,
+    encoding: StrEncoding,
+    any_bmp: bool,
+    result: &mut Vec<FoundString>,
+) {
+    let unit = |pair: &[u8]| match encoding {
+        StrEncoding::Utf16BE => u16::from_be_bytes([pair[0], pair[1]]),
+        _ => u16::from_le_bytes([pair[0], pair[1]]),
+    };
+    // strings may start on either byte alignment
+    for align in 0..2 {
+        if input.len() < align + 2 {
+            break;
+        }
+        let units: Vec<u16> = input[align..].chunks_exact(2).map(unit).collect();
+        let mut i = 0;
+        while i < units.len() {
+            let start = i;
+            let mut text = String::new();
+            let mut chars = 0;
+            // like `strings -el`, only Latin-1 units count by default; any printable unit
+            // would turn plain ASCII read off by one byte into runs of CJK
+            let unit_char = |u: u16| match any_bmp {
+                true => char::from_u32(u as u32),
+                false => u8::try_from(u).ok().map(char::from),
+            };
+            while let Some(c) = units.get(i).copied().and_then(unit_char) {
+                if !printable(c) {
+                    break;
+                }
+                text.push(c);
+                chars += 1;
+                i += 1;
+            }
+            if chars >= min_len {
+                result.push(FoundString {
+                    offset: align + start * 2,
+                    encoding,
+                    text,
+                });
+            }
+            if i == start {
+                i += 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::StrEncoding::*;
+
+    #[test]
+    fn test_extract() {
+        let mut data = b"\x00\x01hello\x00ab\xffworld!\x00".to_vec();
+        data.extend_from_slice("日本語".as_bytes());
+        data.push(0);
+        data.extend("wide".encode_utf16().flat_map(|u| u.to_le_bytes()));
+        data.extend_from_slice(&[0, 0]);
+
+        let ascii = super::extract(&data, 4, &[Ascii], false);
+        let texts: Vec<&str> = ascii.iter().map(|s| s.text.as_str()).collect();
+        assert_eq!(texts, vec!["hello", "world!"]);
+        assert_eq!(ascii[0].offset, 2);
+
+        let utf8 = super::extract(&data, 3, &[Ascii, Utf8], false);
+        assert!(utf8
+            .iter()
+            .any(|s| s.encoding == Utf8 && s.text == "日本語"));
+        assert!(!utf8.iter().any(|s| s.encoding == Utf8 && s.text == "hello"));
+
+        let wide = super::extract(&data, 4, &[Utf16LE], false);
+        assert!(wide.iter().any(|s| s.text == "wide" && s.offset % 2 == 0));
+    }
+
+    #[test]
+    fn test_utf16_ascii() {
+        let data = b"plain ascii text, nothing wide in here".to_vec();
+        assert!(super::extract(&data, 4, &[Utf16LE, Utf16BE], false).is_empty());
+        let wide: Vec<u8> = "caf\u{e9} au lait"
+            .encode_utf16()
+            .flat_map(|u| u.to_be_bytes())
+            .collect();
+        let found = super::extract(&wide, 4, &[Utf16BE], false);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].text, "caf\u{e9} au lait");
+        let wide: Vec<u8> = "日本語のテキスト"
+            .encode_utf16()
+            .flat_map(|u| u.to_le_bytes())
+            .collect();
+        assert!(super::extract(&wide, 4, &[Utf16LE], false).is_empty());
+        let found = super::extract(&wide, 4, &[Utf16LE], true);
+        assert!(found
+            .iter()
+            .any(|s| s.text == "日本語のテキスト" && s.offset == 0));
+    }
+}
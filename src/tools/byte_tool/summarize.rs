@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 use std::io::Write;
+#[cfg(windows)]
 use std::os::windows::process::CommandExt;
 
 const U8ALL: usize = u8::MAX as usize + 1;
@@ -62,6 +63,171 @@ pub fn xor(input: &[u8], key: &[u8]) -> String {
     hex::encode(buff)
 }
 
+pub fn histogram(input: &[u8]) -> Vec<usize> {
+    count(input).to_vec()
+}
+
+/// Shannon entropy in bits per byte, from 0.0 (constant) to 8.0 (uniformly random)
+pub fn shannon_entropy(input: &[u8]) -> f64 {
+    if input.is_empty() {
+        return 0.0;
+    }
+    let len = input.len() as f64;
+    count(input)
+        .iter()
+        .filter(|&&c| c > 0)
+        .map(|&c| {
+            let p = c as f64 / len;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+/// Entropy of each window of `window` bytes, moving `step` bytes at a time.
+/// Returns (offset, entropy) pairs.
+pub fn sliding_entropy(input: &[u8], window: usize, step: usize) -> Vec<(usize, f64)> {
+    if input.is_empty() || window == 0 || step == 0 {
+        return Vec::new();
+    }
+    if input.len() <= window {
+        return vec![(0, shannon_entropy(input))];
+    }
+    let mut result = Vec::with_capacity((input.len() - window) / step + 1);
+    let mut offset = 0;
+    while offset + window <= input.len() {
+        result.push((offset, shannon_entropy(&input[offset..offset + window])));
+        offset += step;
+    }
+    result
+}
+
+pub struct RandomnessStats {
+    pub entropy: f64,
+    pub chi_square: f64,
+    pub chi_square_p: f64,
+    pub mean: f64,
+    pub monte_carlo_pi: f64,
+    pub serial_correlation: f64,
+}
+
+/// Randomness statistics in the manner of the `ent` utility
+pub fn randomness_stats(input: &[u8]) -> RandomnessStats {
+    let n = input.len() as f64;
+    let buff = count(input);
+    let expected = n / U8ALL as f64;
+    let chi_square = if input.is_empty() {
+        0.0
+    } else {
+        buff.iter()
+            .map(|&c| {
+                let d = c as f64 - expected;
+                d * d / expected
+            })
+            .sum()
+    };
+    let mean = if input.is_empty() {
+        0.0
+    } else {
+        input.iter().map(|&b| b as f64).sum::<f64>() / n
+    };
+
+    // Monte Carlo: each 6 bytes form a point (24-bit x, 24-bit y) in a square,
+    // the ratio falling inside the inscribed quarter circle approximates pi/4.
+    const RADIUS: f64 = 16777215.0; // 256^3 - 1
+    let mut inside = 0usize;
+    let mut total = 0usize;
+    for point in input.chunks_exact(6) {
+        let x = u32::from_be_bytes([0, point[0], point[1], point[2]]) as f64;
+        let y = u32::from_be_bytes([0, point[3], point[4], point[5]]) as f64;
+        if x * x + y * y <= RADIUS * RADIUS {
+            inside += 1;
+        }
+        total += 1;
+    }
+    let monte_carlo_pi = if total == 0 {
+        0.0
+    } else {
+        4.0 * inside as f64 / total as f64
+    };
+
+    // Serial correlation between each byte and the next, wrapping around at the end
+    let mut t1 = 0.0;
+    let mut t2 = 0.0;
+    let mut t3 = 0.0;
+    for i in 0..input.len() {
+        let u = input[i] as f64;
+        let v = input[(i + 1) % input.len()] as f64;
+        t1 += u * v;
+        t2 += u * u;
+        t3 += u;
+    }
+    let denominator = n * t2 - t3 * t3;
+    let serial_correlation = if denominator == 0.0 {
+        // constant data, correlation is undefined
+        f64::NAN
+    } else {
+        (n * t1 - t3 * t3) / denominator
+    };
+
+    RandomnessStats {
+        entropy: shannon_entropy(input),
+        chi_square,
+        chi_square_p: chi_square_upper_p(chi_square, (U8ALL - 1) as f64),
+        mean,
+        monte_carlo_pi,
+        serial_correlation,
+    }
+}
+
+/// Upper tail probability of the chi-square distribution, using the
+/// Wilson-Hilferty normal approximation (accurate enough for 255 degrees of freedom)
+fn chi_square_upper_p(x: f64, k: f64) -> f64 {
+    if x <= 0.0 {
+        return 1.0;
+    }
+    let v = 2.0 / (9.0 * k);
+    let z = ((x / k).powf(1.0 / 3.0) - (1.0 - v)) / v.sqrt();
+    0.5 * erfc(z / std::f64::consts::SQRT_2)
+}
+
+/// Complementary error function (Numerical Recipes erfcc, relative error < 1.2e-7)
+fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let r = t
+        * (-z * z - 1.26551223
+            + t * (1.00002368
+                + t * (0.37409196
+                    + t * (0.09678418
+                        + t * (-0.18628806
+                            + t * (0.27886807
+                                + t * (-1.13520398
+                                    + t * (1.48851587 + t * (-0.82215223 + t * 0.17087277)))))))))
+            .exp();
+    if x >= 0.0 {
+        r
+    } else {
+        2.0 - r
+    }
+}
+
+pub fn format_stats(stats: &RandomnessStats) -> String {
+    format!(
+        "Entropy: {:.6} bits per byte\n\
+         Chi-square: {:.2} (p = {:.4})\n\
+         Arithmetic mean: {:.4} (127.5 = random)\n\
+         Monte Carlo Pi: {:.6} (error {:.2}%)\n\
+         Serial correlation: {:.6} (0.0 = uncorrelated)",
+        stats.entropy,
+        stats.chi_square,
+        stats.chi_square_p,
+        stats.mean,
+        stats.monte_carlo_pi,
+        (stats.monte_carlo_pi - std::f64::consts::PI).abs() / std::f64::consts::PI * 100.0,
+        stats.serial_correlation,
+    )
+}
+
 pub fn format_guess(input: &[u8]) -> String {
     let return_val = match magika_wrapper(input) {
         Ok(output) => output,
@@ -88,11 +254,13 @@ fn magika_wrapper(input: &[u8]) -> Result<String, String> {
 
     // Run magika.exe with temporary file
 
-    let output = std::process::Command::new(magika_path)
-        .arg("--jsonl")
+    let mut cmd = std::process::Command::new(magika_path);
+    cmd.arg("--jsonl")
         .arg(&temp_path)
-        .stdout(std::process::Stdio::piped())
-        .creation_flags(0x08000000) // Set creation flags to hide console window
+        .stdout(std::process::Stdio::piped());
+    #[cfg(windows)]
+    cmd.creation_flags(0x08000000);
+    let output = cmd
         .output()
         .map_err(|err| format!("Failed to run magika.exe: {}", err))?;
 
@@ -174,3 +342,36 @@ fn magika_json_resolve(magika_json: String) -> Result<String, String> {
     output_buffer.shrink_to_fit();
     Ok(output_buffer)
 }
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn test_entropy() {
+        assert_eq!(super::shannon_entropy(&[]), 0.0);
+        assert_eq!(super::shannon_entropy(&[7; 100]), 0.0);
+        let all: Vec<u8> = (0..=255).collect();
+        assert!((super::shannon_entropy(&all) - 8.0).abs() < 1e-9);
+        assert!((super::shannon_entropy(&[0, 1, 0, 1]) - 1.0).abs() < 1e-9);
+    }
+    #[test]
+    fn test_sliding_entropy() {
+        let mut data = vec![0u8; 512];
+        data.extend((0..=255).cycle().take(512).map(|b| b as u8));
+        let windows = super::sliding_entropy(&data, 256, 256);
+        assert_eq!(windows.len(), 4);
+        assert_eq!(windows[0], (0, 0.0));
+        assert_eq!(windows[3].0, 768);
+        assert!((windows[3].1 - 8.0).abs() < 1e-9);
+    }
+    #[test]
+    fn test_randomness_stats() {
+        let all: Vec<u8> = (0..=255).cycle().take(256 * 16).map(|b| b as u8).collect();
+        let stats = super::randomness_stats(&all);
+        assert_eq!(stats.chi_square, 0.0);
+        assert!((stats.mean - 127.5).abs() < 1e-9);
+        assert!(stats.chi_square_p > 0.99);
+        assert!(super::randomness_stats(&[5; 10])
+            .serial_correlation
+            .is_nan());
+    }
+}
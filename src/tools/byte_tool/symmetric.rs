@@ -0,0 +1,482 @@
+use aes_gcm::aead::Aead;
+use cipher::block_padding::{NoPadding, Padding as BlockPadding, Pkcs7, ZeroPadding};
+use cipher::consts::{U12, U16};
+use cipher::{
+    BlockCipherDecrypt, BlockCipherEncrypt, BlockModeDecrypt, BlockModeEncrypt, BlockSizeUser,
+    KeyInit, KeyIvInit, StreamCipher,
+};
+
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum Algorithm {
+    Aes,
+    Sm4,
+    Des,
+    TripleDes,
+    ChaCha20,
+    ChaCha20Poly1305,
+    Rc4,
+}
+
+pub const ALGORITHMS: [Algorithm; 7] = [
+    Algorithm::Aes,
+    Algorithm::Sm4,
+    Algorithm::Des,
+    Algorithm::TripleDes,
+    Algorithm::ChaCha20,
+    Algorithm::ChaCha20Poly1305,
+    Algorithm::Rc4,
+];
+
+impl Algorithm {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Algorithm::Aes => "AES",
+            Algorithm::Sm4 => "SM4",
+            Algorithm::Des => "DES",
+            Algorithm::TripleDes => "3DES",
+            Algorithm::ChaCha20 => "ChaCha20",
+            Algorithm::ChaCha20Poly1305 => "ChaCha20-Poly1305",
+            Algorithm::Rc4 => "RC4",
+        }
+    }
+    /// Block modes of the algorithm, empty for stream ciphers and AEADs
+    pub fn modes(&self) -> &'static [Mode] {
+        match self {
+            Algorithm::Aes | Algorithm::Sm4 => &[Mode::Ecb, Mode::Cbc, Mode::Ctr, Mode::Gcm],
+            Algorithm::Des | Algorithm::TripleDes => &[Mode::Ecb, Mode::Cbc, Mode::Ctr],
+            _ => &[],
+        }
+    }
+    pub fn key_sizes(&self) -> &'static str {
+        match self {
+            Algorithm::Aes => "16, 24 or 32 bytes",
+            Algorithm::Sm4 => "16 bytes",
+            Algorithm::Des => "8 bytes",
+            Algorithm::TripleDes => "16 or 24 bytes",
+            Algorithm::ChaCha20 | Algorithm::ChaCha20Poly1305 => "32 bytes",
+            Algorithm::Rc4 => "1 to 256 bytes",
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum Mode {
+    Ecb,
+    Cbc,
+    Ctr,
+    Gcm,
+}
+
+impl Mode {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Mode::Ecb => "ECB",
+            Mode::Cbc => "CBC",
+            Mode::Ctr => "CTR",
+            Mode::Gcm => "GCM",
+        }
+    }
+    pub fn padded(&self) -> bool {
+        matches!(self, Mode::Ecb | Mode::Cbc)
+    }
+}
+
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum Padding {
+    Pkcs7,
+    Zero,
+    None,
+}
+
+pub const PADDINGS: [Padding; 3] = [Padding::Pkcs7, Padding::Zero, Padding::None];
+
+impl Padding {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Padding::Pkcs7 => "PKCS#7",
+            Padding::Zero => "Zero",
+            Padding::None => "None",
+        }
+    }
+}
+
+/// How a key, IV or nonce is typed in
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum KeyEncoding {
+    Hex,
+    Text,
+    Base64,
+}
+
+pub const KEY_ENCODINGS: [KeyEncoding; 3] =
+    [KeyEncoding::Hex, KeyEncoding::Text, KeyEncoding::Base64];
+
+impl KeyEncoding {
+    pub fn name(&self) -> &'static str {
+        match self {
+            KeyEncoding::Hex => "HEX",
+            KeyEncoding::Text => "Text",
+            KeyEncoding::Base64 => "Base64",
+        }
+    }
+    pub fn parse(&self, input: &str) -> Result<Vec<u8>, String> {
+        match self {
+            KeyEncoding::Hex => {
+                let digits: String = input.split_whitespace().collect();
+                hex::decode(digits).map_err(|e| e.to_string())
+            }
+            KeyEncoding::Text => Ok(input.as_bytes().to_vec()),
+            KeyEncoding::Base64 => super::basic::base64_decode(input.trim()),
+        }
+    }
+}
+
+pub struct Params<'a> {
+    pub algorithm: Algorithm,
+    pub mode: Mode,
+    pub padding: Padding,
+    pub key: &'a [u8],
+    /// IV for CBC and CTR, nonce for GCM and ChaCha20
+    pub iv: &'a [u8],
+    /// Additional authenticated data for GCM and ChaCha20-Poly1305
+    pub aad: &'a [u8],
+}
+
+impl Params<'_> {
+    /// Whether the selected cipher takes an IV or nonce
+    pub fn uses_iv(&self) -> bool {
+        match self.algorithm {
+            Algorithm::Rc4 => false,
+            Algorithm::ChaCha20 | Algorithm::ChaCha20Poly1305 => true,
+            _ => self.mode != Mode::Ecb,
+        }
+    }
+    /// Whether the padding setting applies
+    pub fn padded(&self) -> bool {
+        !self.algorithm.modes().is_empty() && self.mode.padded()
+    }
+    pub fn uses_aad(&self) -> bool {
+        self.algorithm == Algorithm::ChaCha20Poly1305
+            || (self.mode == Mode::Gcm && !self.algorithm.modes().is_empty())
+    }
+}
+
+const AUTH_FAILED: &str =
+    "Authentication failed: the tag does not match, so the key, nonce, AAD or data is wrong";
+
+fn key_err(algorithm: Algorithm, len: usize) -> String {
+    format!(
+        "{} key must be {}, got {} bytes",
+        algorithm.name(),
+        algorithm.key_sizes(),
+        len
+    )
+}
+
+fn iv_err(what: &str, expected: &str, len: usize) -> String {
+    format!("{} must be {}, got {} bytes", what, expected, len)
+}
+
+fn pad<P: BlockPadding, C>(
+    input: &[u8],
+    encrypt: bool,
+    mode: Mode,
+    key: &[u8],
+    iv: &[u8],
+) -> Result<Vec<u8>, String>
+where
+    C: BlockCipherEncrypt + BlockCipherDecrypt + KeyInit,
+{
+    let block = C::block_size();
+    if !encrypt && !input.len().is_multiple_of(block) {
+        return Err(format!(
+            "Ciphertext length {} is not a multiple of the {}-byte block",
+            input.len(),
+            block
+        ));
+    }
+    let unpad_err = |_| "Invalid padding, the key or IV may be wrong".to_string();
+    match (mode, encrypt) {
+        (Mode::Ecb, true) => Ok(ecb::Encryptor::<C>::new_from_slice(key)
+            .map_err(|e| e.to_string())?
+            .encrypt_padded_vec::<P>(input)),
+        (Mode::Ecb, false) => ecb::Decryptor::<C>::new_from_slice(key)
+            .map_err(|e| e.to_string())?
+            .decrypt_padded_vec::<P>(input)
+            .map_err(unpad_err),
+        (Mode::Cbc, true) => Ok(cbc::Encryptor::<C>::new_from_slices(key, iv)
+            .map_err(|_| iv_err("IV", &format!("{} bytes", block), iv.len()))?
+            .encrypt_padded_vec::<P>(input)),
+        (Mode::Cbc, false) => cbc::Decryptor::<C>::new_from_slices(key, iv)
+            .map_err(|_| iv_err("IV", &format!("{} bytes", block), iv.len()))?
+            .decrypt_padded_vec::<P>(input)
+            .map_err(unpad_err),
+        _ => unreachable!("only ECB and CBC are padded"),
+    }
+}
+
+/// ECB, CBC, CTR and GCM over one block cipher. CTR uses a big-endian counter
+/// over the whole block, as in NIST SP 800-38A.
+fn block_mode<C>(input: &[u8], encrypt: bool, params: &Params) -> Result<Vec<u8>, String>
+where
+    C: BlockCipherEncrypt + BlockCipherDecrypt + KeyInit + BlockSizeUser<BlockSize = U16>,
+{
+    let (key, iv) = (params.key, params.iv);
+    match params.mode {
+        Mode::Ecb | Mode::Cbc => padded::<C>(input, encrypt, params),
+        Mode::Ctr => {
+            let mut cipher = ctr::Ctr128BE::<C>::new_from_slices(key, iv)
+                .map_err(|_| iv_err("IV", "16 bytes", iv.len()))?;
+            let mut out = input.to_vec();
+            cipher.apply_keystream(&mut out);
+            Ok(out)
+        }
+        Mode::Gcm => {
+            let gcm = aes_gcm::AesGcm::<C, U12>::new_from_slice(key).map_err(|e| e.to_string())?;
+            let nonce = iv
+                .try_into()
+                .map_err(|_| iv_err("GCM nonce", "12 bytes", iv.len()))?;
+            let payload = aes_gcm::aead::Payload {
+                msg: input,
+                aad: params.aad,
+            };
+            if encrypt {
+                gcm.encrypt(nonce, payload).map_err(|e| e.to_string())
+            } else {
+                gcm.decrypt(nonce, payload)
+                    .map_err(|_| AUTH_FAILED.to_string())
+            }
+        }
+    }
+}
+
+/// DES has 8-byte blocks, so CTR uses a 64-bit counter and there is no GCM
+fn block_mode_64<C>(input: &[u8], encrypt: bool, params: &Params) -> Result<Vec<u8>, String>
+where
+    C: BlockCipherEncrypt
+        + BlockCipherDecrypt
+        + KeyInit
+        + BlockSizeUser<BlockSize = cipher::consts::U8>,
+{
+    match params.mode {
+        Mode::Ecb | Mode::Cbc => padded::<C>(input, encrypt, params),
+        Mode::Ctr => {
+            let mut cipher = ctr::Ctr64BE::<C>::new_from_slices(params.key, params.iv)
+                .map_err(|_| iv_err("IV", "8 bytes", params.iv.len()))?;
+            let mut out = input.to_vec();
+            cipher.apply_keystream(&mut out);
+            Ok(out)
+        }
+        Mode::Gcm => Err("GCM needs a 128-bit block cipher".to_string()),
+    }
+}
+
+fn padded<C>(input: &[u8], encrypt: bool, params: &Params) -> Result<Vec<u8>, String>
+where
+    C: BlockCipherEncrypt + BlockCipherDecrypt + KeyInit,
+{
+    let (mode, key, iv) = (params.mode, params.key, params.iv);
+    match params.padding {
+        Padding::Pkcs7 => pad::<Pkcs7, C>(input, encrypt, mode, key, iv),
+        Padding::Zero => pad::<ZeroPadding, C>(input, encrypt, mode, key, iv),
+        Padding::None => {
+            if !input.len().is_multiple_of(C::block_size()) {
+                return Err(format!(
+                    "Without padding the input must be a multiple of {} bytes, got {}",
+                    C::block_size(),
+                    input.len()
+                ));
+            }
+            pad::<NoPadding, C>(input, encrypt, mode, key, iv)
+        }
+    }
+}
+
+fn chacha20(input: &[u8], key: &[u8], nonce: &[u8]) -> Result<Vec<u8>, String> {
+    let mut out = input.to_vec();
+    let err = |_| iv_err("ChaCha20 nonce", "8, 12 or 24 bytes", nonce.len());
+    // the nonce length picks the original, IETF or extended variant
+    match nonce.len() {
+        8 => chacha20::ChaCha20Legacy::new_from_slices(key, nonce)
+            .map_err(err)?
+            .apply_keystream(&mut out),
+        24 => chacha20::XChaCha20::new_from_slices(key, nonce)
+            .map_err(err)?
+            .apply_keystream(&mut out),
+        _ => chacha20::ChaCha20::new_from_slices(key, nonce)
+            .map_err(err)?
+            .apply_keystream(&mut out),
+    }
+    Ok(out)
+}
+
+fn chacha20_poly1305(input: &[u8], encrypt: bool, params: &Params) -> Result<Vec<u8>, String> {
+    use chacha20poly1305::{ChaCha20Poly1305, XChaCha20Poly1305};
+    let payload = aes_gcm::aead::Payload {
+        msg: input,
+        aad: params.aad,
+    };
+    let nonce_err = || iv_err("ChaCha20-Poly1305 nonce", "12 or 24 bytes", params.iv.len());
+    let result = match params.iv.len() {
+        12 => {
+            let aead = ChaCha20Poly1305::new_from_slice(params.key).map_err(|e| e.to_string())?;
+            let nonce = params.iv.try_into().map_err(|_| nonce_err())?;
+            if encrypt {
+                aead.encrypt(nonce, payload)
+            } else {
+                aead.decrypt(nonce, payload)
+            }
+        }
+        24 => {
+            let aead = XChaCha20Poly1305::new_from_slice(params.key).map_err(|e| e.to_string())?;
+            let nonce = params.iv.try_into().map_err(|_| nonce_err())?;
+            if encrypt {
+                aead.encrypt(nonce, payload)
+            } else {
+                aead.decrypt(nonce, payload)
+            }
+        }
+        _ => return Err(nonce_err()),
+    };
+    result.map_err(|e| {
+        if encrypt {
+            e.to_string()
+        } else {
+            AUTH_FAILED.to_string()
+        }
+    })
+}
+
+fn run(input: &[u8], encrypt: bool, params: &Params) -> Result<Vec<u8>, String> {
+    let key = params.key;
+    let algorithm = params.algorithm;
+    match (algorithm, key.len()) {
+        (Algorithm::Aes, 16) => block_mode::<aes::Aes128>(input, encrypt, params),
+        (Algorithm::Aes, 24) => block_mode::<aes::Aes192>(input, encrypt, params),
+        (Algorithm::Aes, 32) => block_mode::<aes::Aes256>(input, encrypt, params),
+        (Algorithm::Sm4, 16) => block_mode::<sm4::Sm4>(input, encrypt, params),
+        (Algorithm::Des, 8) => block_mode_64::<des::Des>(input, encrypt, params),
+        (Algorithm::TripleDes, 16) => block_mode_64::<des::TdesEde2>(input, encrypt, params),
+        (Algorithm::TripleDes, 24) => block_mode_64::<des::TdesEde3>(input, encrypt, params),
+        (Algorithm::ChaCha20, 32) => chacha20(input, key, params.iv),
+        (Algorithm::ChaCha20Poly1305, 32) => chacha20_poly1305(input, encrypt, params),
+        (Algorithm::Rc4, 1..=256) => {
+            let mut out = input.to_vec();
+            rc4::Rc4::new_from_slice(key)
+                .map_err(|e| e.to_string())?
+                .apply_keystream(&mut out);
+            Ok(out)
+        }
+        _ => Err(key_err(algorithm, key.len())),
+    }
+}
+
+/// Encrypts the input. AEAD ciphers append their 16-byte tag to the ciphertext.
+pub fn encrypt(input: &[u8], params: &Params) -> Result<Vec<u8>, String> {
+    run(input, true, params)
+}
+
+/// Decrypts the input, expecting AEAD tags at the end of the ciphertext
+pub fn decrypt(input: &[u8], params: &Params) -> Result<Vec<u8>, String> {
+    run(input, false, params)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Algorithm, Mode, Padding, Params};
+
+    fn params<'a>(algorithm: Algorithm, mode: Mode, key: &'a [u8], iv: &'a [u8]) -> Params<'a> {
+        Params {
+            algorithm,
+            mode,
+            padding: Padding::None,
+            key,
+            iv,
+            aad: b"",
+        }
+    }
+
+    #[test]
+    fn test_vectors() {
+        // FIPS-197 appendix C.1
+        let key = hex::decode("000102030405060708090a0b0c0d0e0f").unwrap();
+        let plain = hex::decode("00112233445566778899aabbccddeeff").unwrap();
+        let p = params(Algorithm::Aes, Mode::Ecb, &key, b"");
+        assert_eq!(
+            hex::encode(super::encrypt(&plain, &p).unwrap()),
+            "69c4e0d86a7b0430d8cdb78070b4c55a"
+        );
+        // GB/T 32907 example 1
+        let key = hex::decode("0123456789abcdeffedcba9876543210").unwrap();
+        let p = params(Algorithm::Sm4, Mode::Ecb, &key, b"");
+        assert_eq!(
+            hex::encode(super::encrypt(&key, &p).unwrap()),
+            "681edf34d206965e86b3e94f536e4246"
+        );
+        // RFC 6229, 40-bit key
+        let p = params(Algorithm::Rc4, Mode::Ecb, b"\x01\x02\x03\x04\x05", b"");
+        assert_eq!(
+            hex::encode(super::encrypt(&[0; 8], &p).unwrap()),
+            "b2396305f03dc027"
+        );
+        // DES: the classic "Now is t" example
+        let key = hex::decode("0123456789abcdef").unwrap();
+        let p = params(Algorithm::Des, Mode::Ecb, &key, b"");
+        assert_eq!(
+            hex::encode(super::encrypt(b"Now is t", &p).unwrap()),
+            "3fa40e8a984d4815"
+        );
+    }
+    #[test]
+    fn test_roundtrip() {
+        let data = b"attack at dawn, attack at dusk!";
+        let key32 = [7u8; 32];
+        let cases = [
+            (Algorithm::Aes, Mode::Cbc, &key32[..16], &[1u8; 16][..]),
+            (Algorithm::Aes, Mode::Ctr, &key32[..24], &[1u8; 16][..]),
+            (Algorithm::Aes, Mode::Gcm, &key32[..], &[1u8; 12][..]),
+            (Algorithm::Sm4, Mode::Gcm, &key32[..16], &[1u8; 12][..]),
+            (Algorithm::TripleDes, Mode::Cbc, &key32[..24], &[1u8; 8][..]),
+            (Algorithm::Des, Mode::Ctr, &key32[..8], &[1u8; 8][..]),
+            (Algorithm::ChaCha20, Mode::Ecb, &key32[..], &[1u8; 24][..]),
+            (
+                Algorithm::ChaCha20Poly1305,
+                Mode::Ecb,
+                &key32[..],
+                &[1u8; 12][..],
+            ),
+        ];
+        for (algorithm, mode, key, iv) in cases {
+            for padding in super::PADDINGS {
+                let mut p = params(algorithm, mode, key, iv);
+                p.padding = padding;
+                p.aad = b"header";
+                let encrypted = super::encrypt(data, &p);
+                if padding == Padding::None && p.padded() {
+                    assert!(encrypted.is_err());
+                    continue;
+                }
+                let encrypted = encrypted.unwrap();
+                let name = format!("{} {}", algorithm.name(), mode.name());
+                assert_eq!(super::decrypt(&encrypted, &p).unwrap(), data, "{}", name);
+            }
+        }
+    }
+    #[test]
+    fn test_auth_failure() {
+        let key = [3u8; 32];
+        let mut p = params(Algorithm::Aes, Mode::Gcm, &key, &[0; 12]);
+        let mut sealed = super::encrypt(b"secret", &p).unwrap();
+        p.aad = b"other";
+        assert!(super::decrypt(&sealed, &p)
+            .unwrap_err()
+            .starts_with("Authentication failed"));
+        p.aad = b"";
+        sealed[0] ^= 1;
+        assert!(super::decrypt(&sealed, &p).is_err());
+        let p = params(Algorithm::Aes, Mode::Ecb, &key[..5], b"");
+        assert!(super::encrypt(b"x", &p)
+            .unwrap_err()
+            .contains("16, 24 or 32"));
+    }
+}
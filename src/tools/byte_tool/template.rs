@@ -0,0 +1,517 @@
+use mlua::prelude::*;
+use std::cell::RefCell;
+
+/// Directory users drop their own `.lua` templates into, next to the executable
+pub const TEMPLATE_DIR: &str = "templates";
+
+const BUILTIN: [(&str, &str); 6] = [
+    ("png.lua", include_str!("../../../assets/templates/png.lua")),
+    ("zip.lua", include_str!("../../../assets/templates/zip.lua")),
+    ("elf.lua", include_str!("../../../assets/templates/elf.lua")),
+    ("pe.lua", include_str!("../../../assets/templates/pe.lua")),
+    ("bmp.lua", include_str!("../../../assets/templates/bmp.lua")),
+    ("wav.lua", include_str!("../../../assets/templates/wav.lua")),
+];
+
+/// A named range of the buffer, with nested fields for groups
+#[derive(Clone, Debug)]
+pub struct Field {
+    pub name: String,
+    pub offset: usize,
+    pub length: usize,
+    pub value: String,
+    pub children: Vec<Field>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Template {
+    pub name: String,
+    /// "built-in" or the file name of a user template
+    pub origin: String,
+    pub source: String,
+    pub magic: Option<(usize, Vec<u8>)>,
+}
+
+enum Value {
+    Int(i64),
+    UInt(u64),
+    Bytes(Vec<u8>),
+    Str(Vec<u8>),
+}
+
+impl Value {
+    fn display(&self) -> String {
+        match self {
+            Value::Int(v) => format!("{}", v),
+            Value::UInt(v) => format!("{} (0x{:X})", v, v),
+            Value::Bytes(v) if v.len() > 32 => format!("{}...", hex::encode(&v[..32])),
+            Value::Bytes(v) => hex::encode(v),
+            Value::Str(v) => format!("{:?}", String::from_utf8_lossy(v)),
+        }
+    }
+    /// Numbers without the hex form, for use after an enum label
+    fn plain(&self) -> String {
+        match self {
+            Value::Int(v) => v.to_string(),
+            Value::UInt(v) => v.to_string(),
+            _ => self.display(),
+        }
+    }
+    fn into_lua(self, lua: &Lua) -> LuaResult<LuaValue> {
+        match self {
+            Value::Int(v) => v.into_lua(lua),
+            Value::UInt(v) => v.into_lua(lua),
+            Value::Bytes(v) | Value::Str(v) => lua.create_string(v).map(LuaValue::String),
+        }
+    }
+}
+
+/// Size in bytes of a fixed-width field type
+fn sizeof(ty: &str) -> Option<usize> {
+    match ty {
+        "u8" | "i8" => Some(1),
+        "u16le" | "u16be" | "i16le" | "i16be" => Some(2),
+        "u32le" | "u32be" | "i32le" | "i32be" => Some(4),
+        "u64le" | "u64be" | "i64le" | "i64be" => Some(8),
+        _ => None,
+    }
+}
+
+/// Reads a value of type `ty`, returning it with the number of bytes it covers.
+/// `bytes` and `str` need a length, `cstr` reads up to a NUL within the optional length.
+fn read_value(
+    input: &[u8],
+    ty: &str,
+    offset: usize,
+    length: Option<usize>,
+) -> Result<(Value, usize), String> {
+    let size = match (sizeof(ty), ty) {
+        (Some(size), _) => size,
+        (None, "bytes" | "str") => length.ok_or(format!("Type {} needs a length", ty))?,
+        (None, "cstr") => length.unwrap_or(input.len().saturating_sub(offset)),
+        _ => return Err(format!("Unknown field type: {}", ty)),
+    };
+    let data = offset
+        .checked_add(size)
+        .and_then(|end| input.get(offset..end))
+        .ok_or(format!(
+            "Reading {} bytes at offset 0x{:X} runs past the end (0x{:X})",
+            size,
+            offset,
+            input.len()
+        ))?;
+    let value = match ty {
+        "bytes" => Value::Bytes(data.to_vec()),
+        "str" => Value::Str(data.to_vec()),
+        "cstr" => match data.iter().position(|&b| b == 0) {
+            Some(nul) => return Ok((Value::Str(data[..nul].to_vec()), nul + 1)),
+            None => Value::Str(data.to_vec()),
+        },
+        // integers are 1 to 8 bytes wide here, as `sizeof` gave their size
+        _ => {
+            let mut be = [0u8; 8];
+            let mut le = [0u8; 8];
+            be[8 - size..].copy_from_slice(data);
+            le[..size].copy_from_slice(data);
+            let unsigned = if ty.ends_with("be") {
+                u64::from_be_bytes(be)
+            } else {
+                u64::from_le_bytes(le)
+            };
+            if ty.starts_with('i') {
+                // sign extend from the field width
+                let shift = 64 - size as u32 * 8;
+                Value::Int(((unsigned << shift) as i64) >> shift)
+            } else {
+                Value::UInt(unsigned)
+            }
+        }
+    };
+    Ok((value, size))
+}
+
+fn new_lua() -> Result<Lua, String> {
+    let stdlib = LuaStdLib::TABLE | LuaStdLib::STRING | LuaStdLib::BIT | LuaStdLib::MATH;
+    let lua = Lua::new_with(stdlib, LuaOptions::default()).map_err(|e| e.to_string())?;
+    lua.set_memory_limit(256 * 1024 * 1024)
+        .map_err(|e| e.to_string())?;
+    // stop runaway loops in user templates
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+    lua.set_hook(
+        mlua::HookTriggers::new().every_nth_instruction(10000),
+        move |_, _| {
+            if std::time::Instant::now() > deadline {
+                Err(LuaError::runtime("Template timed out"))
+            } else {
+                Ok(mlua::VmState::Continue)
+            }
+        },
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(lua)
+}
+
+/// Evaluates a template script, which returns `{ name = ..., magic = ..., parse = function() ... end }`
+fn describe(lua: &Lua, source: &str, origin: &str) -> LuaResult<LuaTable> {
+    lua.load(source).set_name(format!("={}", origin)).eval()
+}
+
+fn load(source: String, origin: String) -> Result<Template, String> {
+    let lua = new_lua()?;
+    let table = describe(&lua, &source, &origin).map_err(|e| format!("{}: {}", origin, e))?;
+    let name: String = table
+        .get("name")
+        .map_err(|e| format!("{}: {}", origin, e))?;
+    let magic: Option<LuaString> = table.get("magic").map_err(|e| e.to_string())?;
+    let magic_offset: Option<usize> = table.get("magic_offset").map_err(|e| e.to_string())?;
+    Ok(Template {
+        name,
+        origin,
+        source,
+        magic: magic.map(|m| (magic_offset.unwrap_or(0), m.as_bytes().to_vec())),
+    })
+}
+
+/// Built-in templates followed by the ones in [`TEMPLATE_DIR`].
+/// User templates that fail to load are reported in the second list instead.
+pub fn templates() -> (Vec<Template>, Vec<String>) {
+    let mut list = Vec::new();
+    let mut errors = Vec::new();
+    for (file, source) in BUILTIN {
+        match load(source.to_string(), format!("built-in {}", file)) {
+            Ok(t) => list.push(Template {
+                origin: "built-in".to_string(),
+                ..t
+            }),
+            Err(e) => errors.push(e),
+        }
+    }
+    let dir = match crate::sub_path(TEMPLATE_DIR).and_then(|dir| {
+        crate::ensure_dir(&dir)?;
+        Ok(dir)
+    }) {
+        Ok(dir) => dir,
+        Err(e) => {
+            errors.push(e);
+            return (list, errors);
+        }
+    };
+    let mut files: Vec<std::path::PathBuf> = match std::fs::read_dir(&dir) {
+        Ok(entries) => entries
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|p| p.extension().is_some_and(|ext| ext == "lua"))
+            .collect(),
+        Err(e) => {
+            errors.push(format!("Failed to read {}: {}", dir.to_string_lossy(), e));
+            return (list, errors);
+        }
+    };
+    files.sort();
+    for path in files {
+        let origin = path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        match crate::read_file(&path).and_then(|source| load(source, origin)) {
+            Ok(t) => list.push(t),
+            Err(e) => errors.push(e),
+        }
+    }
+    (list, errors)
+}
+
+/// The first template whose magic matches the input
+pub fn detect(templates: &[Template], input: &[u8]) -> Option<usize> {
+    templates.iter().position(|t| match &t.magic {
+        Some((offset, magic)) => input
+            .get(*offset..)
+            .is_some_and(|rest| rest.starts_with(magic)),
+        None => false,
+    })
+}
+
+/// Moves the innermost open group into its parent, sizing it to its children if needed
+fn close_group(stack: &mut Vec<Field>, length: Option<usize>) {
+    if let Some(mut group) = stack.pop() {
+        group.length = length.unwrap_or_else(|| {
+            group
+                .children
+                .iter()
+                .map(|c| c.offset + c.length)
+                .max()
+                .unwrap_or(group.offset)
+                .saturating_sub(group.offset)
+        });
+        if let Some(parent) = stack.last_mut() {
+            parent.children.push(group);
+        }
+    }
+}
+
+/// Runs the template's `parse` function over the input.
+///
+/// Fields parsed before an error are kept, so a damaged header still shows
+/// everything up to the point where it breaks.
+///
+/// Globals available to `parse`, offsets are 0-based:
+/// - `size`: length of the buffer
+/// - `field(name, type, offset, [length], [names])`: adds a field and returns its value,
+///   `names` maps values to labels. Types are `u8`, `i8`, `u16le`, `u16be`, `i16le`, ...
+///   up to `u64be`, plus `bytes`, `str` (both need a length) and `cstr`
+/// - `note(name, offset, length, text)`: adds a field with custom text
+/// - `group(name, offset, [length], function)`: nests the fields added by the function,
+///   sized to its children when the length is nil
+/// - `read(type, offset, [length])`, `sizeof(type)`: read without adding a field
+/// - `find(pattern, [from])`, `rfind(pattern)`: offset of a byte string or nil
+/// - `png_chunks()`: list of `{offset, length, type, crc}` of a PNG stream
+pub fn run(template: &Template, input: &[u8]) -> (Vec<Field>, Option<String>) {
+    let lua = match new_lua() {
+        Ok(lua) => lua,
+        Err(e) => return (Vec::new(), Some(e)),
+    };
+    let root = Field {
+        name: template.name.clone(),
+        offset: 0,
+        length: input.len(),
+        value: String::new(),
+        children: Vec::new(),
+    };
+    let stack = RefCell::new(vec![root]);
+    let result = lua.scope(|scope| {
+        let globals = lua.globals();
+        globals.set("size", input.len())?;
+        globals.set(
+            "sizeof",
+            scope.create_function(|_, ty: String| Ok(sizeof(&ty)))?,
+        )?;
+        globals.set(
+            "read",
+            scope.create_function(
+                |lua, (ty, offset, length): (String, usize, Option<usize>)| {
+                    let (value, _) =
+                        read_value(input, &ty, offset, length).map_err(LuaError::runtime)?;
+                    value.into_lua(lua)
+                },
+            )?,
+        )?;
+        globals.set(
+            "field",
+            scope.create_function(
+                |lua,
+                 (name, ty, offset, length, names): (
+                    String,
+                    String,
+                    usize,
+                    Option<usize>,
+                    Option<LuaTable>,
+                )| {
+                    let (value, length) =
+                        read_value(input, &ty, offset, length).map_err(LuaError::runtime)?;
+                    let mut display = value.display();
+                    let plain = value.plain();
+                    let lua_value = value.into_lua(lua)?;
+                    if let Some(names) = names {
+                        if let Some(label) = names.get::<Option<String>>(lua_value.clone())? {
+                            display = format!("{} ({})", label, plain);
+                        }
+                    }
+                    if let Some(group) = stack.borrow_mut().last_mut() {
+                        group.children.push(Field {
+                            name,
+                            offset,
+                            length,
+                            value: display,
+                            children: Vec::new(),
+                        });
+                    }
+                    Ok(lua_value)
+                },
+            )?,
+        )?;
+        globals.set(
+            "note",
+            scope.create_function(
+                |_, (name, offset, length, text): (String, usize, usize, String)| {
+                    if let Some(group) = stack.borrow_mut().last_mut() {
+                        group.children.push(Field {
+                            name,
+                            offset,
+                            length,
+                            value: text,
+                            children: Vec::new(),
+                        });
+                    }
+                    Ok(())
+                },
+            )?,
+        )?;
+        globals.set(
+            "group",
+            scope.create_function(
+                |_, (name, offset, length, body): (String, usize, Option<usize>, LuaFunction)| {
+                    stack.borrow_mut().push(Field {
+                        name,
+                        offset,
+                        length: 0,
+                        value: String::new(),
+                        children: Vec::new(),
+                    });
+                    // the stack must not stay borrowed while the body adds fields
+                    let result = body.call::<()>(());
+                    // close it even on error, so a damaged group still shows what it parsed
+                    close_group(&mut stack.borrow_mut(), length);
+                    result
+                },
+            )?,
+        )?;
+        globals.set(
+            "find",
+            scope.create_function(|_, (pattern, from): (LuaString, Option<usize>)| {
+                Ok(crate::find_index_from(
+                    input,
+                    &pattern.as_bytes(),
+                    from.unwrap_or(0),
+                ))
+            })?,
+        )?;
+        globals.set(
+            "rfind",
+            scope.create_function(|_, pattern: LuaString| {
+                Ok(crate::rfind_index(input, &pattern.as_bytes()))
+            })?,
+        )?;
+        globals.set(
+            "png_chunks",
+            scope.create_function(|lua, ()| {
+                let chunks =
+                    crate::tools::polyglot_png::png_chunks(input).map_err(LuaError::runtime)?;
+                let list = lua.create_table()?;
+                for chunk in chunks {
+                    let item = lua.create_table()?;
+                    item.set("offset", chunk.offset)?;
+                    item.set("length", chunk.length)?;
+                    item.set("type", lua.create_string(chunk.chunk_type)?)?;
+                    item.set("crc", chunk.crc)?;
+                    list.push(item)?;
+                }
+                Ok(list)
+            })?,
+        )?;
+        let table = describe(&lua, &template.source, &template.origin)?;
+        table.get::<LuaFunction>("parse")?.call::<()>(())
+    });
+    let mut stack = stack.into_inner();
+    let fields = stack.pop().map(|root| root.children).unwrap_or_default();
+    (fields, result.err().map(|e| e.to_string()))
+}
+
+#[cfg(test)]
+mod test {
+    fn builtin(name: &str) -> super::Template {
+        let (list, errors) = super::templates();
+        assert!(errors.is_empty(), "{:?}", errors);
+        list.into_iter().find(|t| t.name == name).unwrap()
+    }
+    fn find<'a>(fields: &'a [super::Field], name: &str) -> &'a super::Field {
+        fields.iter().find(|f| f.name == name).unwrap()
+    }
+
+    #[test]
+    fn test_read() {
+        let data = [0xff, 0xfe, 0x01, 0x00, b'h', b'i', 0, b'x'];
+        let (v, _) = super::read_value(&data, "i16le", 0, None).unwrap();
+        assert_eq!(v.display(), "-257");
+        let (v, _) = super::read_value(&data, "u16be", 2, None).unwrap();
+        assert_eq!(v.display(), "256 (0x100)");
+        let (v, len) = super::read_value(&data, "cstr", 4, None).unwrap();
+        assert_eq!((v.display().as_str(), len), ("\"hi\"", 3));
+        assert!(super::read_value(&data, "u32le", 6, None).is_err());
+        for ty in ["bytes", "str", "cstr"] {
+            assert_eq!(super::read_value(&data, ty, 8, Some(0)).unwrap().1, 0);
+        }
+        assert_eq!(super::read_value(&data, "cstr", 8, None).unwrap().1, 0);
+    }
+    /// Each built-in template on input that makes it read a zero-length field,
+    /// or for BMP and PE, whose lengths are fixed or guarded, a truncated file
+    #[test]
+    fn test_empty_fields() {
+        // ZIP entry with an empty name
+        let mut zip = b"PK\x03\x04".to_vec();
+        zip.extend_from_slice(&[0; 26]);
+        let mut central = b"PK\x01\x02".to_vec();
+        central.extend_from_slice(&[0; 42]);
+        zip.extend_from_slice(&central);
+        let mut eocd = b"PK\x05\x06\0\0\0\0\x01\0\x01\0".to_vec();
+        eocd.extend_from_slice(&46u32.to_le_bytes());
+        eocd.extend_from_slice(&30u32.to_le_bytes());
+        eocd.extend_from_slice(&[0, 0]);
+        zip.extend_from_slice(&eocd);
+        // PNG tEXt chunk without a keyword
+        let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+        png.extend_from_slice(b"\0\0\0\0tEXt\0\0\0\0");
+        png.extend_from_slice(b"\0\0\0\0IEND\xaeB`\x82");
+        // ELF section whose name string table starts at the end of the file
+        let mut elf = b"\x7fELF\x02\x01\x01".to_vec();
+        elf.resize(128, 0);
+        elf[0x3a] = 64; // shentsize
+        elf[0x3c] = 1; // shnum
+        elf[0x28] = 64; // shoff
+        elf[64 + 24] = 128; // offset of section 0, read as the string table
+
+        // WAV data chunk cut off after its header
+        let mut wav = b"RIFF\x0c\0\0\0WAVEdata".to_vec();
+        wav.extend_from_slice(&16u32.to_le_bytes());
+        let bmp = b"BM\x46\x00\x00\x00".to_vec();
+        let mut pe = b"MZ".to_vec();
+        pe.resize(0x40, 0);
+        pe[0x3c] = 0x40;
+        for (name, input, empty) in [
+            ("ZIP", zip, Some("name")),
+            ("PNG", png, Some("keyword")),
+            ("ELF", elf, None),
+            ("WAV", wav, Some("data")),
+            ("BMP", bmp, None),
+            ("PE/COFF", pe, None),
+        ] {
+            let (fields, _) = super::run(&builtin(name), &input);
+            fn lengths(fields: &[super::Field], name: &str) -> Vec<usize> {
+                fields
+                    .iter()
+                    .flat_map(|f| {
+                        let own = (f.name == name).then_some(f.length);
+                        own.into_iter().chain(lengths(&f.children, name))
+                    })
+                    .collect()
+            }
+            if let Some(field) = empty {
+                assert!(lengths(&fields, field).contains(&0), "{}", name);
+            }
+        }
+    }
+    #[test]
+    fn test_png() {
+        let png = include_bytes!("../../../assets/shitools.png");
+        let (list, _) = super::templates();
+        let template = &list[super::detect(&list, png).unwrap()];
+        assert_eq!(template.name, "PNG");
+        let (fields, error) = super::run(template, png);
+        assert!(error.is_none(), "{:?}", error);
+        let ihdr = find(&fields, "IHDR");
+        assert_eq!((ihdr.offset, ihdr.length), (8, 25));
+        assert_eq!(find(&ihdr.children, "width").value, "256 (0x100)");
+        assert_eq!(fields.last().unwrap().name, "IEND");
+    }
+    #[test]
+    fn test_partial() {
+        let mut bmp =
+            b"BM\x46\x00\x00\x00\x00\x00\x00\x00\x36\x00\x00\x00\x28\x00\x00\x00".to_vec();
+        bmp.extend_from_slice(&[0x02, 0, 0, 0]);
+        let (fields, error) = super::run(&builtin("BMP"), &bmp);
+        assert!(error.is_some());
+        assert_eq!(find(&fields, "file header").children.len(), 5);
+        assert_eq!(
+            find(&find(&fields, "info header").children, "width").value,
+            "2"
+        );
+    }
+}
@@ -0,0 +1,262 @@
+/// Relative frequency of letters a-z in English text
+const ENGLISH_FREQ: [f64; 26] = [
+    0.08167, 0.01492, 0.02782, 0.04253, 0.12702, 0.02228, 0.02015, 0.06094, 0.06966, 0.00153,
+    0.00772, 0.04025, 0.02406, 0.06749, 0.07507, 0.01929, 0.00095, 0.05987, 0.06327, 0.09056,
+    0.02758, 0.00978, 0.02360, 0.00150, 0.01974, 0.00074,
+];
+
+/// Scores how much the bytes look like English text, higher is better.
+/// Letters score by frequency, spaces score like a common letter,
+/// other printable bytes barely count and non-printable bytes are penalised.
+pub fn english_score(input: &[u8]) -> f64 {
+    if input.is_empty() {
+        return 0.0;
+    }
+    let mut score = 0.0;
+    for &b in input {
+        score += match b {
+            b'a'..=b'z' => ENGLISH_FREQ[(b - b'a') as usize],
+            b'A'..=b'Z' => ENGLISH_FREQ[(b - b'A') as usize] * 0.8,
+            b' ' => 0.13,
+            b'0'..=b'9' => 0.005,
+            b'\n' | b'\r' | b'\t' => 0.01,
+            0x21..=0x7e => 0.002,
+            _ => -0.5,
+        };
+    }
+    score / input.len() as f64
+}
+
+fn xor_with(input: &[u8], key: &[u8]) -> Vec<u8> {
+    input
+        .iter()
+        .enumerate()
+        .map(|(i, b)| b ^ key[i % key.len()])
+        .collect()
+}
+
+fn printable_preview(input: &[u8], max: usize) -> String {
+    input
+        .iter()
+        .take(max)
+        .map(|&b| {
+            if (0x20..=0x7e).contains(&b) {
+                b as char
+            } else {
+                '·'
+            }
+        })
+        .collect()
+}
+
+/// Tries all 256 single-byte keys, best first. Returns (key, score).
+pub fn single_byte_rank(input: &[u8]) -> Vec<(u8, f64)> {
+    let mut ranked: Vec<(u8, f64)> = (0..=255u8)
+        .map(|key| {
+            let decoded: Vec<u8> = input.iter().map(|b| b ^ key).collect();
+            (key, english_score(&decoded))
+        })
+        .collect();
+    ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+    ranked
+}
+
+pub fn single_byte_report(input: &[u8], top: usize) -> String {
+    let mut report = String::from("key\tscore\tpreview\n");
+    for (key, score) in single_byte_rank(input).into_iter().take(top) {
+        let decoded: Vec<u8> = input.iter().map(|b| b ^ key).collect();
+        report.push_str(&format!(
+            "{:02x}\t{:.4}\t{}\n",
+            key,
+            score,
+            printable_preview(&decoded, 64)
+        ));
+    }
+    report
+}
+
+pub fn hamming(a: &[u8], b: &[u8]) -> u32 {
+    a.iter().zip(b).map(|(x, y)| (x ^ y).count_ones()).sum()
+}
+
+/// Average Hamming distance between consecutive blocks, per bit.
+/// The right key length makes blocks line up with the same key bytes, giving a lower distance.
+pub fn normalized_hamming(input: &[u8], keylen: usize) -> Option<f64> {
+    let blocks: Vec<&[u8]> = input.chunks_exact(keylen).collect();
+    if blocks.len() < 2 {
+        return None;
+    }
+    let pairs = blocks.len() - 1;
+    let total: u32 = blocks.windows(2).map(|w| hamming(w[0], w[1])).sum();
+    Some(total as f64 / pairs as f64 / keylen as f64)
+}
+
+/// Kasiski examination: distances between repeated trigrams vote for each of their factors.
+/// Returns votes indexed by key length, up to `max_keylen`.
+pub fn kasiski(input: &[u8], max_keylen: usize) -> Vec<usize> {
+    let mut votes = vec![0usize; max_keylen + 1];
+    let mut last_seen = std::collections::HashMap::new();
+    for i in 0..input.len().saturating_sub(2) {
+        let trigram = [input[i], input[i + 1], input[i + 2]];
+        if let Some(prev) = last_seen.insert(trigram, i) {
+            let distance = i - prev;
+            for (k, vote) in votes.iter_mut().enumerate().skip(2) {
+                if distance % k == 0 {
+                    *vote += 1;
+                }
+            }
+        }
+    }
+    votes
+}
+
+/// Key length candidates, best first: (key length, normalized Hamming distance, Kasiski votes)
+pub fn keylen_rank(input: &[u8], max_keylen: usize) -> Vec<(usize, f64, usize)> {
+    let votes = kasiski(input, max_keylen);
+    let mut ranked: Vec<(usize, f64, usize)> = (1..=max_keylen)
+        .filter_map(|k| normalized_hamming(input, k).map(|h| (k, h, votes[k])))
+        .collect();
+    ranked.sort_by(|a, b| a.1.total_cmp(&b.1));
+    ranked
+}
+
+pub fn keylen_report(input: &[u8], max_keylen: usize) -> String {
+    let mut report = String::from("length\thamming\tkasiski\n");
+    for (k, h, v) in keylen_rank(input, max_keylen).into_iter().take(10) {
+        report.push_str(&format!("{}\t{:.4}\t{}\n", k, h, v));
+    }
+    report
+}
+
+/// Solves each column of a repeating-key XOR as a single-byte XOR
+pub fn solve_repeating(input: &[u8], keylen: usize) -> Vec<u8> {
+    (0..keylen)
+        .map(|col| {
+            let column: Vec<u8> = input.iter().skip(col).step_by(keylen).copied().collect();
+            single_byte_rank(&column)[0].0
+        })
+        .collect()
+}
+
+/// Picks the most likely key length (0 means automatic) and solves it
+pub fn solve_report(input: &[u8], keylen: usize, max_keylen: usize) -> Result<String, String> {
+    let keylen = if keylen == 0 {
+        keylen_rank(input, max_keylen)
+            .first()
+            .map(|c| c.0)
+            .ok_or("Input too short to estimate the key length".to_string())?
+    } else {
+        keylen
+    };
+    let key = solve_repeating(input, keylen);
+    let decoded = xor_with(input, &key);
+    Ok(format!(
+        "key length: {}\nkey (hex): {}\nkey (text): {}\nscore: {:.4}\n\n{}",
+        keylen,
+        hex::encode(&key),
+        printable_preview(&key, key.len()),
+        english_score(&decoded),
+        printable_preview(&decoded, 1024)
+    ))
+}
+
+/// The shortest period that repeats through the whole keystream
+pub fn find_period(keystream: &[u8]) -> usize {
+    (1..=keystream.len())
+        .find(|&p| (p..keystream.len()).all(|i| keystream[i] == keystream[i - p]))
+        .unwrap_or(keystream.len())
+}
+
+/// XORs a known plaintext into the input at `offset` to recover the keystream there.
+/// When the keystream repeats, the key is reported aligned to the start of the input.
+pub fn known_plaintext(input: &[u8], plain: &[u8], offset: usize) -> Result<String, String> {
+    if plain.is_empty() {
+        return Err("Known plaintext is empty".to_string());
+    }
+    if offset + plain.len() > input.len() {
+        return Err(format!(
+            "Known plaintext of {} bytes at offset {} runs past the input ({} bytes)",
+            plain.len(),
+            offset,
+            input.len()
+        ));
+    }
+    let keystream: Vec<u8> = input[offset..offset + plain.len()]
+        .iter()
+        .zip(plain)
+        .map(|(c, p)| c ^ p)
+        .collect();
+    let mut report = format!("keystream: {}\n", hex::encode(&keystream));
+    let period = find_period(&keystream);
+    if period < keystream.len() {
+        let shift = offset % period;
+        let key: Vec<u8> = (0..period)
+            .map(|i| keystream[(i + period - shift) % period])
+            .collect();
+        let decoded = xor_with(input, &key);
+        report.push_str(&format!(
+            "repeating key ({} bytes): {}\nkey (text): {}\n\n{}",
+            period,
+            hex::encode(&key),
+            printable_preview(&key, key.len()),
+            printable_preview(&decoded, 1024)
+        ));
+    } else {
+        report.push_str("no repeating period found, the key may be longer than the plaintext");
+    }
+    Ok(report)
+}
+
+/// Slides a crib over the input and lists offsets where the implied keystream is printable,
+/// which is where a text key would be found
+pub fn crib_drag(input: &[u8], crib: &[u8]) -> String {
+    let mut report = String::from("offset\tkeystream\n");
+    if crib.is_empty() || crib.len() > input.len() {
+        return report;
+    }
+    for offset in 0..=input.len() - crib.len() {
+        let keystream: Vec<u8> = input[offset..offset + crib.len()]
+            .iter()
+            .zip(crib)
+            .map(|(c, p)| c ^ p)
+            .collect();
+        if keystream.iter().all(|b| (0x20..=0x7e).contains(b)) {
+            report.push_str(&format!(
+                "{:08X}\t{}\n",
+                offset,
+                printable_preview(&keystream, keystream.len())
+            ));
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+mod test {
+    const PLAIN: &[u8] = b"It was the best of times, it was the worst of times, it was the age of \
+        wisdom, it was the age of foolishness, it was the epoch of belief, it was the epoch of \
+        incredulity, it was the season of Light, it was the season of Darkness, it was the \
+        spring of hope, it was the winter of despair.";
+
+    #[test]
+    fn test_single_byte() {
+        let cipher = super::xor_with(PLAIN, &[0x5a]);
+        assert_eq!(super::single_byte_rank(&cipher)[0].0, 0x5a);
+    }
+    #[test]
+    fn test_repeating() {
+        assert_eq!(super::hamming(b"this is a test", b"wokka wokka!!!"), 37);
+        let key = b"ICE?";
+        let cipher = super::xor_with(PLAIN, key);
+        let ranked = super::keylen_rank(&cipher, 16);
+        assert_eq!(ranked[0].0 % key.len(), 0);
+        assert_eq!(super::solve_repeating(&cipher, key.len()), key);
+    }
+    #[test]
+    fn test_known_plaintext() {
+        let cipher = super::xor_with(PLAIN, b"key");
+        let report = super::known_plaintext(&cipher, b"best of times", 11).unwrap();
+        assert!(report.contains(&format!("repeating key (3 bytes): {}", hex::encode("key"))));
+        assert_eq!(super::find_period(b"abcabcab"), 3);
+    }
+}
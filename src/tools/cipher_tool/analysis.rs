@@ -0,0 +1,158 @@
+use super::cipher;
+
+/// Relative letter frequencies of English text, A to Z
+pub const ENGLISH: [f64; 26] = [
+    0.08167, 0.01492, 0.02782, 0.04253, 0.12702, 0.02228, 0.02015, 0.06094, 0.06966, 0.00153,
+    0.00772, 0.04025, 0.02406, 0.06749, 0.07507, 0.01929, 0.00095, 0.05987, 0.06327, 0.09056,
+    0.02758, 0.00978, 0.02360, 0.00150, 0.01974, 0.00074,
+];
+
+/// Index of coincidence of English and of uniformly random letters
+const IC_ENGLISH: f64 = 0.0667;
+const IC_RANDOM: f64 = 1.0 / 26.0;
+
+fn letters(text: &str) -> Vec<u8> {
+    text.bytes()
+        .filter(u8::is_ascii_alphabetic)
+        .map(|b| b.to_ascii_uppercase() - b'A')
+        .collect()
+}
+
+pub fn counts(text: &str) -> [usize; 26] {
+    count(&letters(text))
+}
+
+fn count(letters: &[u8]) -> [usize; 26] {
+    let mut counts = [0; 26];
+    for &x in letters {
+        counts[x as usize] += 1;
+    }
+    counts
+}
+
+/// Chi-squared distance from English; lower is more English-like
+pub fn chi_squared(counts: &[usize; 26]) -> f64 {
+    let total: usize = counts.iter().sum();
+    if total == 0 {
+        return f64::INFINITY;
+    }
+    counts
+        .iter()
+        .zip(ENGLISH)
+        .map(|(&c, p)| {
+            let expected = p * total as f64;
+            (c as f64 - expected).powi(2) / expected
+        })
+        .sum()
+}
+
+pub fn index_of_coincidence(counts: &[usize; 26]) -> f64 {
+    let total: usize = counts.iter().sum();
+    if total < 2 {
+        return 0.0;
+    }
+    let pairs: usize = counts.iter().map(|c| c * c.saturating_sub(1)).sum();
+    pairs as f64 / (total * (total - 1)) as f64
+}
+
+/// Every Caesar shift of `text` as (shift, chi-squared, plaintext), most English-like first
+pub fn brute_force(text: &str) -> Vec<(i32, f64, String)> {
+    let mut all: Vec<(i32, f64, String)> = (1..26)
+        .map(|n| {
+            let plain = cipher::shift(text, -n);
+            (n, chi_squared(&counts(&plain)), plain)
+        })
+        .collect();
+    all.sort_by(|a, b| a.1.total_cmp(&b.1));
+    all
+}
+
+/// Friedman's estimate of the Vigenère key length from the index of coincidence
+pub fn friedman(text: &str) -> Option<f64> {
+    let ic = index_of_coincidence(&counts(text));
+    (ic > IC_RANDOM).then(|| (IC_ENGLISH - IC_RANDOM) / (ic - IC_RANDOM))
+}
+
+/// Kasiski examination: how many distances between repeated trigrams each key length
+/// from 2 to `max` divides, most frequent first
+pub fn kasiski(text: &str, max: usize) -> Vec<(usize, usize)> {
+    let letters = letters(text);
+    let mut distances = Vec::new();
+    let mut last = std::collections::HashMap::new();
+    for (i, trigram) in letters.windows(3).enumerate() {
+        if let Some(prev) = last.insert(trigram, i) {
+            distances.push(i - prev);
+        }
+    }
+    let mut lengths: Vec<(usize, usize)> = (2..=max)
+        .map(|len| (len, distances.iter().filter(|d| *d % len == 0).count()))
+        .filter(|(_, n)| *n > 0)
+        .collect();
+    lengths.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    lengths
+}
+
+/// Average index of coincidence of the columns when `text` is split by key length `len`
+pub fn column_ic(text: &str, len: usize) -> f64 {
+    let letters = letters(text);
+    let total: f64 = (0..len)
+        .map(|col| {
+            let column: Vec<u8> = letters.iter().skip(col).step_by(len).copied().collect();
+            index_of_coincidence(&count(&column))
+        })
+        .sum();
+    total / len as f64
+}
+
+/// The most English-like Vigenère key of length `len`, solving each column as a Caesar shift
+pub fn guess_key(text: &str, len: usize) -> String {
+    let letters = letters(text);
+    (0..len)
+        .map(|col| {
+            let column: Vec<u8> = letters.iter().skip(col).step_by(len).copied().collect();
+            let best = (0..26u8)
+                .min_by(|&a, &b| {
+                    let score = |k: u8| {
+                        chi_squared(&count(
+                            &column.iter().map(|x| (x + 26 - k) % 26).collect::<Vec<_>>(),
+                        ))
+                    };
+                    score(a).total_cmp(&score(b))
+                })
+                .unwrap_or(0);
+            (b'A' + best) as char
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const PLAIN: &str = "It was the best of times, it was the worst of times, it was the age of \
+        wisdom, it was the age of foolishness, it was the epoch of belief, it was the epoch of \
+        incredulity, it was the season of Light, it was the season of Darkness, it was the \
+        spring of hope, it was the winter of despair, we had everything before us, we had \
+        nothing before us, we were all going direct to Heaven, we were all going direct the \
+        other way";
+
+    #[test]
+    fn test_caesar() {
+        let best = &brute_force(&cipher::shift(PLAIN, 7))[0];
+        assert_eq!(best.0, 7);
+        assert_eq!(best.2, PLAIN);
+    }
+
+    #[test]
+    fn test_vigenere() {
+        let encrypted = cipher::vigenere(PLAIN, "DICKENS", false).unwrap();
+        assert!(kasiski(&encrypted, 20)
+            .iter()
+            .take(3)
+            .any(|(len, _)| *len == 7));
+        let estimate = friedman(&encrypted).unwrap();
+        assert!((3.0..12.0).contains(&estimate), "{}", estimate);
+        assert!(column_ic(&encrypted, 7) > column_ic(&encrypted, 5));
+        assert_eq!(guess_key(&encrypted, 7), "DICKENS");
+    }
+}
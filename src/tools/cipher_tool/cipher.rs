@@ -0,0 +1,300 @@
+/// Letters of a key, as 0..26, ignoring everything else
+fn key_shifts(key: &str) -> Vec<u8> {
+    key.chars()
+        .filter(char::is_ascii_alphabetic)
+        .map(|c| c.to_ascii_uppercase() as u8 - b'A')
+        .collect()
+}
+
+/// Applies `f` to the alphabet index of each ASCII letter, keeping case and leaving the rest
+fn map_letters(text: &str, mut f: impl FnMut(u8) -> u8) -> String {
+    text.chars()
+        .map(|c| {
+            let base = match c {
+                'a'..='z' => b'a',
+                'A'..='Z' => b'A',
+                _ => return c,
+            };
+            (base + f(c as u8 - base) % 26) as char
+        })
+        .collect()
+}
+
+/// Caesar cipher; ROT13 is a shift of 13
+pub fn shift(text: &str, n: i32) -> String {
+    let n = n.rem_euclid(26) as u8;
+    map_letters(text, |x| x + n)
+}
+
+pub fn vigenere(text: &str, key: &str, decrypt: bool) -> Result<String, String> {
+    let shifts = key_shifts(key);
+    if shifts.is_empty() {
+        return Err("key has no letters".to_string());
+    }
+    let mut i = 0;
+    Ok(map_letters(text, |x| {
+        let k = shifts[i % shifts.len()];
+        i += 1;
+        if decrypt {
+            x + 26 - k
+        } else {
+            x + k
+        }
+    }))
+}
+
+pub fn atbash(text: &str) -> String {
+    map_letters(text, |x| 25 - x)
+}
+
+fn mod_inverse(a: i32) -> Option<i32> {
+    (1..26).find(|x| (a * x).rem_euclid(26) == 1)
+}
+
+/// `E(x) = a·x + b mod 26`, where `a` has to be coprime with 26
+pub fn affine(text: &str, a: i32, b: i32, decrypt: bool) -> Result<String, String> {
+    let inverse = mod_inverse(a).ok_or(format!("a = {} is not coprime with 26", a))?;
+    Ok(map_letters(text, |x| {
+        let x = x as i32;
+        let y = if decrypt {
+            inverse * (x - b)
+        } else {
+            a * x + b
+        };
+        y.rem_euclid(26) as u8
+    }))
+}
+
+/// Rail of each position when writing `len` characters in a zigzag
+fn zigzag(len: usize, rails: usize) -> Vec<usize> {
+    let cycle = 2 * (rails - 1);
+    (0..len)
+        .map(|i| {
+            let r = i % cycle;
+            if r < rails {
+                r
+            } else {
+                cycle - r
+            }
+        })
+        .collect()
+}
+
+pub fn rail_fence(text: &str, rails: usize, decrypt: bool) -> Result<String, String> {
+    if rails < 2 {
+        return Err("at least 2 rails are needed".to_string());
+    }
+    let chars: Vec<char> = text.chars().collect();
+    let pattern = zigzag(chars.len(), rails);
+    // positions in the order they are read off the rails
+    let mut order: Vec<usize> = (0..chars.len()).collect();
+    order.sort_by_key(|&i| pattern[i]);
+    let mut out = vec![' '; chars.len()];
+    for (k, &i) in order.iter().enumerate() {
+        if decrypt {
+            out[i] = chars[k];
+        } else {
+            out[k] = chars[i];
+        }
+    }
+    Ok(out.into_iter().collect())
+}
+
+/// 5×5 Playfair square from the key, with J merged into I
+pub fn playfair_square(key: &str) -> [u8; 25] {
+    let mut square = [0; 25];
+    let mut n = 0;
+    let letters = key
+        .bytes()
+        .filter(u8::is_ascii_alphabetic)
+        .map(|b| b.to_ascii_uppercase())
+        .chain(b'A'..=b'Z');
+    for b in letters {
+        let b = if b == b'J' { b'I' } else { b };
+        if !square[..n].contains(&b) {
+            square[n] = b;
+            n += 1;
+            if n == 25 {
+                break;
+            }
+        }
+    }
+    square
+}
+
+/// Playfair on the letters of `text`. Encryption splits doubled letters and pads
+/// the last pair with X; decryption leaves the fillers in place.
+pub fn playfair(text: &str, key: &str, decrypt: bool) -> Result<String, String> {
+    let square = playfair_square(key);
+    let pos = |b: u8| square.iter().position(|&s| s == b).unwrap_or(0);
+    let letters: Vec<u8> = text
+        .bytes()
+        .filter(u8::is_ascii_alphabetic)
+        .map(|b| match b.to_ascii_uppercase() {
+            b'J' => b'I',
+            b => b,
+        })
+        .collect();
+    let mut pairs = Vec::new();
+    if decrypt {
+        if letters.len() % 2 == 1 {
+            return Err("ciphertext has an odd number of letters".to_string());
+        }
+        for pair in letters.chunks(2) {
+            if pair[0] == pair[1] {
+                return Err(format!("{} is doubled within a pair", pair[0] as char));
+            }
+            pairs.push((pair[0], pair[1]));
+        }
+    } else {
+        let mut i = 0;
+        while i < letters.len() {
+            let a = letters[i];
+            let filler = if a == b'X' { b'Q' } else { b'X' };
+            match letters.get(i + 1) {
+                Some(&b) if b != a => {
+                    pairs.push((a, b));
+                    i += 2;
+                }
+                _ => {
+                    pairs.push((a, filler));
+                    i += 1;
+                }
+            }
+        }
+    }
+    let step = if decrypt { 4 } else { 1 };
+    let mut out = String::new();
+    for (a, b) in pairs {
+        let (pa, pb) = (pos(a), pos(b));
+        let (ra, ca, rb, cb) = (pa / 5, pa % 5, pb / 5, pb % 5);
+        let (x, y) = if ra == rb {
+            (ra * 5 + (ca + step) % 5, rb * 5 + (cb + step) % 5)
+        } else if ca == cb {
+            (((ra + step) % 5) * 5 + ca, ((rb + step) % 5) * 5 + cb)
+        } else {
+            (ra * 5 + cb, rb * 5 + ca)
+        };
+        out.push(square[x] as char);
+        out.push(square[y] as char);
+    }
+    Ok(out)
+}
+
+/// Bacon's cipher with a distinct code for each of the 26 letters, AAAAA for A
+pub fn bacon_encode(text: &str) -> String {
+    text.chars()
+        .filter(char::is_ascii_alphabetic)
+        .map(|c| {
+            let x = c.to_ascii_uppercase() as u8 - b'A';
+            (0..5)
+                .rev()
+                .map(|bit| if x >> bit & 1 == 1 { 'B' } else { 'A' })
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Reads A/B groups of five, ignoring anything else
+pub fn bacon_decode(text: &str) -> Result<String, String> {
+    let bits: Vec<u8> = text
+        .chars()
+        .filter_map(|c| match c {
+            'A' | 'a' => Some(0),
+            'B' | 'b' => Some(1),
+            _ => None,
+        })
+        .collect();
+    if !bits.len().is_multiple_of(5) {
+        return Err(format!("{} A/B letters is not a multiple of 5", bits.len()));
+    }
+    bits.chunks(5)
+        .map(|group| {
+            let x = group.iter().fold(0, |acc, b| acc << 1 | b);
+            if x < 26 {
+                Ok((b'A' + x) as char)
+            } else {
+                Err(format!("code {} is past Z", x))
+            }
+        })
+        .collect()
+}
+
+/// Monoalphabetic substitution; `alphabet` is what A..Z become
+pub fn substitution(text: &str, alphabet: &str, decrypt: bool) -> Result<String, String> {
+    let table = key_shifts(alphabet);
+    let mut seen = [false; 26];
+    for &x in &table {
+        seen[x as usize] = true;
+    }
+    if table.len() != 26 || seen.contains(&false) {
+        return Err("the alphabet needs each of the 26 letters once".to_string());
+    }
+    let mut inverse = [0; 26];
+    for (i, &x) in table.iter().enumerate() {
+        inverse[x as usize] = i as u8;
+    }
+    Ok(map_letters(text, |x| {
+        if decrypt {
+            inverse[x as usize]
+        } else {
+            table[x as usize]
+        }
+    }))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip() {
+        let text = "Attack at Dawn, 5 o'clock!";
+        assert_eq!(shift(text, 3), "Dwwdfn dw Gdzq, 5 r'forfn!");
+        assert_eq!(shift(&shift(text, 3), -3), text);
+        assert_eq!(
+            vigenere("ATTACKATDAWN", "LEMON", false).unwrap(),
+            "LXFOPVEFRNHR"
+        );
+        assert_eq!(
+            vigenere("LXFOPVEFRNHR", "lemon", true).unwrap(),
+            "ATTACKATDAWN"
+        );
+        assert_eq!(atbash("Hello"), "Svool");
+        assert_eq!(affine("AFFINE", 5, 8, false).unwrap(), "IHHWVC");
+        assert_eq!(affine("IHHWVC", 5, 8, true).unwrap(), "AFFINE");
+        assert!(affine("x", 13, 0, false).is_err());
+        let fenced = rail_fence("WEAREDISCOVEREDFLEEATONCE", 3, false).unwrap();
+        assert_eq!(fenced, "WECRLTEERDSOEEFEAOCAIVDEN");
+        assert_eq!(
+            rail_fence(&fenced, 3, true).unwrap(),
+            "WEAREDISCOVEREDFLEEATONCE"
+        );
+        assert_eq!(rail_fence("ab", 5, false).unwrap(), "ab");
+        let alphabet = "QWERTYUIOPASDFGHJKLZXCVBNM";
+        let substituted = substitution(text, alphabet, false).unwrap();
+        assert_eq!(substitution(&substituted, alphabet, true).unwrap(), text);
+        assert!(substitution(text, "ABC", false).is_err());
+    }
+
+    #[test]
+    fn test_playfair() {
+        let key = "playfair example";
+        let encrypted = playfair("Hide the gold in the tree stump", key, false).unwrap();
+        assert_eq!(encrypted, "BMODZBXDNABEKUDMUIXMMOUVIF");
+        assert_eq!(
+            playfair(&encrypted, key, true).unwrap(),
+            "HIDETHEGOLDINTHETREXESTUMP"
+        );
+        assert!(playfair("ABC", key, true).is_err());
+    }
+
+    #[test]
+    fn test_bacon() {
+        assert_eq!(bacon_encode("Hi"), "AABBB ABAAA");
+        assert_eq!(bacon_decode("aabbb-abaaa").unwrap(), "HI");
+        assert!(bacon_decode("AAAA").is_err());
+        assert!(bacon_decode("BBBBB").is_err());
+    }
+}
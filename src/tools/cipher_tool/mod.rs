@@ -0,0 +1,429 @@
+use super::*;
+use eframe::egui;
+
+mod analysis;
+mod cipher;
+
+/// Longest Vigenère key the analysis tries
+const MAX_KEY_LEN: usize = 20;
+
+#[derive(PartialEq, Eq, Hash, Clone)]
+enum Catagory {
+    Caesar,
+    Vigenere,
+    Atbash,
+    Affine,
+    RailFence,
+    Playfair,
+    Bacon,
+    Substitution,
+    Frequency,
+}
+
+/// Key length candidates of a Vigenère ciphertext
+#[derive(PartialEq, Clone)]
+struct KeyAnalysis {
+    friedman: Option<f64>,
+    /// Length, Kasiski count, average column index of coincidence, guessed key
+    lengths: Vec<(usize, usize, f64, String)>,
+}
+
+impl KeyAnalysis {
+    fn new(text: &str) -> Self {
+        let kasiski = analysis::kasiski(text, MAX_KEY_LEN);
+        let max = (analysis::counts(text).iter().sum::<usize>() / 2).clamp(1, MAX_KEY_LEN);
+        let lengths = (1..=max)
+            .map(|len| {
+                let hits = kasiski
+                    .iter()
+                    .find(|(l, _)| *l == len)
+                    .map_or(0, |(_, n)| *n);
+                let ic = analysis::column_ic(text, len);
+                (len, hits, ic, analysis::guess_key(text, len))
+            })
+            .collect();
+        Self {
+            friedman: analysis::friedman(text),
+            lengths,
+        }
+    }
+}
+
+#[derive(PartialEq, Clone)]
+pub struct CipherTool {
+    input: String,
+    output: String,
+    selected_catagory: Catagory,
+    shift: i32,
+    /// Caesar shifts of Input, most English-like first
+    brute_force: Vec<(i32, f64, String)>,
+    vigenere_key: String,
+    key_analysis: Option<KeyAnalysis>,
+    affine_a: i32,
+    affine_b: i32,
+    rails: usize,
+    playfair_key: String,
+    alphabet: String,
+    /// Input the letter counts were taken from
+    counted: String,
+    counts: [usize; 26],
+    msg: Option<Msg>,
+}
+
+impl CipherTool {
+    pub fn new() -> Self {
+        Self {
+            input: String::new(),
+            output: String::new(),
+            selected_catagory: Catagory::Caesar,
+            shift: 3,
+            brute_force: Vec::new(),
+            vigenere_key: String::new(),
+            key_analysis: None,
+            affine_a: 5,
+            affine_b: 8,
+            rails: 3,
+            playfair_key: String::new(),
+            alphabet: "QWERTYUIOPASDFGHJKLZXCVBNM".to_string(),
+            counted: String::new(),
+            counts: [0; 26],
+            msg: None,
+        }
+    }
+    fn set_output(&mut self, result: Result<String, String>) {
+        match result {
+            Ok(v) => self.output = v,
+            Err(e) => self.msg = Some(Msg::new(format!("Error: {}", e), MsgType::Error)),
+        }
+    }
+    /// Encrypt and Decrypt buttons, returning `Some(decrypt)` for the one clicked
+    fn direction(ui: &mut egui::Ui) -> Option<bool> {
+        let mut clicked = None;
+        if ui.button("Encrypt").clicked() {
+            clicked = Some(false);
+        }
+        if ui.button("Decrypt").clicked() {
+            clicked = Some(true);
+        }
+        clicked
+    }
+    fn frequency_chart(&self, ui: &mut egui::Ui) {
+        let total: usize = self.counts.iter().sum();
+        let ic = analysis::index_of_coincidence(&self.counts);
+        ui.label(format!(
+            "{} letters, index of coincidence {:.4} (English about 0.0667, random 0.0385)",
+            total, ic
+        ));
+        let bars = |values: Vec<f64>, offset: f64| {
+            values
+                .into_iter()
+                .enumerate()
+                .map(|(i, v)| {
+                    egui_plot::Bar::new(i as f64 + offset, v * 100.0)
+                        .width(0.4)
+                        .name(((b'A' + i as u8) as char).to_string())
+                })
+                .collect()
+        };
+        let text: Vec<f64> = self
+            .counts
+            .iter()
+            .map(|&c| c as f64 / total.max(1) as f64)
+            .collect();
+        egui_plot::Plot::new("tool/cipher_tool/frequency")
+            .height(200.0)
+            .include_x(-0.5)
+            .include_x(25.5)
+            .include_y(0.0)
+            .allow_scroll(false)
+            .legend(egui_plot::Legend::default())
+            .y_axis_label("%")
+            .x_axis_formatter(|mark, _| {
+                let i = mark.value.round();
+                if (0.0..26.0).contains(&i) && (mark.value - i).abs() < 1e-6 {
+                    ((b'A' + i as u8) as char).to_string()
+                } else {
+                    String::new()
+                }
+            })
+            .show(ui, |plot_ui| {
+                plot_ui.bar_chart(egui_plot::BarChart::new(bars(text, -0.2)).name("Input"));
+                plot_ui.bar_chart(
+                    egui_plot::BarChart::new(bars(analysis::ENGLISH.to_vec(), 0.2)).name("English"),
+                );
+            });
+    }
+}
+
+impl ToolComponent for CipherTool {
+    fn paint_ui(&mut self, ui: &mut egui::Ui, _ctx: &egui::Context) {
+        let width = ui.available_width();
+        ui.horizontal(|ui| {
+            let mut cursor = ui.cursor();
+            cursor.set_width(width / 2.0 - 5.0);
+            ui.allocate_new_ui(egui::UiBuilder::new().max_rect(cursor), |ui| {
+                ui.vertical(|ui| {
+                    ui.horizontal(|ui| {
+                        ui.label("Input");
+                        if ui.button("Clear").clicked() {
+                            self.input.clear();
+                        }
+                        if ui.button("Swap").clicked() {
+                            std::mem::swap(&mut self.input, &mut self.output);
+                        }
+                    });
+                    ui.add(
+                        egui::TextEdit::multiline(&mut self.input)
+                            .desired_width(f32::INFINITY)
+                            .desired_rows(8),
+                    );
+                });
+            });
+            cursor = ui.cursor();
+            cursor.set_width(width / 2.0 - 5.0);
+            ui.allocate_new_ui(egui::UiBuilder::new().max_rect(cursor), |ui| {
+                ui.vertical(|ui| {
+                    ui.horizontal(|ui| {
+                        ui.label("Output");
+                        if ui.button("Copy").clicked() {
+                            ui.output_mut(|o| {
+                                o.copied_text = self.output.clone();
+                            });
+                        }
+                    });
+                    ui.add(
+                        egui::TextEdit::multiline(&mut self.output)
+                            .desired_width(f32::INFINITY)
+                            .desired_rows(8),
+                    );
+                });
+            });
+        });
+        ui.separator();
+        ui.horizontal_wrapped(|ui| {
+            ui.radio_value(
+                &mut self.selected_catagory,
+                Catagory::Caesar,
+                "Caesar / ROT",
+            );
+            ui.radio_value(&mut self.selected_catagory, Catagory::Vigenere, "Vigenère");
+            ui.radio_value(&mut self.selected_catagory, Catagory::Atbash, "Atbash");
+            ui.radio_value(&mut self.selected_catagory, Catagory::Affine, "Affine");
+            ui.radio_value(
+                &mut self.selected_catagory,
+                Catagory::RailFence,
+                "Rail Fence",
+            );
+            ui.radio_value(&mut self.selected_catagory, Catagory::Playfair, "Playfair");
+            ui.radio_value(&mut self.selected_catagory, Catagory::Bacon, "Bacon");
+            ui.radio_value(
+                &mut self.selected_catagory,
+                Catagory::Substitution,
+                "Substitution",
+            );
+            ui.radio_value(
+                &mut self.selected_catagory,
+                Catagory::Frequency,
+                "Frequency",
+            );
+        });
+        match self.selected_catagory {
+            Catagory::Caesar => {
+                ui.horizontal(|ui| {
+                    ui.label("Shift");
+                    ui.add(egui::DragValue::new(&mut self.shift).range(0..=25));
+                    if let Some(decrypt) = Self::direction(ui) {
+                        let n = if decrypt { -self.shift } else { self.shift };
+                        self.output = cipher::shift(&self.input, n);
+                    }
+                    if ui.button("ROT13").clicked() {
+                        self.output = cipher::shift(&self.input, 13);
+                    }
+                    if ui
+                        .button("Brute Force")
+                        .on_hover_text("List every shift, the most English-like first")
+                        .clicked()
+                    {
+                        self.brute_force = analysis::brute_force(&self.input);
+                    }
+                });
+                let mut chosen = None;
+                egui::ScrollArea::vertical()
+                    .id_salt("tool/cipher_tool/brute_force")
+                    .show(ui, |ui| {
+                        egui::Grid::new("tool/cipher_tool/brute_force")
+                            .striped(true)
+                            .show(ui, |ui| {
+                                for (i, (n, score, plain)) in self.brute_force.iter().enumerate() {
+                                    if ui.small_button("Use").clicked() {
+                                        chosen = Some((*n, plain.clone()));
+                                    }
+                                    let label = format!("-{}", n);
+                                    if i == 0 {
+                                        ui.strong(label);
+                                    } else {
+                                        ui.monospace(label);
+                                    }
+                                    ui.label(format!("χ² {:.1}", score));
+                                    let preview: String = plain.chars().take(80).collect();
+                                    ui.label(preview);
+                                    ui.end_row();
+                                }
+                            });
+                    });
+                if let Some((n, plain)) = chosen {
+                    self.shift = n;
+                    self.output = plain;
+                }
+            }
+            Catagory::Vigenere => {
+                ui.horizontal(|ui| {
+                    ui.label("Key");
+                    ui.text_edit_singleline(&mut self.vigenere_key);
+                    if let Some(decrypt) = Self::direction(ui) {
+                        let result = cipher::vigenere(&self.input, &self.vigenere_key, decrypt);
+                        self.set_output(result);
+                    }
+                    if ui
+                        .button("Analyze Key Length")
+                        .on_hover_text("Kasiski examination and Friedman test on Input")
+                        .clicked()
+                    {
+                        self.key_analysis = Some(KeyAnalysis::new(&self.input));
+                    }
+                });
+                let mut chosen = None;
+                if let Some(key_analysis) = &self.key_analysis {
+                    match key_analysis.friedman {
+                        Some(len) => ui.label(format!("Friedman estimate: {:.1}", len)),
+                        None => ui.label("Friedman estimate: text looks random"),
+                    };
+                    egui::ScrollArea::vertical()
+                        .id_salt("tool/cipher_tool/key_analysis")
+                        .show(ui, |ui| {
+                            egui::Grid::new("tool/cipher_tool/key_analysis")
+                                .striped(true)
+                                .show(ui, |ui| {
+                                    ui.strong("Length");
+                                    ui.strong("Kasiski");
+                                    ui.strong("Column IC");
+                                    ui.strong("Likely Key");
+                                    ui.end_row();
+                                    for (len, hits, ic, key) in &key_analysis.lengths {
+                                        ui.label(len.to_string());
+                                        ui.label(hits.to_string());
+                                        // columns of the right length read like English
+                                        if *ic > 0.06 {
+                                            ui.colored_label(
+                                                ui.visuals().warn_fg_color,
+                                                format!("{:.4}", ic),
+                                            );
+                                        } else {
+                                            ui.label(format!("{:.4}", ic));
+                                        }
+                                        ui.horizontal(|ui| {
+                                            ui.monospace(key);
+                                            if ui.small_button("Use").clicked() {
+                                                chosen = Some(key.clone());
+                                            }
+                                        });
+                                        ui.end_row();
+                                    }
+                                });
+                        });
+                }
+                if let Some(key) = chosen {
+                    let result = cipher::vigenere(&self.input, &key, true);
+                    self.vigenere_key = key;
+                    self.set_output(result);
+                }
+            }
+            Catagory::Atbash => {
+                if ui.button("Apply").clicked() {
+                    self.output = cipher::atbash(&self.input);
+                }
+            }
+            Catagory::Affine => {
+                ui.horizontal(|ui| {
+                    ui.label("a");
+                    ui.add(egui::DragValue::new(&mut self.affine_a).range(1..=25));
+                    ui.label("b");
+                    ui.add(egui::DragValue::new(&mut self.affine_b).range(0..=25));
+                    if let Some(decrypt) = Self::direction(ui) {
+                        let result =
+                            cipher::affine(&self.input, self.affine_a, self.affine_b, decrypt);
+                        self.set_output(result);
+                    }
+                });
+            }
+            Catagory::RailFence => {
+                ui.horizontal(|ui| {
+                    ui.label("Rails");
+                    ui.add(egui::DragValue::new(&mut self.rails).range(2..=64));
+                    if let Some(decrypt) = Self::direction(ui) {
+                        let result = cipher::rail_fence(&self.input, self.rails, decrypt);
+                        self.set_output(result);
+                    }
+                });
+            }
+            Catagory::Playfair => {
+                ui.horizontal(|ui| {
+                    ui.label("Key");
+                    ui.text_edit_singleline(&mut self.playfair_key);
+                    if let Some(decrypt) = Self::direction(ui) {
+                        let result = cipher::playfair(&self.input, &self.playfair_key, decrypt);
+                        self.set_output(result);
+                    }
+                });
+                let square = cipher::playfair_square(&self.playfair_key);
+                egui::Grid::new("tool/cipher_tool/playfair").show(ui, |ui| {
+                    for row in square.chunks(5) {
+                        for &b in row {
+                            ui.monospace((b as char).to_string());
+                        }
+                        ui.end_row();
+                    }
+                });
+            }
+            Catagory::Bacon => {
+                ui.horizontal(|ui| {
+                    if ui.button("Encode").clicked() {
+                        self.output = cipher::bacon_encode(&self.input);
+                    }
+                    if ui
+                        .button("Decode")
+                        .on_hover_text("Read groups of five A/B letters")
+                        .clicked()
+                    {
+                        let result = cipher::bacon_decode(&self.input);
+                        self.set_output(result);
+                    }
+                });
+            }
+            Catagory::Substitution => {
+                ui.horizontal(|ui| {
+                    ui.label("A..Z become");
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.alphabet)
+                            .font(egui::TextStyle::Monospace)
+                            .desired_width(240.0),
+                    );
+                    if let Some(decrypt) = Self::direction(ui) {
+                        let result = cipher::substitution(&self.input, &self.alphabet, decrypt);
+                        self.set_output(result);
+                    }
+                });
+            }
+            Catagory::Frequency => {
+                if self.counted != self.input {
+                    self.counted = self.input.clone();
+                    self.counts = analysis::counts(&self.input);
+                }
+                self.frequency_chart(ui);
+            }
+        }
+    }
+    fn get_msg(&mut self) -> Option<Msg> {
+        self.msg.take()
+    }
+}
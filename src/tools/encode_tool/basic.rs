@@ -1,56 +1,489 @@
+use super::byte_tool::binascii;
+use super::encoding::{self, Decoded, Trap};
 use base64::prelude::*;
 
+/// Decoded payloads are read as UTF-8, invalid bytes handled by `trap`
+fn utf8_text(v: Vec<u8>, trap: Trap) -> Result<Decoded, String> {
+    encoding::decode(v, "UTF-8", trap).map_err(|e| {
+        format!(
+            "decoded data is not UTF-8 text ({}), try another trap or Byte Tool",
+            e
+        )
+    })
+}
+
 pub fn base64_encode_std(input: &str) -> String {
     BASE64_STANDARD.encode(input.as_bytes())
 }
-pub fn base64_decode_std(input: &str) -> String {
-    match BASE64_STANDARD.decode(input) {
-        Ok(v) => String::from_utf8(v).unwrap(),
-        Err(e) => format!("Error: {}", e),
-    }
+pub fn base64_decode_std(input: &str, trap: Trap) -> Result<Decoded, String> {
+    utf8_text(
+        BASE64_STANDARD.decode(input).map_err(|e| e.to_string())?,
+        trap,
+    )
 }
 pub fn base64_encode_url(input: &str) -> String {
     BASE64_URL_SAFE.encode(input.as_bytes())
 }
-pub fn base64_decode_url(input: &str) -> String {
-    match BASE64_URL_SAFE.decode(input) {
-        Ok(v) => String::from_utf8(v).unwrap(),
-        Err(e) => format!("Error: {}", e),
-    }
+pub fn base64_decode_url(input: &str, trap: Trap) -> Result<Decoded, String> {
+    utf8_text(
+        BASE64_URL_SAFE.decode(input).map_err(|e| e.to_string())?,
+        trap,
+    )
 }
 pub fn base64_encode_std_no_pad(input: &str) -> String {
     BASE64_STANDARD_NO_PAD.encode(input.as_bytes())
 }
-pub fn base64_decode_std_no_pad(input: &str) -> String {
-    match BASE64_STANDARD_NO_PAD.decode(input) {
-        Ok(v) => String::from_utf8(v).unwrap(),
-        Err(e) => format!("Error: {}", e),
-    }
+pub fn base64_decode_std_no_pad(input: &str, trap: Trap) -> Result<Decoded, String> {
+    utf8_text(
+        BASE64_STANDARD_NO_PAD
+            .decode(input)
+            .map_err(|e| e.to_string())?,
+        trap,
+    )
 }
 pub fn base64_encode_url_no_pad(input: &str) -> String {
     BASE64_URL_SAFE_NO_PAD.encode(input.as_bytes())
 }
-pub fn base64_decode_url_no_pad(input: &str) -> String {
-    match BASE64_URL_SAFE_NO_PAD.decode(input) {
-        Ok(v) => String::from_utf8(v).unwrap(),
-        Err(e) => format!("Error: {}", e),
-    }
+pub fn base64_decode_url_no_pad(input: &str, trap: Trap) -> Result<Decoded, String> {
+    utf8_text(
+        BASE64_URL_SAFE_NO_PAD
+            .decode(input)
+            .map_err(|e| e.to_string())?,
+        trap,
+    )
 }
 pub fn url_encode(input: &str) -> String {
     urlencoding::encode(input).into_owned()
 }
-pub fn url_decode(input: &str) -> String {
-    match urlencoding::decode(input) {
-        Ok(v) => v.into_owned(),
-        Err(e) => format!("Error: {}", e),
-    }
+pub fn url_decode(input: &str, trap: Trap) -> Result<Decoded, String> {
+    utf8_text(
+        urlencoding::decode_binary(input.as_bytes()).into_owned(),
+        trap,
+    )
 }
 pub fn hex_encode(input: &str) -> String {
     hex::encode(input)
 }
-pub fn hex_decode(input: &str) -> String {
-    match hex::decode(input) {
-        Ok(v) => String::from_utf8(v).unwrap(),
+pub fn hex_decode(input: &str, trap: Trap) -> Result<Decoded, String> {
+    utf8_text(hex::decode(input).map_err(|e| e.to_string())?, trap)
+}
+pub fn binascii_encode(input: &str, scheme: binascii::Scheme) -> String {
+    match binascii::encode(input.as_bytes(), scheme) {
+        Ok(v) => v,
         Err(e) => format!("Error: {}", e),
     }
 }
+pub fn binascii_decode(
+    input: &str,
+    scheme: binascii::Scheme,
+    trap: Trap,
+) -> Result<Decoded, String> {
+    utf8_text(binascii::decode(input, scheme)?, trap)
+}
+/// Decodes with the first scheme that fits, returning it so the selection can follow
+pub fn binascii_decode_auto(
+    input: &str,
+    trap: Trap,
+) -> (Option<binascii::Scheme>, Result<Decoded, String>) {
+    match binascii::decode_auto(input) {
+        Ok((scheme, v)) => (Some(scheme), utf8_text(v, trap)),
+        Err(e) => (None, Err(e)),
+    }
+}
+/// Escapes the characters special to HTML/XML, `numeric` also turns every non-ASCII character into `&#x..;`
+pub fn html_encode(input: &str, numeric: bool) -> String {
+    let escaped = html_escape::encode_safe(input);
+    if !numeric {
+        return escaped.into_owned();
+    }
+    escaped
+        .chars()
+        .map(|c| match c.is_ascii() {
+            true => c.to_string(),
+            false => format!("&#x{:X};", c as u32),
+        })
+        .collect()
+}
+/// Decodes named and numeric character references
+pub fn html_decode(input: &str) -> String {
+    html_escape::decode_html_entities(input).into_owned()
+}
+pub fn qp_encode(input: &str) -> String {
+    quoted_printable::encode_to_str(input.as_bytes())
+}
+pub fn qp_decode(input: &str, trap: Trap) -> Result<Decoded, String> {
+    let v = quoted_printable::decode(input, quoted_printable::ParseMode::Robust)
+        .map_err(|e| e.to_string())?;
+    utf8_text(v, trap)
+}
+/// Longest encoded text of one RFC 2047 word, keeping it within 75 characters
+const ENCODED_WORD_TEXT: usize = 75 - "=?UTF-8?B??=".len();
+/// Encodes as UTF-8 encoded words, `q` choosing Q over B encoding, splitting only between characters
+pub fn rfc2047_encode(input: &str, q: bool) -> String {
+    let encode_char = |c: char| -> String {
+        let mut buf = [0; 4];
+        let bytes = c.encode_utf8(&mut buf).as_bytes();
+        match c {
+            ' ' if q => "_".to_string(),
+            'A'..='Z' | 'a'..='z' | '0'..='9' | '!' | '*' | '+' | '-' | '/' if q => c.to_string(),
+            _ if q => bytes.iter().map(|b| format!("={:02X}", b)).collect(),
+            _ => String::new(),
+        }
+    };
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut bytes = Vec::new();
+    for c in input.chars() {
+        if q {
+            let encoded = encode_char(c);
+            if word.len() + encoded.len() > ENCODED_WORD_TEXT {
+                words.push(std::mem::take(&mut word));
+            }
+            word.push_str(&encoded);
+        } else {
+            // 3 bytes make 4 base64 characters
+            if (bytes.len() + c.len_utf8()).div_ceil(3) * 4 > ENCODED_WORD_TEXT {
+                words.push(BASE64_STANDARD.encode(&bytes));
+                bytes.clear();
+            }
+            bytes.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+        }
+    }
+    if !bytes.is_empty() {
+        word = BASE64_STANDARD.encode(&bytes);
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    let kind = if q { 'Q' } else { 'B' };
+    words
+        .iter()
+        .map(|w| format!("=?UTF-8?{}?{}?=", kind, w))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+/// Decodes the encoded words of a header value in any charset with a WHATWG label
+pub fn rfc2047_decode(input: &str, trap: Trap) -> Result<String, String> {
+    let re = regex::Regex::new(r"=\?([^?\s]+)\?([BbQq])\?([^?\s]*)\?=").unwrap();
+    let mut output = String::new();
+    let mut last = 0;
+    for caps in re.captures_iter(input) {
+        let whole = caps.get(0).unwrap();
+        let between = &input[last..whole.start()];
+        // whitespace between two encoded words is folding, not text
+        if last == 0 || !between.trim().is_empty() {
+            output.push_str(between);
+        }
+        last = whole.end();
+        // a language may follow the charset, as in "UTF-8*en"
+        let charset = caps[1].split('*').next().unwrap_or_default();
+        let text = &caps[3];
+        let bytes = if caps[2].eq_ignore_ascii_case("B") {
+            BASE64_STANDARD
+                .decode(text)
+                .map_err(|e| format!("encoded word at {}: {}", whole.start(), e))?
+        } else {
+            let mut bytes = Vec::new();
+            let mut rest = text.as_bytes();
+            while let Some((&b, tail)) = rest.split_first() {
+                rest = tail;
+                match b {
+                    b'_' => bytes.push(b' '),
+                    b'=' if rest.len() >= 2 => {
+                        let v = std::str::from_utf8(&rest[..2])
+                            .ok()
+                            .and_then(|h| u8::from_str_radix(h, 16).ok())
+                            .ok_or(format!(
+                                "invalid escape in encoded word at {}",
+                                whole.start()
+                            ))?;
+                        bytes.push(v);
+                        rest = &rest[2..];
+                    }
+                    _ => bytes.push(b),
+                }
+            }
+            bytes
+        };
+        let decoded = encoding::decode(bytes, charset, trap)
+            .map_err(|e| format!("encoded word at {}: {}", whole.start(), e))?;
+        output.push_str(&decoded.text);
+    }
+    if last == 0 {
+        return Err("no encoded words like =?UTF-8?B?...?= found".to_string());
+    }
+    output.push_str(&input[last..]);
+    Ok(output)
+}
+/// Runs `f` on every non-empty line, so a list of domains converts at once
+fn per_line(input: &str, f: impl Fn(&str) -> Result<String, String>) -> String {
+    input
+        .lines()
+        .map(|line| match line.trim() {
+            "" => String::new(),
+            line => f(line).unwrap_or_else(|e| format!("Error: {}", e)),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+pub fn punycode_encode(input: &str) -> String {
+    per_line(input, |s| {
+        idna::punycode::encode_str(s).ok_or("cannot encode as punycode".to_string())
+    })
+}
+pub fn punycode_decode(input: &str) -> String {
+    per_line(input, |s| {
+        idna::punycode::decode_to_string(s).ok_or("invalid punycode".to_string())
+    })
+}
+pub fn idna_to_ascii(input: &str) -> String {
+    per_line(input, |s| {
+        idna::domain_to_ascii(s).map_err(|e| e.to_string())
+    })
+}
+pub fn idna_to_unicode(input: &str) -> String {
+    per_line(input, |s| match idna::domain_to_unicode(s) {
+        (v, Ok(())) => Ok(v),
+        (_, Err(e)) => Err(e.to_string()),
+    })
+}
+pub fn rot13(input: &str) -> String {
+    input
+        .chars()
+        .map(|c| match c {
+            'a'..='z' => ((c as u8 - b'a' + 13) % 26 + b'a') as char,
+            'A'..='Z' => ((c as u8 - b'A' + 13) % 26 + b'A') as char,
+            _ => c,
+        })
+        .collect()
+}
+/// Rotates every printable ASCII character but space by 47
+pub fn rot47(input: &str) -> String {
+    input
+        .chars()
+        .map(|c| match c {
+            '!'..='~' => ((c as u8 - b'!' + 47) % 94 + b'!') as char,
+            _ => c,
+        })
+        .collect()
+}
+const MORSE: [(char, &str); 54] = [
+    ('A', ".-"),
+    ('B', "-..."),
+    ('C', "-.-."),
+    ('D', "-.."),
+    ('E', "."),
+    ('F', "..-."),
+    ('G', "--."),
+    ('H', "...."),
+    ('I', ".."),
+    ('J', ".---"),
+    ('K', "-.-"),
+    ('L', ".-.."),
+    ('M', "--"),
+    ('N', "-."),
+    ('O', "---"),
+    ('P', ".--."),
+    ('Q', "--.-"),
+    ('R', ".-."),
+    ('S', "..."),
+    ('T', "-"),
+    ('U', "..-"),
+    ('V', "...-"),
+    ('W', ".--"),
+    ('X', "-..-"),
+    ('Y', "-.--"),
+    ('Z', "--.."),
+    ('0', "-----"),
+    ('1', ".----"),
+    ('2', "..---"),
+    ('3', "...--"),
+    ('4', "....-"),
+    ('5', "....."),
+    ('6', "-...."),
+    ('7', "--..."),
+    ('8', "---.."),
+    ('9', "----."),
+    ('.', ".-.-.-"),
+    (',', "--..--"),
+    ('?', "..--.."),
+    ('\'', ".----."),
+    ('!', "-.-.--"),
+    ('/', "-..-."),
+    ('(', "-.--."),
+    (')', "-.--.-"),
+    ('&', ".-..."),
+    (':', "---..."),
+    (';', "-.-.-."),
+    ('=', "-...-"),
+    ('+', ".-.-."),
+    ('-', "-....-"),
+    ('_', "..--.-"),
+    ('"', ".-..-."),
+    ('$', "...-..-"),
+    ('@', ".--.-."),
+];
+/// Letters are separated by spaces and words by " / "
+pub fn morse_encode(input: &str) -> String {
+    let mut words = Vec::new();
+    for word in input.split_whitespace() {
+        let mut letters = Vec::new();
+        for c in word.chars() {
+            let upper = c.to_ascii_uppercase();
+            match MORSE.iter().find(|(l, _)| *l == upper) {
+                Some((_, code)) => letters.push(*code),
+                None => return format!("Error: no Morse code for '{}'", c),
+            }
+        }
+        words.push(letters.join(" "));
+    }
+    words.join(" / ")
+}
+pub fn morse_decode(input: &str) -> String {
+    let mut output = String::new();
+    for (i, word) in input.split('/').enumerate() {
+        if i > 0 {
+            output.push(' ');
+        }
+        for code in word.split_whitespace() {
+            match MORSE.iter().find(|(_, c)| *c == code) {
+                Some((l, _)) => output.push(*l),
+                None => return format!("Error: unknown Morse code {}", code),
+            }
+        }
+    }
+    output
+}
+/// Writes the UTF-8 bytes of Input as space separated numbers in base 2, 8 or 10
+pub fn radix_encode(input: &str, radix: u32) -> String {
+    input
+        .bytes()
+        .map(|b| match radix {
+            2 => format!("{:08b}", b),
+            8 => format!("{:03o}", b),
+            _ => b.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+/// Reads bytes separated by whitespace or commas, unseparated binary is read 8 digits at a time
+pub fn radix_decode(input: &str, radix: u32, trap: Trap) -> Result<Decoded, String> {
+    let mut bytes = Vec::new();
+    let tokens = input.split(|c: char| c.is_whitespace() || c == ',');
+    for (i, token) in tokens.filter(|t| !t.is_empty()).enumerate() {
+        // byte slicing below needs ASCII; anything else fails to parse as a whole token
+        let chunks: Vec<&str> =
+            if radix == 2 && token.is_ascii() && token.len() > 8 && token.len() % 8 == 0 {
+                (0..token.len())
+                    .step_by(8)
+                    .map(|j| &token[j..j + 8])
+                    .collect()
+            } else {
+                vec![token]
+            };
+        for chunk in chunks {
+            let b = u8::from_str_radix(chunk, radix)
+                .map_err(|e| format!("number {} \"{}\": {}", i + 1, chunk, e))?;
+            bytes.push(b);
+        }
+    }
+    utf8_text(bytes, trap)
+}
+/// `application/x-www-form-urlencoded`, spaces become `+`
+pub fn form_encode(input: &str) -> String {
+    form_urlencoded::byte_serialize(input.as_bytes()).collect()
+}
+pub fn form_decode(input: &str, trap: Trap) -> Result<Decoded, String> {
+    let plus_as_space = input.replace('+', " ");
+    utf8_text(
+        urlencoding::decode_binary(plus_as_space.as_bytes()).into_owned(),
+        trap,
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn text(r: Result<Decoded, String>) -> String {
+        r.unwrap().text
+    }
+
+    #[test]
+    fn test_web() {
+        assert_eq!(
+            html_encode("<a href=\"x\">é</a>", false),
+            "&lt;a href=&quot;x&quot;&gt;é&lt;&#x2F;a&gt;"
+        );
+        assert_eq!(html_encode("é&", true), "&#xE9;&amp;");
+        assert_eq!(
+            html_decode("&lt;&eacute;&#233;&#xE9;&nbsp;&amp;"),
+            "<ééé\u{A0}&"
+        );
+        assert_eq!(qp_encode("café = 1"), "caf=C3=A9 =3D 1");
+        assert_eq!(
+            text(qp_decode("caf=C3=A9 =\r\nok", Trap::Strict)),
+            "café ok"
+        );
+        assert_eq!(form_encode("a b&c=é*"), "a+b%26c%3D%C3%A9*");
+        assert_eq!(
+            text(form_decode("a+b%26c%3D%C3%A9%2B", Trap::Strict)),
+            "a b&c=é+"
+        );
+        assert!(form_decode("%FF", Trap::Strict).is_err());
+    }
+
+    #[test]
+    fn test_rfc2047() {
+        let long = "Grüße aus Köln, ".repeat(6);
+        for q in [false, true] {
+            let encoded = rfc2047_encode(&long, q);
+            assert!(encoded.split(' ').all(|w| w.len() <= 75), "{}", encoded);
+            assert_eq!(rfc2047_decode(&encoded, Trap::Strict).unwrap(), long);
+        }
+        assert_eq!(
+            rfc2047_decode(
+                "Subject: =?ISO-8859-1?Q?Caf=E9_au?= =?utf-8?b?bGFpdA==?= !",
+                Trap::Strict
+            )
+            .unwrap(),
+            "Subject: Café aulait !"
+        );
+        assert_eq!(
+            rfc2047_decode("=?gb2312?B?1tDOxA==?=", Trap::Strict).unwrap(),
+            "中文"
+        );
+        assert!(rfc2047_decode("plain", Trap::Strict).is_err());
+    }
+
+    #[test]
+    fn test_idna() {
+        assert_eq!(punycode_encode("bücher"), "bcher-kva");
+        assert_eq!(punycode_decode("bcher-kva"), "bücher");
+        assert_eq!(
+            idna_to_ascii("Bücher.example\n\n中文.com"),
+            "xn--bcher-kva.example\n\nxn--fiq228c.com"
+        );
+        assert_eq!(idna_to_unicode("xn--bcher-kva.example"), "bücher.example");
+    }
+
+    #[test]
+    fn test_text() {
+        assert_eq!(rot13("Hello, World!"), "Uryyb, Jbeyq!");
+        assert_eq!(rot13(&rot13("abcXYZ")), "abcXYZ");
+        assert_eq!(rot47("Hello, World!"), "w6==@[ (@C=5P");
+        assert_eq!(morse_encode("SOS Help"), "... --- ... / .... . .-.. .--.");
+        assert_eq!(morse_decode("... --- ... / .... . .-.. .--."), "SOS HELP");
+        assert!(morse_encode("ü").starts_with("Error"));
+        assert_eq!(radix_encode("Hi", 2), "01001000 01101001");
+        assert_eq!(radix_encode("Hi", 8), "110 151");
+        assert_eq!(radix_encode("Hi", 10), "72 105");
+        assert_eq!(
+            text(radix_decode("0100100001101001", 2, Trap::Strict)),
+            "Hi"
+        );
+        assert_eq!(text(radix_decode("72, 105", 10, Trap::Strict)), "Hi");
+        assert!(radix_decode("72 256", 10, Trap::Strict).is_err());
+        assert!(radix_decode("0000000é0000000", 2, Trap::Strict).is_err());
+    }
+}
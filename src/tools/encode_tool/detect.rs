@@ -0,0 +1,314 @@
+use encoding::all::*;
+use encoding::{DecoderTrap, EncodingRef};
+
+#[derive(PartialEq, Clone, Copy)]
+enum Lang {
+    /// UTF-8 carries no language and is rarely valid by accident
+    Unicode,
+    /// Any byte pair is a code unit, so only common characters count
+    Utf16,
+    Simplified,
+    Traditional,
+    Japanese,
+    Korean,
+    Latin,
+    CentralEuropean,
+    Cyrillic,
+}
+
+const CHARSETS: [(&str, EncodingRef, Lang); 12] = [
+    ("UTF-8", UTF_8, Lang::Unicode),
+    ("UTF-16LE", UTF_16LE, Lang::Utf16),
+    ("UTF-16BE", UTF_16BE, Lang::Utf16),
+    ("GBK / GB18030", GB18030, Lang::Simplified),
+    ("Big5", BIG5_2003, Lang::Traditional),
+    ("Shift_JIS", WINDOWS_31J, Lang::Japanese),
+    ("EUC-JP", EUC_JP, Lang::Japanese),
+    ("EUC-KR", WINDOWS_949, Lang::Korean),
+    ("windows-1252", WINDOWS_1252, Lang::Latin),
+    ("ISO-8859-2", ISO_8859_2, Lang::CentralEuropean),
+    ("windows-1251", WINDOWS_1251, Lang::Cyrillic),
+    ("KOI8-R", KOI8_R, Lang::Cyrillic),
+];
+
+/// The most frequent hanzi in simplified Chinese text
+const COMMON_SIMPLIFIED: &str = "的一是不了人我在有他这中大来上国个到说们为子和你地出道也时年得就那要下以生会自着去之过家学对可她里后小么心多天而能好都然没日于起还发成事只作当想看文无开手十用主行方又如前所本见经头面公同三已老从动两长知民样现分将外但身些与高意进把法此实回二理美点月明其种声全工己话儿者向情部正名定女问力机给等几很业最间新什打便位因重被走电四第门相次东政海口使教西再平真听世气信北少关并内加化由却代军产入先山五太水万市眼体别处总才场师书比住员九笑性通目华报立马命张活难神数件安表原车白应路期叫死常提感金何更反合放做系计或司利受光王果亲界及今京务制解各任至清物台象记边共风战干接它许八特觉望直服毛林题建南度统色字请交爱让认算论百吃义科怎元社术结六功指思非流每青管夫连远资队跟带花快条院变联言权往展该领传近留红治决周保达办运武半候七必城父强步完革深区即求品士转量空甚众技轻程告江语英基派满式李息写呢识极令黄德收脸钱党倒未持取设始版双历越史商千片容研像找友孩站广改议形委早房音火际则首单据导影失拿网香似斯专石若兵弟谁校读志飞观争究包组造落视济喜离虽坏兴妈";
+
+/// The same characters in traditional script
+const COMMON_TRADITIONAL: &str = "的一是不了人我在有他這中大來上國個到說們為子和你地出道也時年得就那要下以生會自著去之過家學對可她裡後小麼心多天而能好都然沒日於起還發成事只作當想看文無開手十用主行方又如前所本見經頭面公同三已老從動兩長知民樣現分將外但身些與高意進把法此實回二理美點月明其種聲全工己話兒者向情部正名定女問力機給等幾很業最間新什打便位因重被走電四第門相次東政海口使教西再平真聽世氣信北少關並內加化由卻代軍產入先山五太水萬市眼體別處總才場師書比住員九笑性通目華報立馬命張活難神數件安表原車白應路期叫死常提感金何更反合放做系計或司利受光王果親界及今京務制解各任至清物台象記邊共風戰乾接它許八特覺望直服毛林題建南度統色字請交愛讓認算論百吃義科怎元社術結六功指思非流每青管夫連遠資隊跟帶花快條院變聯言權往展該領傳近留紅治決周保達辦運武半候七必城父強步完革深區即求品士轉量空甚眾技輕程告江語英基派滿式李息寫呢識極令黃德收臉錢黨倒未持取設始版雙歷越史商千片容研像找友孩站廣改議形委早房音火際則首單據導影失拿網香似斯專石若兵弟誰校讀志飛觀爭究包組造落視濟喜離雖壞興媽";
+
+/// Frequent kanji in Japanese text
+const COMMON_KANJI: &str = "日本人年大一中出上生時行見月分後前間事自社発者地業方新場員立開手力問代明動京目通言理体田主題意不作用度強公持野以思家世多正安院心界教文元重近考画海売知道集別物使品計死特私始朝運終台広住無真有口少町料工建空急止送切転研足究楽起着店病質待試族銀早映親験英医仕去味写字答夜音注帰古歌買悪図週室歩風紙黒花春赤青館屋色走秋夏習駅洋旅服夕借曜飲肉貸堂鳥飯勉冬昼茶弟牛魚兄犬妹姉漢気子会国入小何来今先学電話語";
+
+/// Frequent hangul syllables in Korean text
+const COMMON_HANGUL: &str = "이다는의에을하고를가한지서기사로어도으리인자수들대시그나아있게해정적일라보요면것만부제주전위상소국성여과원내까용무동장경신간문중관연우화없세오말생저했습니때와같또더안못할수록했던많은오늘날씨좋친구함께공원산책갔학교회사람우리너무정말어떻게그래서하지만그리고된다되어알았모든시간생각마음사랑집밖물음식먹었";
+
+const COMMON_LATIN: &str = "éèàçêâôûùîïëüöäßñóíáúÉÀÇ";
+
+const COMMON_CENTRAL: &str = "ąćęłńóśźżčďěňřšťůžőűáéíýŁŚŻŠŽČ";
+
+const COMMON_CYRILLIC: &str = "оеаинтсрвлкмдпуяыьгзбчйхжшюцщэфъё";
+
+/// Bytes sampled for detection; decoding always covers the whole input
+const SAMPLE: usize = 256 * 1024;
+
+#[derive(PartialEq, Eq, Hash, Clone)]
+pub struct Guess {
+    pub name: &'static str,
+    /// Confidence in percent
+    pub score: u32,
+    /// Undecodable sequences in the sample
+    pub errors: usize,
+    pub bom: bool,
+    pub preview: String,
+}
+
+fn weight(chars: &[char], i: usize, lang: Lang) -> Option<f32> {
+    let c = chars[i];
+    let prev = i.checked_sub(1).map(|i| chars[i]);
+    let next = chars.get(i + 1).copied();
+    let near_ascii_letter = prev.is_some_and(|c| c.is_ascii_alphabetic())
+        || next.is_some_and(|c| c.is_ascii_alphabetic());
+    let in_set = |set: &str| set.contains(c);
+    Some(match c {
+        '\t' | '\n' | '\r' | ' '..='~' => return None,
+        '\u{FFFD}' => 0.0,
+        c if c.is_control() => 0.0,
+        '\u{3040}'..='\u{30FF}' => match lang {
+            Lang::Japanese | Lang::Unicode | Lang::Utf16 => 1.0,
+            _ => 0.4,
+        },
+        '\u{AC00}'..='\u{D7A3}' => match lang {
+            Lang::Unicode => 1.0,
+            Lang::Korean | Lang::Utf16 if in_set(COMMON_HANGUL) => 1.0,
+            Lang::Korean => 0.5,
+            _ => 0.2,
+        },
+        '\u{4E00}'..='\u{9FFF}' => {
+            let own = match lang {
+                Lang::Simplified => in_set(COMMON_SIMPLIFIED),
+                Lang::Traditional => in_set(COMMON_TRADITIONAL),
+                Lang::Japanese => in_set(COMMON_KANJI),
+                _ => false,
+            };
+            let any =
+                in_set(COMMON_SIMPLIFIED) || in_set(COMMON_TRADITIONAL) || in_set(COMMON_KANJI);
+            match (own, any, lang) {
+                (true, _, _) | (_, true, Lang::Unicode | Lang::Utf16) => 1.0,
+                (_, false, Lang::Unicode) => 0.5,
+                (_, false, Lang::Utf16) => 0.2,
+                (_, true, _) => 0.6,
+                _ => 0.35,
+            }
+        }
+        '\u{3000}'..='\u{303F}' | '\u{FF01}'..='\u{FF5E}' => 0.9,
+        // half-width katakana, rare even in Japanese but what stray Shift_JIS bytes decode to
+        '\u{FF61}'..='\u{FF9F}' => match lang {
+            Lang::Japanese => 0.3,
+            _ => 0.1,
+        },
+        '\u{00C0}'..='\u{017F}' if c != '×' && c != '÷' => {
+            let common = match lang {
+                Lang::Latin => in_set(COMMON_LATIN),
+                Lang::CentralEuropean => in_set(COMMON_CENTRAL),
+                Lang::Unicode | Lang::Utf16 => true,
+                _ => false,
+            };
+            // accented letters sit inside words, not in runs of their own
+            match (lang, common, near_ascii_letter) {
+                (Lang::Latin | Lang::CentralEuropean | Lang::Unicode | Lang::Utf16, true, true) => {
+                    0.9
+                }
+                (Lang::Latin | Lang::CentralEuropean, false, true) => 0.6,
+                (Lang::Latin | Lang::CentralEuropean | Lang::Unicode | Lang::Utf16, _, false) => {
+                    0.4
+                }
+                _ => 0.3,
+            }
+        }
+        // Cyrillic words do not mix with Latin letters
+        '\u{0400}'..='\u{04FF}' if near_ascii_letter => 0.2,
+        '\u{0400}'..='\u{04FF}' => match lang {
+            Lang::Cyrillic | Lang::Unicode | Lang::Utf16 if COMMON_CYRILLIC.contains(c) => 0.9,
+            Lang::Cyrillic | Lang::Unicode | Lang::Utf16 if c.is_lowercase() => 0.6,
+            Lang::Cyrillic | Lang::Unicode | Lang::Utf16
+                if next.is_some_and(|n| n.is_lowercase()) || prev.is_none_or(|p| p == ' ') =>
+            {
+                0.6
+            }
+            _ => 0.3,
+        },
+        '\u{2010}'..='\u{2027}' | '\u{2030}'..='\u{205E}' => 0.7,
+        '\u{00A0}'..='\u{00BF}' | '\u{20A0}'..='\u{20CF}' => 0.4,
+        c if lang == Lang::Unicode && c.is_alphabetic() => 0.6,
+        _ => 0.05,
+    })
+}
+
+/// Mean weight of the non-ASCII characters, None for plain ASCII text
+fn plausibility(text: &str, lang: Lang) -> Option<f32> {
+    let chars: Vec<char> = text.chars().collect();
+    let weights: Vec<f32> = (0..chars.len())
+        .filter_map(|i| weight(&chars, i, lang))
+        .collect();
+    (!weights.is_empty()).then(|| weights.iter().sum::<f32>() / weights.len() as f32)
+}
+
+fn bom(bytes: &[u8]) -> Option<(&'static str, usize)> {
+    if bytes.starts_with(&[0xEF, 0xBB, 0xBF]) {
+        Some(("UTF-8", 3))
+    } else if bytes.starts_with(&[0xFF, 0xFE]) {
+        Some(("UTF-16LE", 2))
+    } else if bytes.starts_with(&[0xFE, 0xFF]) {
+        Some(("UTF-16BE", 2))
+    } else {
+        None
+    }
+}
+
+fn preview(text: &str) -> String {
+    text.chars()
+        .take(120)
+        .map(|c| if c.is_control() { ' ' } else { c })
+        .collect()
+}
+
+/// Ranks the charsets by how likely they produced `bytes`, best first.
+///
+/// A BOM settles it; otherwise every charset decodes a sample and is scored by
+/// its undecodable sequences and how common the resulting characters are in its language.
+pub fn detect(bytes: &[u8]) -> Vec<Guess> {
+    let sample = &bytes[..bytes.len().min(SAMPLE)];
+    let truncated = sample.len() < bytes.len();
+    let bom = bom(sample);
+    let mut guesses: Vec<Guess> = CHARSETS
+        .iter()
+        .map(|(name, encoding, lang)| {
+            let has_bom = bom.is_some_and(|(b, _)| b == *name);
+            let skip = if has_bom {
+                bom.map_or(0, |(_, len)| len)
+            } else {
+                0
+            };
+            let text = encoding
+                .decode(&sample[skip..], DecoderTrap::Replace)
+                .unwrap_or_default();
+            let mut errors = text.matches('\u{FFFD}').count();
+            // a multi-byte character cut off by the sample end is not an error
+            if truncated && errors > 0 && text.ends_with('\u{FFFD}') {
+                errors -= 1;
+            }
+            let score = if has_bom {
+                1.0
+            } else {
+                let plausible = plausibility(&text, *lang);
+                let mut score = match (plausible, lang) {
+                    (None, _) => 1.0,
+                    // multi-byte UTF-8 is rarely valid by accident
+                    (Some(p), Lang::Unicode) if *name == "UTF-8" && errors == 0 => 0.98 + 0.02 * p,
+                    (Some(p), Lang::Latin | Lang::CentralEuropean | Lang::Cyrillic) => 0.9 * p,
+                    (Some(p), _) => p,
+                };
+                if errors > 0 {
+                    score *= 0.9;
+                }
+                score
+            };
+            Guess {
+                name,
+                score: (score * 100.0).round() as u32,
+                errors,
+                bom: has_bom,
+                preview: preview(&text),
+            }
+        })
+        .collect();
+    guesses.sort_by_key(|g| std::cmp::Reverse(g.score));
+    guesses
+}
+
+/// Decodes all of `bytes` with a charset from `detect`, dropping its BOM.
+/// Returns the text and the number of sequences replaced by U+FFFD.
+pub fn decode(bytes: &[u8], name: &str) -> Result<(String, usize), String> {
+    let (_, encoding, _) = CHARSETS
+        .iter()
+        .find(|(n, _, _)| *n == name)
+        .ok_or(format!("Unknown charset {}", name))?;
+    let skip = match bom(bytes) {
+        Some((b, len)) if b == name => len,
+        _ => 0,
+    };
+    let text = encoding.decode(&bytes[skip..], DecoderTrap::Replace)?;
+    let errors = text.matches('\u{FFFD}').count();
+    Ok((text, errors))
+}
+
+#[cfg(test)]
+mod test {
+    use encoding::all::*;
+    use encoding::{EncoderTrap, Encoding};
+
+    fn top(bytes: &[u8]) -> &'static str {
+        super::detect(bytes)[0].name
+    }
+
+    #[test]
+    fn test_detect() {
+        let chinese = "我们在这个问题上的意见是一致的，所以今天就不再讨论了。";
+        let traditional = "我們在這個問題上的意見是一致的，所以今天就不再討論了。";
+        let japanese = "今日は天気がいいので、友達と公園へ散歩に行きました。";
+        let korean = "오늘은 날씨가 좋아서 친구와 함께 공원에 산책을 갔습니다.";
+        let russian = "Сегодня хорошая погода, поэтому мы пошли гулять в парк.";
+        let french = "Le garçon a mangé une pêche à la crème près du château.";
+        assert_eq!(top(chinese.as_bytes()), "UTF-8");
+        assert_eq!(
+            top(&GBK.encode(chinese, EncoderTrap::Strict).unwrap()),
+            "GBK / GB18030"
+        );
+        assert_eq!(
+            top(&BIG5_2003.encode(traditional, EncoderTrap::Strict).unwrap()),
+            "Big5"
+        );
+        assert_eq!(
+            top(&WINDOWS_31J.encode(japanese, EncoderTrap::Strict).unwrap()),
+            "Shift_JIS"
+        );
+        assert_eq!(
+            top(&EUC_JP.encode(japanese, EncoderTrap::Strict).unwrap()),
+            "EUC-JP"
+        );
+        assert_eq!(
+            top(&WINDOWS_949.encode(korean, EncoderTrap::Strict).unwrap()),
+            "EUC-KR"
+        );
+        assert_eq!(
+            top(&WINDOWS_1251.encode(russian, EncoderTrap::Strict).unwrap()),
+            "windows-1251"
+        );
+        assert_eq!(
+            top(&KOI8_R.encode(russian, EncoderTrap::Strict).unwrap()),
+            "KOI8-R"
+        );
+        assert_eq!(
+            top(&WINDOWS_1252.encode(french, EncoderTrap::Strict).unwrap()),
+            "windows-1252"
+        );
+        assert_eq!(
+            top(&UTF_16LE.encode(french, EncoderTrap::Strict).unwrap()),
+            "UTF-16LE"
+        );
+        assert_eq!(
+            top(&UTF_16BE.encode(chinese, EncoderTrap::Strict).unwrap()),
+            "UTF-16BE"
+        );
+
+        let mut with_bom = vec![0xFF, 0xFE];
+        with_bom.extend(UTF_16LE.encode("hi", EncoderTrap::Strict).unwrap());
+        let guess = &super::detect(&with_bom)[0];
+        assert!(guess.name == "UTF-16LE" && guess.bom && guess.score == 100);
+        assert_eq!(
+            super::decode(&with_bom, "UTF-16LE").unwrap(),
+            ("hi".to_string(), 0)
+        );
+    }
+}
@@ -1,108 +1,311 @@
-use encoding::all::*;
-use encoding::{DecoderTrap, EncoderTrap, Encoding};
+use encoding::label::encoding_from_whatwg_label;
+use encoding::{EncoderTrap, EncodingRef};
+use std::ops::Range;
 
-pub fn utf8_to_hex(input: &str) -> String {
-    match UTF_8.encode(input, EncoderTrap::Strict) {
-        Ok(v) => hex::encode(v),
-        Err(e) => format!("Error: {}", e),
-    }
+/// Every encoding of the WHATWG Encoding Standard by its canonical name,
+/// leaving out "replacement", which only exists to refuse decoding
+pub const WHATWG: [&str; 39] = [
+    "UTF-8",
+    "UTF-16LE",
+    "UTF-16BE",
+    "IBM866",
+    "ISO-8859-2",
+    "ISO-8859-3",
+    "ISO-8859-4",
+    "ISO-8859-5",
+    "ISO-8859-6",
+    "ISO-8859-7",
+    "ISO-8859-8",
+    "ISO-8859-8-I",
+    "ISO-8859-10",
+    "ISO-8859-13",
+    "ISO-8859-14",
+    "ISO-8859-15",
+    "ISO-8859-16",
+    "KOI8-R",
+    "KOI8-U",
+    "macintosh",
+    "windows-874",
+    "windows-1250",
+    "windows-1251",
+    "windows-1252",
+    "windows-1253",
+    "windows-1254",
+    "windows-1255",
+    "windows-1256",
+    "windows-1257",
+    "windows-1258",
+    "x-mac-cyrillic",
+    "GBK",
+    "gb18030",
+    "Big5",
+    "EUC-JP",
+    "ISO-2022-JP",
+    "Shift_JIS",
+    "EUC-KR",
+    "x-user-defined",
+];
+
+/// Strict 7-bit ASCII, which is not in the standard; its "ascii" label means windows-1252
+pub const ASCII: &str = "ASCII";
+
+/// ASCII followed by the WHATWG encodings
+pub fn names() -> impl Iterator<Item = &'static str> {
+    std::iter::once(ASCII).chain(WHATWG)
 }
-pub fn hex_to_utf8(input: &str) -> String {
-    match hex::decode(input) {
-        Ok(v) => match UTF_8.decode(&v, DecoderTrap::Strict) {
-            Ok(v) => v,
-            Err(e) => format!("Error: {}", e),
-        },
-        Err(e) => format!("Error: {}", e),
+
+fn lookup(name: &str) -> Result<EncodingRef, String> {
+    if name == ASCII {
+        return Ok(encoding::all::ASCII);
     }
+    encoding_from_whatwg_label(name).ok_or(format!("Unknown encoding {}", name))
 }
-pub fn utf16le_to_hex(input: &str) -> String {
-    match UTF_16LE.encode(input, EncoderTrap::Strict) {
-        Ok(v) => hex::encode(v),
-        Err(e) => format!("Error: {}", e),
-    }
+
+/// Encodings whose name contains `filter`, plus the one `filter` is a WHATWG label of,
+/// so "latin1" finds windows-1252 and "sjis" finds Shift_JIS
+pub fn search(filter: &str) -> Vec<&'static str> {
+    let filter = filter.trim().to_lowercase();
+    let labelled = encoding_from_whatwg_label(&filter).and_then(|e| e.whatwg_name());
+    names()
+        .filter(|name| {
+            name.to_lowercase().contains(&filter)
+                || labelled.is_some_and(|l| l.eq_ignore_ascii_case(name))
+        })
+        .collect()
 }
-pub fn hex_to_utf16le(input: &str) -> String {
-    match hex::decode(input) {
-        Ok(v) => match UTF_16LE.decode(&v, DecoderTrap::Strict) {
-            Ok(v) => v,
-            Err(e) => format!("Error: {}", e),
-        },
-        Err(e) => format!("Error: {}", e),
+
+pub fn bom(name: &str) -> Option<&'static [u8]> {
+    match name {
+        "UTF-8" => Some(&[0xEF, 0xBB, 0xBF]),
+        "UTF-16LE" => Some(&[0xFF, 0xFE]),
+        "UTF-16BE" => Some(&[0xFE, 0xFF]),
+        _ => None,
     }
 }
-pub fn utf16be_to_hex(input: &str) -> String {
-    match UTF_16BE.encode(input, EncoderTrap::Strict) {
-        Ok(v) => hex::encode(v),
-        Err(e) => format!("Error: {}", e),
-    }
+
+pub fn encode(input: &str, name: &str, with_bom: bool) -> Result<Vec<u8>, String> {
+    let mut bytes = match bom(name) {
+        Some(bom) if with_bom => bom.to_vec(),
+        _ => Vec::new(),
+    };
+    lookup(name)?
+        .encode_to(input, EncoderTrap::Strict, &mut bytes)
+        .map_err(|e| e.to_string())?;
+    Ok(bytes)
 }
-pub fn hex_to_utf16be(input: &str) -> String {
-    match hex::decode(input) {
-        Ok(v) => match UTF_16BE.decode(&v, DecoderTrap::Strict) {
-            Ok(v) => v,
-            Err(e) => format!("Error: {}", e),
-        },
-        Err(e) => format!("Error: {}", e),
+
+/// Encodes `input`, writing `?` for characters the encoding cannot represent
+/// and returning their byte ranges in `input`
+pub fn encode_lossy(input: &str, name: &str) -> Result<(Vec<u8>, Vec<Range<usize>>), String> {
+    let mut encoder = lookup(name)?.raw_encoder();
+    let mut bytes = Vec::new();
+    let mut unmappable = Vec::new();
+    let mut remaining = 0;
+    while remaining < input.len() {
+        let (offset, err) = encoder.raw_feed(&input[remaining..], &mut bytes);
+        let Some(err) = err else {
+            break;
+        };
+        let end = remaining + err.upto as usize;
+        unmappable.push(remaining + offset..end);
+        bytes.push(b'?');
+        remaining = end;
     }
-}
-pub fn gbk_to_hex(input: &str) -> String {
-    match GBK.encode(input, EncoderTrap::Strict) {
-        Ok(v) => hex::encode(v),
-        Err(e) => format!("Error: {}", e),
+    if let Some(err) = encoder.raw_finish(&mut bytes) {
+        return Err(err.cause.to_string());
     }
+    Ok((bytes, unmappable))
 }
-pub fn hex_to_gbk(input: &str) -> String {
-    match hex::decode(input) {
-        Ok(v) => match GBK.decode(&v, DecoderTrap::Strict) {
-            Ok(v) => v,
-            Err(e) => format!("Error: {}", e),
-        },
-        Err(e) => format!("Error: {}", e),
-    }
+
+/// What to do with bytes that are not valid in the encoding
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum Trap {
+    Strict,
+    Replace,
+    Ignore,
+    Escape,
 }
-pub fn shiftjis_to_hex(input: &str) -> String {
-    match EUC_JP.encode(input, EncoderTrap::Strict) {
-        Ok(v) => hex::encode(v),
-        Err(e) => format!("Error: {}", e),
+
+pub const TRAPS: [Trap; 4] = [Trap::Strict, Trap::Replace, Trap::Ignore, Trap::Escape];
+
+impl Trap {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Trap::Strict => "Strict",
+            Trap::Replace => "Replace with U+FFFD",
+            Trap::Ignore => "Ignore",
+            Trap::Escape => "Escape as \\xNN",
+        }
     }
 }
-pub fn hex_to_shiftjis(input: &str) -> String {
-    match hex::decode(input) {
-        Ok(v) => match EUC_JP.decode(&v, DecoderTrap::Strict) {
-            Ok(v) => v,
-            Err(e) => format!("Error: {}", e),
-        },
-        Err(e) => format!("Error: {}", e),
-    }
+
+/// Decoded text together with the input bytes and the ranges of them that were invalid
+#[derive(PartialEq, Eq, Hash, Clone, Debug)]
+pub struct Decoded {
+    pub bytes: Vec<u8>,
+    pub text: String,
+    pub invalid: Vec<Range<usize>>,
 }
-pub fn big5_to_hex(input: &str) -> String {
-    match BIG5_2003.encode(input, EncoderTrap::Strict) {
-        Ok(v) => hex::encode(v),
-        Err(e) => format!("Error: {}", e),
-    }
+
+pub fn decode(bytes: Vec<u8>, name: &str, trap: Trap) -> Result<Decoded, String> {
+    decode_from(bytes, 0, name, trap)
 }
-pub fn hex_to_big5(input: &str) -> String {
-    match hex::decode(input) {
-        Ok(v) => match BIG5_2003.decode(&v, DecoderTrap::Strict) {
-            Ok(v) => v,
-            Err(e) => format!("Error: {}", e),
-        },
-        Err(e) => format!("Error: {}", e),
+
+/// Decodes `bytes[skip..]`, keeping offsets relative to the whole of `bytes`.
+/// This is `Encoding::decode_to` with the trap inlined so the failing ranges are kept.
+fn decode_from(bytes: Vec<u8>, skip: usize, name: &str, trap: Trap) -> Result<Decoded, String> {
+    let mut decoder = lookup(name)?.raw_decoder();
+    let mut text = String::new();
+    let mut invalid = Vec::new();
+    let mut remaining = skip;
+    loop {
+        let (offset, err) = decoder.raw_feed(&bytes[remaining..], &mut text);
+        let unprocessed = remaining + offset;
+        let err = match err {
+            Some(err) => {
+                remaining = (remaining as isize + err.upto) as usize;
+                err
+            }
+            None => {
+                remaining = bytes.len();
+                match decoder.raw_finish(&mut text) {
+                    Some(err) => {
+                        remaining = (remaining as isize + err.upto) as usize;
+                        err
+                    }
+                    None => break,
+                }
+            }
+        };
+        let range = unprocessed..remaining;
+        match trap {
+            Trap::Strict => {
+                return Err(format!("{} at offset {}", err.cause, unprocessed));
+            }
+            Trap::Replace => text.push('\u{FFFD}'),
+            Trap::Ignore => {}
+            Trap::Escape => {
+                for b in &bytes[range.clone()] {
+                    text.push_str(&format!("\\x{:02X}", b));
+                }
+            }
+        }
+        if !range.is_empty() {
+            invalid.push(range);
+        }
+        if remaining >= bytes.len() {
+            break;
+        }
     }
+    Ok(Decoded {
+        bytes,
+        text,
+        invalid,
+    })
 }
-pub fn ascii_to_hex(input: &str) -> String {
-    match ASCII.encode(input, EncoderTrap::Strict) {
+
+pub fn text_to_hex(input: &str, name: &str, with_bom: bool) -> String {
+    match encode(input, name, with_bom) {
         Ok(v) => hex::encode(v),
         Err(e) => format!("Error: {}", e),
     }
 }
-pub fn hex_to_ascii(input: &str) -> String {
-    match hex::decode(input) {
-        Ok(v) => match ASCII.decode(&v, DecoderTrap::Strict) {
-            Ok(v) => v,
-            Err(e) => format!("Error: {}", e),
-        },
-        Err(e) => format!("Error: {}", e),
+/// Decodes `bytes`, skipping the encoding's own BOM if present
+pub fn decode_text(bytes: Vec<u8>, name: &str, trap: Trap) -> Result<Decoded, String> {
+    let skip = match bom(name) {
+        Some(bom) if bytes.starts_with(bom) => bom.len(),
+        _ => 0,
+    };
+    decode_from(bytes, skip, name, trap)
+}
+
+pub fn hex_to_text(input: &str, name: &str, trap: Trap) -> Result<Decoded, String> {
+    let digits: String = input.chars().filter(|c| !c.is_whitespace()).collect();
+    let bytes = hex::decode(digits).map_err(|e| e.to_string())?;
+    decode_text(bytes, name, trap)
+}
+
+#[cfg(test)]
+mod test {
+    /// Text each encoding can represent, in its own script
+    fn sample(name: &str) -> &'static str {
+        match name {
+            "UTF-8" | "UTF-16LE" | "UTF-16BE" | "gb18030" => "Unicode ✓ 中文 日本語 한국어 😀",
+            "IBM866" | "ISO-8859-5" | "KOI8-R" | "windows-1251" | "x-mac-cyrillic" => "Привет, мир",
+            "KOI8-U" => "Привіт, світ",
+            "ISO-8859-2" | "windows-1250" => "Zażółć gęślą jaźń",
+            "ISO-8859-3" => "Ĉu ĝi ŝanĝas?",
+            "ISO-8859-4" | "ISO-8859-13" | "windows-1257" => "Ąžuolas ėjo",
+            "ISO-8859-6" | "windows-1256" => "مرحبا",
+            "ISO-8859-7" | "windows-1253" => "Καλημέρα",
+            "ISO-8859-8" | "ISO-8859-8-I" | "windows-1255" => "שלום",
+            "ISO-8859-10" => "Þórður",
+            "ISO-8859-14" => "Ŵŷ ḃ",
+            "ISO-8859-15" | "windows-1252" | "macintosh" => "Crème brûlée €5",
+            "ISO-8859-16" => "Știință",
+            "windows-874" => "สวัสดี",
+            "windows-1254" => "Günaydın",
+            "windows-1258" => "Ăn Đơn Ưu ₫",
+            "GBK" => "简体中文",
+            "Big5" => "繁體中文",
+            "EUC-JP" | "ISO-2022-JP" | "Shift_JIS" => "日本語のテキスト",
+            "EUC-KR" => "한국어",
+            "x-user-defined" => "ASCII \u{F780}\u{F7FF}",
+            _ => unreachable!("no sample for {}", name),
+        }
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        for name in super::WHATWG {
+            let text = sample(name);
+            let bytes =
+                super::encode(text, name, false).unwrap_or_else(|e| panic!("{}: {}", name, e));
+            let decoded = super::decode(bytes, name, super::Trap::Strict).unwrap();
+            assert_eq!(decoded.text, text, "{}", name);
+        }
+        // Shift_JIS and EUC-JP differ, the old Shift_JIS conversion used EUC-JP
+        assert_eq!(super::text_to_hex("あ", "Shift_JIS", false), "82a0");
+        assert_eq!(super::text_to_hex("あ", "EUC-JP", false), "a4a2");
+        assert_eq!(super::text_to_hex("A", "UTF-16LE", true), "fffe4100");
+        let text = |hex, name| super::hex_to_text(hex, name, super::Trap::Strict).map(|d| d.text);
+        assert_eq!(text("feff 0041", "UTF-16BE").unwrap(), "A");
+        assert!(text("82", "Shift_JIS").is_err());
+        // unlike windows-1252, ASCII refuses anything past 0x7f
+        assert_eq!(super::text_to_hex("Az~", "ASCII", false), "417a7e");
+        assert!(super::encode("é", "ASCII", false).is_err());
+        assert!(text("e9", "ASCII").is_err());
+        assert_eq!(text("e9", "windows-1252").unwrap(), "é");
+    }
+
+    #[test]
+    fn test_trap() {
+        use super::Trap;
+        let decode = |trap| super::decode(b"a\xffb\xe4\xb8".to_vec(), "UTF-8", trap);
+        assert_eq!(
+            decode(Trap::Strict).unwrap_err(),
+            "invalid sequence at offset 1"
+        );
+        let replaced = decode(Trap::Replace).unwrap();
+        assert_eq!(replaced.text, "a\u{FFFD}b\u{FFFD}");
+        assert_eq!(replaced.invalid, vec![1..2, 3..5]);
+        assert_eq!(decode(Trap::Ignore).unwrap().text, "ab");
+        assert_eq!(decode(Trap::Escape).unwrap().text, "a\\xFFb\\xE4\\xB8");
+        // offsets stay relative to the input when a BOM is skipped
+        let decoded = super::hex_to_text("efbbbf 61 80", "UTF-8", Trap::Escape).unwrap();
+        assert_eq!(decoded.text, "a\\x80");
+        assert_eq!(decoded.invalid, vec![4..5]);
+        let decoded = super::hex_to_text("82a0 82", "Shift_JIS", Trap::Replace).unwrap();
+        assert_eq!(decoded.text, "あ\u{FFFD}");
+        assert_eq!(decoded.invalid, vec![2..3]);
+    }
+
+    #[test]
+    fn test_search() {
+        assert_eq!(super::search("sjis"), vec!["Shift_JIS"]);
+        assert_eq!(super::search("latin1"), vec!["windows-1252"]);
+        assert_eq!(super::search("8859-1").len(), 5);
+        assert_eq!(super::search("ascii"), vec!["ASCII", "windows-1252"]);
+        assert_eq!(super::search("").len(), super::WHATWG.len() + 1);
     }
 }
@@ -0,0 +1,769 @@
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum Lang {
+    Rust,
+    Json,
+    C,
+    Python,
+    Java,
+    Shell,
+    Sql,
+}
+
+pub const LANGS: [Lang; 7] = [
+    Lang::Rust,
+    Lang::Json,
+    Lang::C,
+    Lang::Python,
+    Lang::Java,
+    Lang::Shell,
+    Lang::Sql,
+];
+
+impl Lang {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Lang::Rust => "Rust",
+            Lang::Json => "JSON",
+            Lang::C => "C",
+            Lang::Python => "Python",
+            Lang::Java => "Java",
+            Lang::Shell => "Shell",
+            Lang::Sql => "SQL",
+        }
+    }
+    pub fn has_raw(&self) -> bool {
+        matches!(self, Lang::Rust | Lang::Python)
+    }
+}
+
+/// Writes `input` as a quoted literal, a raw one if `raw` and the language has them
+pub fn escape(input: &str, lang: Lang, raw: bool) -> Result<String, String> {
+    if raw && lang.has_raw() {
+        return match lang {
+            Lang::Rust => Ok(rust_raw(input)),
+            _ => python_raw(input),
+        };
+    }
+    let mut output = String::new();
+    match lang {
+        Lang::Rust => {
+            output.push('"');
+            for c in input.chars() {
+                match c {
+                    '\\' => output.push_str("\\\\"),
+                    '"' => output.push_str("\\\""),
+                    '\n' => output.push_str("\\n"),
+                    '\r' => output.push_str("\\r"),
+                    '\t' => output.push_str("\\t"),
+                    '\0' => output.push_str("\\0"),
+                    c if c.is_control() => output.push_str(&format!("\\u{{{:x}}}", c as u32)),
+                    c => output.push(c),
+                }
+            }
+            output.push('"');
+        }
+        Lang::Json => output = serde_json::to_string(input).map_err(|e| e.to_string())?,
+        Lang::C | Lang::Python | Lang::Java => {
+            output.push('"');
+            for c in input.chars() {
+                match c {
+                    '\\' => output.push_str("\\\\"),
+                    '"' => output.push_str("\\\""),
+                    '\n' => output.push_str("\\n"),
+                    '\r' => output.push_str("\\r"),
+                    '\t' => output.push_str("\\t"),
+                    '\u{8}' if lang != Lang::Python => output.push_str("\\b"),
+                    '\u{c}' if lang != Lang::Python => output.push_str("\\f"),
+                    // \x reads exactly two digits in Python but any number in C
+                    c if c.is_control() && lang == Lang::Python => {
+                        output.push_str(&format!("\\x{:02x}", c as u32))
+                    }
+                    // octal is a single byte in C, so C1 controls need their code point
+                    c if c.is_control() && lang == Lang::C && (c as u32) >= 0x80 => {
+                        output.push_str(&format!("\\u{:04x}", c as u32))
+                    }
+                    // Java turns \u000a into a line break before lexing, octal is safe
+                    c if c.is_control() && (c as u32) < 0x100 => {
+                        output.push_str(&format!("\\{:03o}", c as u32))
+                    }
+                    c => output.push(c),
+                }
+            }
+            output.push('"');
+        }
+        Lang::Shell => {
+            let safe = |c: char| c.is_ascii_alphanumeric() || "_@%+=:,./-".contains(c);
+            if !input.is_empty() && input.chars().all(safe) {
+                output.push_str(input);
+            } else {
+                output = format!("'{}'", input.replace('\'', "'\\''"));
+            }
+        }
+        Lang::Sql => output = format!("'{}'", input.replace('\'', "''")),
+    }
+    Ok(output)
+}
+
+/// `r#"..."#` with one `#` more than the longest `"#..` run inside
+fn rust_raw(input: &str) -> String {
+    let mut hashes = 0;
+    for (i, _) in input.match_indices('"') {
+        let run = input[i + 1..].chars().take_while(|&c| c == '#').count();
+        hashes = hashes.max(run + 1);
+    }
+    let hashes = "#".repeat(hashes);
+    format!("r{}\"{}\"{}", hashes, input, hashes)
+}
+
+fn python_raw(input: &str) -> Result<String, String> {
+    let trailing = input.chars().rev().take_while(|&c| c == '\\').count();
+    if trailing % 2 == 1 {
+        return Err("a raw string cannot end with an odd number of backslashes".to_string());
+    }
+    let quote = ['"', '\'']
+        .into_iter()
+        .find(|&q| !input.contains(q))
+        .ok_or("a raw string cannot contain both quote characters".to_string())?;
+    let quote = match input.contains('\n') {
+        true => quote.to_string().repeat(3),
+        false => quote.to_string(),
+    };
+    Ok(format!("r{}{}{}", quote, input, quote))
+}
+
+/// Reads a literal, quoted or just its content, reporting errors by character position
+pub fn unescape(input: &str, lang: Lang) -> Result<String, String> {
+    let mut p = Parser::new(input);
+    match lang {
+        Lang::Rust => {
+            if let Some(raw) = p.rust_raw() {
+                return Ok(raw);
+            }
+            p.quoted(&["\""]);
+            p.rust()?;
+        }
+        Lang::Json => {
+            p.quoted(&["\""]);
+            p.json()?;
+        }
+        Lang::C => {
+            p.quoted(&["u8\"", "L\"", "u\"", "U\"", "\""]);
+            p.c()?;
+        }
+        Lang::Python => {
+            if let Some(raw) = p.python_raw() {
+                return Ok(raw);
+            }
+            // triple quoted strings may contain their quote character
+            if p.quoted(&["\"\"\"", "'''", "\"", "'"])
+                .is_some_and(|q| q.len() == 3)
+            {
+                p.quote = None;
+            }
+            p.python()?;
+        }
+        Lang::Java => {
+            // a text block starts on the line after its opening quotes
+            if p.quoted(&["\"\"\"", "\""]) == Some("\"\"\"") {
+                p.quote = None;
+                if p.peek() == Some('\n') {
+                    p.pos += 1;
+                }
+            }
+            p.java()?;
+        }
+        Lang::Shell => p.shell()?,
+        Lang::Sql => {
+            p.quoted(&["'"]);
+            p.sql()?;
+        }
+    }
+    p.finish()
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    end: usize,
+    /// The quote closing the literal, which must be escaped inside it
+    quote: Option<char>,
+    out: Vec<u8>,
+}
+
+impl Parser {
+    fn new(input: &str) -> Self {
+        let chars: Vec<char> = input.chars().collect();
+        Self {
+            end: chars.len(),
+            chars,
+            pos: 0,
+            quote: None,
+            out: Vec::new(),
+        }
+    }
+    fn err<T>(&self, at: usize, msg: &str) -> Result<T, String> {
+        Err(format!("{} at character {}", msg, at + 1))
+    }
+    fn starts_with(&self, s: &str) -> bool {
+        let s: Vec<char> = s.chars().collect();
+        self.chars[self.pos..self.end].starts_with(&s)
+    }
+    fn ends_with(&self, s: &str) -> bool {
+        let s: Vec<char> = s.chars().collect();
+        self.chars[self.pos..self.end].ends_with(&s)
+    }
+    /// Narrows parsing to the inside of the first matching `prefix..quote` pair, if any
+    fn quoted<'a>(&mut self, prefixes: &[&'a str]) -> Option<&'a str> {
+        for prefix in prefixes {
+            let quote: String = prefix.chars().skip_while(|c| c.is_alphanumeric()).collect();
+            let len = prefix.chars().count();
+            if self.starts_with(prefix)
+                && self.end - self.pos >= len + quote.chars().count()
+                && self.ends_with(&quote)
+            {
+                self.pos += len;
+                self.end -= quote.chars().count();
+                self.quote = quote.chars().next();
+                return Some(prefix);
+            }
+        }
+        None
+    }
+    fn peek(&self) -> Option<char> {
+        (self.pos < self.end).then(|| self.chars[self.pos])
+    }
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek();
+        self.pos += 1;
+        c
+    }
+    fn push(&mut self, c: char) {
+        self.out
+            .extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+    }
+    /// Reads up to `max` digits of `radix`, at least `min`
+    fn digits(&mut self, radix: u32, min: usize, max: usize, at: usize) -> Result<u32, String> {
+        let mut value = 0u32;
+        let mut count = 0;
+        while count < max {
+            match self.peek().and_then(|c| c.to_digit(radix)) {
+                Some(d) => {
+                    value = value.saturating_mul(radix).saturating_add(d);
+                    self.pos += 1;
+                    count += 1;
+                }
+                None => break,
+            }
+        }
+        if count < min {
+            return self.err(at, &format!("expected {} digits in escape", min));
+        }
+        Ok(value)
+    }
+    fn code_point(&self, value: u32, at: usize) -> Result<char, String> {
+        match char::from_u32(value) {
+            Some(c) => Ok(c),
+            None => self.err(at, &format!("invalid code point {:X}", value)),
+        }
+    }
+    /// `\uXXXX` with UTF-16 surrogate pairs, the `\u` already read
+    fn utf16_escape(&mut self, at: usize, repeated_u: bool) -> Result<(), String> {
+        if repeated_u {
+            // Java allows \uuuu0041
+            while self.peek() == Some('u') {
+                self.pos += 1;
+            }
+        }
+        let high = self.digits(16, 4, 4, at)?;
+        if !(0xD800..0xDC00).contains(&high) {
+            let c = self.code_point(high, at)?;
+            self.push(c);
+            return Ok(());
+        }
+        if !self.starts_with("\\u") {
+            return self.err(at, "unpaired surrogate");
+        }
+        self.pos += 2;
+        let low = self.digits(16, 4, 4, at)?;
+        if !(0xDC00..0xE000).contains(&low) {
+            return self.err(at, "unpaired surrogate");
+        }
+        let c = self.code_point(0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00), at)?;
+        self.push(c);
+        Ok(())
+    }
+    /// Handles a character that is not an escape, refusing the closing quote
+    fn literal(&mut self, c: char, at: usize) -> Result<(), String> {
+        if Some(c) == self.quote {
+            return self.err(at, "unescaped quote");
+        }
+        self.push(c);
+        Ok(())
+    }
+    fn finish(self) -> Result<String, String> {
+        String::from_utf8(self.out).map_err(|e| {
+            format!(
+                "escaped bytes are not UTF-8 at byte {}",
+                e.utf8_error().valid_up_to()
+            )
+        })
+    }
+
+    fn rust_raw(&mut self) -> Option<String> {
+        if !self.starts_with("r") {
+            return None;
+        }
+        let hashes = self.chars[1..].iter().take_while(|&&c| c == '#').count();
+        let open = format!("r{}\"", "#".repeat(hashes));
+        let close = format!("\"{}", "#".repeat(hashes));
+        if self.starts_with(&open) && self.end >= open.len() + close.len() && self.ends_with(&close)
+        {
+            return Some(
+                self.chars[open.len()..self.end - close.len()]
+                    .iter()
+                    .collect(),
+            );
+        }
+        None
+    }
+    fn rust(&mut self) -> Result<(), String> {
+        while let Some(c) = self.next() {
+            let at = self.pos - 1;
+            if c != '\\' {
+                self.literal(c, at)?;
+                continue;
+            }
+            match self.next() {
+                Some('n') => self.push('\n'),
+                Some('r') => self.push('\r'),
+                Some('t') => self.push('\t'),
+                Some('0') => self.push('\0'),
+                Some(c @ ('\\' | '\'' | '"')) => self.push(c),
+                Some('x') => {
+                    let v = self.digits(16, 2, 2, at)?;
+                    if v > 0x7F {
+                        return self.err(at, "\\x escape above 7F");
+                    }
+                    self.push(v as u8 as char);
+                }
+                Some('u') => {
+                    if self.next() != Some('{') {
+                        return self.err(at, "expected { after \\u");
+                    }
+                    let v = self.digits(16, 1, 6, at)?;
+                    if self.next() != Some('}') {
+                        return self.err(at, "expected } closing \\u{");
+                    }
+                    let c = self.code_point(v, at)?;
+                    self.push(c);
+                }
+                // a line continuation also skips the next line's indentation
+                Some('\n') => {
+                    while self.peek().is_some_and(char::is_whitespace) {
+                        self.pos += 1;
+                    }
+                }
+                Some(_) => return self.err(at, "unknown escape"),
+                None => return self.err(at, "dangling backslash"),
+            }
+        }
+        Ok(())
+    }
+    fn json(&mut self) -> Result<(), String> {
+        while let Some(c) = self.next() {
+            let at = self.pos - 1;
+            if c != '\\' {
+                if c.is_control() && (c as u32) < 0x20 {
+                    return self.err(at, "unescaped control character");
+                }
+                self.literal(c, at)?;
+                continue;
+            }
+            match self.next() {
+                Some('b') => self.push('\u{8}'),
+                Some('f') => self.push('\u{c}'),
+                Some('n') => self.push('\n'),
+                Some('r') => self.push('\r'),
+                Some('t') => self.push('\t'),
+                Some(c @ ('\\' | '"' | '/')) => self.push(c),
+                Some('u') => self.utf16_escape(at, false)?,
+                Some(_) => return self.err(at, "unknown escape"),
+                None => return self.err(at, "dangling backslash"),
+            }
+        }
+        Ok(())
+    }
+    fn c(&mut self) -> Result<(), String> {
+        while let Some(c) = self.next() {
+            let at = self.pos - 1;
+            if c != '\\' {
+                self.literal(c, at)?;
+                continue;
+            }
+            match self.next() {
+                Some('a') => self.push('\u{7}'),
+                Some('b') => self.push('\u{8}'),
+                Some('f') => self.push('\u{c}'),
+                Some('n') => self.push('\n'),
+                Some('r') => self.push('\r'),
+                Some('t') => self.push('\t'),
+                Some('v') => self.push('\u{b}'),
+                Some(c @ ('\\' | '\'' | '"' | '?')) => self.push(c),
+                // octal and \x escapes are bytes, not characters
+                Some('0'..='7') => {
+                    self.pos -= 1;
+                    let v = self.digits(8, 1, 3, at)?;
+                    if v > 0xFF {
+                        return self.err(at, "octal escape above 377");
+                    }
+                    self.out.push(v as u8);
+                }
+                Some('x') => {
+                    let v = self.digits(16, 1, usize::MAX, at)?;
+                    if v > 0xFF {
+                        return self.err(at, "\\x escape above FF");
+                    }
+                    self.out.push(v as u8);
+                }
+                Some('u') => {
+                    let v = self.digits(16, 4, 4, at)?;
+                    let c = self.code_point(v, at)?;
+                    self.push(c);
+                }
+                Some('U') => {
+                    let v = self.digits(16, 8, 8, at)?;
+                    let c = self.code_point(v, at)?;
+                    self.push(c);
+                }
+                Some('\n') => {}
+                Some(_) => return self.err(at, "unknown escape"),
+                None => return self.err(at, "dangling backslash"),
+            }
+        }
+        Ok(())
+    }
+    fn python_raw(&mut self) -> Option<String> {
+        let prefix = self
+            .chars
+            .iter()
+            .take_while(|c| c.is_ascii_alphabetic())
+            .count();
+        if prefix == 0 || prefix > 2 || !self.chars[..prefix].iter().any(|c| *c == 'r' || *c == 'R')
+        {
+            return None;
+        }
+        self.pos = prefix;
+        for quote in ["\"\"\"", "'''", "\"", "'"] {
+            if self.starts_with(quote)
+                && self.end - self.pos >= 2 * quote.len()
+                && self.ends_with(quote)
+            {
+                return Some(
+                    self.chars[self.pos + quote.len()..self.end - quote.len()]
+                        .iter()
+                        .collect(),
+                );
+            }
+        }
+        self.pos = 0;
+        None
+    }
+    fn python(&mut self) -> Result<(), String> {
+        while let Some(c) = self.next() {
+            let at = self.pos - 1;
+            if c != '\\' {
+                self.literal(c, at)?;
+                continue;
+            }
+            match self.next() {
+                Some('a') => self.push('\u{7}'),
+                Some('b') => self.push('\u{8}'),
+                Some('f') => self.push('\u{c}'),
+                Some('n') => self.push('\n'),
+                Some('r') => self.push('\r'),
+                Some('t') => self.push('\t'),
+                Some('v') => self.push('\u{b}'),
+                Some(c @ ('\\' | '\'' | '"')) => self.push(c),
+                Some('0'..='7') => {
+                    self.pos -= 1;
+                    let v = self.digits(8, 1, 3, at)?;
+                    let c = self.code_point(v, at)?;
+                    self.push(c);
+                }
+                Some('x') => {
+                    let v = self.digits(16, 2, 2, at)?;
+                    self.push(v as u8 as char);
+                }
+                Some('u') => {
+                    let v = self.digits(16, 4, 4, at)?;
+                    let c = self.code_point(v, at)?;
+                    self.push(c);
+                }
+                Some('U') => {
+                    let v = self.digits(16, 8, 8, at)?;
+                    let c = self.code_point(v, at)?;
+                    self.push(c);
+                }
+                Some('N') => {
+                    if self.next() != Some('{') {
+                        return self.err(at, "expected { after \\N");
+                    }
+                    let mut name = String::new();
+                    loop {
+                        match self.next() {
+                            Some('}') => break,
+                            Some(c) => name.push(c),
+                            None => return self.err(at, "expected } closing \\N{"),
+                        }
+                    }
+                    match unicode_names2::character(&name) {
+                        Some(c) => self.push(c),
+                        None => return self.err(at, &format!("unknown character name {}", name)),
+                    }
+                }
+                Some('\n') => {}
+                // unknown escapes keep their backslash
+                Some(c) => {
+                    self.push('\\');
+                    self.push(c);
+                }
+                None => return self.err(at, "dangling backslash"),
+            }
+        }
+        Ok(())
+    }
+    fn java(&mut self) -> Result<(), String> {
+        while let Some(c) = self.next() {
+            let at = self.pos - 1;
+            if c != '\\' {
+                self.literal(c, at)?;
+                continue;
+            }
+            match self.next() {
+                Some('b') => self.push('\u{8}'),
+                Some('t') => self.push('\t'),
+                Some('n') => self.push('\n'),
+                Some('f') => self.push('\u{c}'),
+                Some('r') => self.push('\r'),
+                Some('s') => self.push(' '),
+                Some(c @ ('\\' | '\'' | '"')) => self.push(c),
+                Some('0'..='7') => {
+                    self.pos -= 1;
+                    let v = self.digits(8, 1, 3, at)?;
+                    if v > 0xFF {
+                        return self.err(at, "octal escape above 377");
+                    }
+                    let c = self.code_point(v, at)?;
+                    self.push(c);
+                }
+                Some('u') => self.utf16_escape(at, true)?,
+                Some('\n') => {}
+                Some(_) => return self.err(at, "unknown escape"),
+                None => return self.err(at, "dangling backslash"),
+            }
+        }
+        Ok(())
+    }
+    /// Joins the words of a POSIX shell command line with single spaces, quoting removed.
+    /// Expansions like `$HOME` are left as written.
+    fn shell(&mut self) -> Result<(), String> {
+        let mut in_word = false;
+        while let Some(c) = self.next() {
+            let at = self.pos - 1;
+            if c.is_whitespace() {
+                in_word = false;
+                continue;
+            }
+            if !in_word && !self.out.is_empty() {
+                self.push(' ');
+            }
+            in_word = true;
+            match c {
+                '\'' => loop {
+                    match self.next() {
+                        Some('\'') => break,
+                        Some(c) => self.push(c),
+                        None => return self.err(at, "unterminated single quote"),
+                    }
+                },
+                '"' => loop {
+                    match self.next() {
+                        Some('"') => break,
+                        Some('\\') => match self.next() {
+                            Some(c @ ('$' | '`' | '"' | '\\')) => self.push(c),
+                            Some('\n') => {}
+                            Some(c) => {
+                                self.push('\\');
+                                self.push(c);
+                            }
+                            None => return self.err(at, "unterminated double quote"),
+                        },
+                        Some(c) => self.push(c),
+                        None => return self.err(at, "unterminated double quote"),
+                    }
+                },
+                '$' if self.peek() == Some('\'') => {
+                    self.pos += 1;
+                    self.ansi_c(at)?;
+                }
+                '\\' => match self.next() {
+                    Some('\n') => {}
+                    Some(c) => self.push(c),
+                    None => return self.err(at, "dangling backslash"),
+                },
+                c => self.push(c),
+            }
+        }
+        Ok(())
+    }
+    /// The body of bash's `$'...'`, up to and including the closing quote
+    fn ansi_c(&mut self, open: usize) -> Result<(), String> {
+        loop {
+            let at = self.pos;
+            match self.next() {
+                Some('\'') => return Ok(()),
+                Some('\\') => match self.next() {
+                    Some('a') => self.push('\u{7}'),
+                    Some('b') => self.push('\u{8}'),
+                    Some('e' | 'E') => self.push('\u{1b}'),
+                    Some('f') => self.push('\u{c}'),
+                    Some('n') => self.push('\n'),
+                    Some('r') => self.push('\r'),
+                    Some('t') => self.push('\t'),
+                    Some('v') => self.push('\u{b}'),
+                    Some(c @ ('\\' | '\'' | '"' | '?')) => self.push(c),
+                    Some('0'..='7') => {
+                        self.pos -= 1;
+                        let v = self.digits(8, 1, 3, at)?;
+                        self.out.push(v as u8);
+                    }
+                    Some('x') => {
+                        let v = self.digits(16, 1, 2, at)?;
+                        self.out.push(v as u8);
+                    }
+                    Some('u') => {
+                        let v = self.digits(16, 1, 4, at)?;
+                        let c = self.code_point(v, at)?;
+                        self.push(c);
+                    }
+                    Some('U') => {
+                        let v = self.digits(16, 1, 8, at)?;
+                        let c = self.code_point(v, at)?;
+                        self.push(c);
+                    }
+                    Some('c') => match self.next() {
+                        Some(c) if c.is_ascii() => {
+                            self.out.push(c.to_ascii_uppercase() as u8 ^ 0x40)
+                        }
+                        _ => return self.err(at, "expected an ASCII character after \\c"),
+                    },
+                    Some(c) => {
+                        self.push('\\');
+                        self.push(c);
+                    }
+                    None => return self.err(open, "unterminated $' quote"),
+                },
+                Some(c) => self.push(c),
+                None => return self.err(open, "unterminated $' quote"),
+            }
+        }
+    }
+    fn sql(&mut self) -> Result<(), String> {
+        while let Some(c) = self.next() {
+            let at = self.pos - 1;
+            if c == '\'' && self.next() != Some('\'') {
+                return self.err(at, "quote not doubled");
+            }
+            self.push(c);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip() {
+        let text = "Tab\tquote\" apostrophe' back\\slash\nline \u{1} \u{85} é 😀 r\"#";
+        for lang in LANGS {
+            let escaped = escape(text, lang, false).unwrap();
+            assert_eq!(
+                unescape(&escaped, lang).unwrap(),
+                text,
+                "{:?}: {}",
+                lang,
+                escaped
+            );
+        }
+        let raw = "C:\\path \"quoted\"# ok";
+        let escaped = escape(raw, Lang::Rust, true).unwrap();
+        assert_eq!(escaped, "r##\"C:\\path \"quoted\"# ok\"##");
+        assert_eq!(unescape(&escaped, Lang::Rust).unwrap(), raw);
+        assert_eq!(
+            escape("C:\\dir", Lang::Python, true).unwrap(),
+            "r\"C:\\dir\""
+        );
+        assert!(escape("a\\", Lang::Python, true).is_err());
+        assert_eq!(unescape("r'C:\\dir'", Lang::Python).unwrap(), "C:\\dir");
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape("a\u{1}", Lang::Rust, false).unwrap(), "\"a\\u{1}\"");
+        assert_eq!(escape("a\u{1}", Lang::Json, false).unwrap(), "\"a\\u0001\"");
+        assert_eq!(escape("a\u{1}b", Lang::C, false).unwrap(), "\"a\\001b\"");
+        assert_eq!(escape("\u{85}", Lang::C, false).unwrap(), "\"\\u0085\"");
+        assert_eq!(escape("a\u{1}", Lang::Python, false).unwrap(), "\"a\\x01\"");
+        assert_eq!(escape("it's", Lang::Shell, false).unwrap(), "'it'\\''s'");
+        assert_eq!(
+            escape("safe-path/x.txt", Lang::Shell, false).unwrap(),
+            "safe-path/x.txt"
+        );
+        assert_eq!(escape("it's", Lang::Sql, false).unwrap(), "'it''s'");
+    }
+
+    #[test]
+    fn test_unescape() {
+        assert_eq!(unescape(r#""\u{1F600}\x41""#, Lang::Rust).unwrap(), "😀A");
+        assert_eq!(unescape(r#""\ud83d\ude00\/""#, Lang::Json).unwrap(), "😀/");
+        assert_eq!(unescape(r#"u8"\xe4\xb8\xad\101""#, Lang::C).unwrap(), "中A");
+        assert_eq!(
+            unescape(r"'\N{GREEK SMALL LETTER ALPHA}\d'", Lang::Python).unwrap(),
+            "α\\d"
+        );
+        assert_eq!(unescape(r#""\uuu0041\101\s""#, Lang::Java).unwrap(), "AA ");
+        assert_eq!(
+            unescape(r#"echo 'a b'"\$x" $'\x41\n'"#, Lang::Shell).unwrap(),
+            "echo a b$x A\n"
+        );
+        assert_eq!(unescape("'it''s'", Lang::Sql).unwrap(), "it's");
+        assert_eq!(unescape("'''a'b'''", Lang::Python).unwrap(), "a'b");
+        assert_eq!(
+            unescape("\"\"\"\n  \"x\"\"\"\"", Lang::Java).unwrap(),
+            "  \"x\""
+        );
+        assert_eq!(
+            unescape("a\\q", Lang::Rust).unwrap_err(),
+            "unknown escape at character 2"
+        );
+        assert_eq!(
+            unescape(r#""ab\ud83d""#, Lang::Json).unwrap_err(),
+            "unpaired surrogate at character 4"
+        );
+        assert_eq!(
+            unescape("\"a\"b\"", Lang::C).unwrap_err(),
+            "unescaped quote at character 3"
+        );
+        assert_eq!(
+            unescape("echo 'abc", Lang::Shell).unwrap_err(),
+            "unterminated single quote at character 6"
+        );
+        assert_eq!(
+            unescape("'it's'", Lang::Sql).unwrap_err(),
+            "quote not doubled at character 4"
+        );
+        assert!(unescape(r#""\xff""#, Lang::C).is_err());
+    }
+}
@@ -2,13 +2,111 @@ use super::*;
 use eframe::egui;
 
 mod basic;
+mod detect;
 mod encoding;
+mod literal;
+mod transcode;
+mod unicode;
+mod urls;
 
 #[derive(PartialEq, Eq, Hash, Clone)]
+enum FileState {
+    None,
+    LoadFile,
+    SaveOutput,
+    ScanDir,
+    OutputDir,
+}
+
+const LOAD_FILE: u8 = 0;
+const SAVE_OUTPUT: u8 = 1;
+const SCAN_DIR: u8 = 2;
+const OUTPUT_DIR: u8 = 3;
+
+struct TranscodeForm {
+    options: transcode::Options,
+    recursive: bool,
+    report: Option<transcode::Report>,
+    recv: Option<std::sync::mpsc::Receiver<transcode::Report>>,
+}
+
+impl TranscodeForm {
+    fn new() -> Self {
+        Self {
+            options: transcode::Options {
+                from: "GBK",
+                to: "UTF-8",
+                bom: false,
+                line_ending: transcode::LineEnding::Keep,
+                lossy: false,
+                out_dir: None,
+                bases: Vec::new(),
+            },
+            recursive: true,
+            report: None,
+            recv: None,
+        }
+    }
+    /// Converts the files listed one per line in `list` on a thread
+    fn start(&mut self, list: &str) -> Result<(), String> {
+        let files: Vec<path::PathBuf> = list
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty())
+            .map(path::PathBuf::from)
+            .collect();
+        if files.is_empty() {
+            return Err("List the files in Input, one per line, or scan a directory".to_string());
+        }
+        let plan = transcode::plan(&files, &self.options)?;
+        let options = self.options.clone();
+        let (send, recv) = std::sync::mpsc::channel();
+        self.recv = Some(recv);
+        self.report = None;
+        std::thread::spawn(move || {
+            let report = transcode::run(&plan, &options);
+            // the receiver is gone if the tool was closed meanwhile
+            let _ = send.send(report);
+        });
+        Ok(())
+    }
+    fn encoding_combo(ui: &mut egui::Ui, id: &str, name: &mut &'static str) {
+        egui::ComboBox::from_id_salt(id)
+            .selected_text(*name)
+            .show_ui(ui, |ui| {
+                for encoding in encoding::names() {
+                    ui.selectable_value(name, encoding, encoding);
+                }
+            });
+    }
+}
+
 pub struct Encoder {
     input: String,
     output: String,
     selected_catagory: Catagory,
+    scheme: byte_tool::binascii::Scheme,
+    msg: Option<Msg>,
+    state: FileState,
+    /// Bytes of the hex Input or loaded file that the guesses were made for
+    detect_data: Vec<u8>,
+    guesses: Vec<detect::Guess>,
+    encoding: &'static str,
+    encoding_filter: String,
+    bom: bool,
+    trap: encoding::Trap,
+    /// Last decode result that had invalid bytes, kept to highlight them
+    decoded: Option<encoding::Decoded>,
+    /// Input the character table was built for
+    inspected: String,
+    chars: Vec<unicode::CharInfo>,
+    unicode_warnings: Vec<String>,
+    literal_lang: literal::Lang,
+    literal_raw: bool,
+    /// URL from Input being edited, rebuilt into Output on every change
+    url: Option<urls::Parts>,
+    url_rows: Vec<urls::Row>,
+    transcode_form: TranscodeForm,
 }
 
 impl Encoder {
@@ -17,6 +115,422 @@ impl Encoder {
             input: String::new(),
             output: String::new(),
             selected_catagory: Catagory::Op,
+            scheme: byte_tool::binascii::Scheme::Base32,
+            msg: None,
+            state: FileState::None,
+            detect_data: Vec::new(),
+            guesses: Vec::new(),
+            encoding: "UTF-8",
+            encoding_filter: String::new(),
+            bom: false,
+            trap: encoding::Trap::Replace,
+            decoded: None,
+            inspected: String::new(),
+            chars: Vec::new(),
+            unicode_warnings: Vec::new(),
+            literal_lang: literal::Lang::Rust,
+            literal_raw: false,
+            url: None,
+            url_rows: Vec::new(),
+            transcode_form: TranscodeForm::new(),
+        }
+    }
+    fn set_detect_data(&mut self, data: Vec<u8>) {
+        self.guesses = detect::detect(&data);
+        self.detect_data = data;
+    }
+    /// Replaces Output with something that isn't a decode, dropping the invalid bytes panel
+    fn set_output(&mut self, output: String) {
+        self.decoded = None;
+        self.output = output;
+    }
+    fn set_decoded(&mut self, result: Result<encoding::Decoded, String>) {
+        self.decoded = None;
+        match result {
+            Ok(mut decoded) => {
+                self.output = std::mem::take(&mut decoded.text);
+                if let Some(first) = decoded.invalid.first() {
+                    self.msg = Some(Msg::new(
+                        format!(
+                            "{} invalid byte sequences, the first at offset {}",
+                            decoded.invalid.len(),
+                            first.start
+                        ),
+                        MsgType::Warning,
+                    ));
+                    self.decoded = Some(decoded);
+                }
+            }
+            Err(e) => self.output = format!("Error: {}", e),
+        }
+    }
+    fn trap_combo(ui: &mut egui::Ui, trap: &mut encoding::Trap) {
+        egui::ComboBox::from_id_salt(ui.next_auto_id())
+            .selected_text(trap.name())
+            .show_ui(ui, |ui| {
+                for t in encoding::TRAPS {
+                    ui.selectable_value(trap, t, t.name());
+                }
+            })
+            .response
+            .on_hover_text("How bytes that fail to decode are handled");
+    }
+    /// One row per character, the grapheme column marking where each cluster starts
+    fn char_table(ui: &mut egui::Ui, chars: &[unicode::CharInfo]) {
+        use egui_extras::{Column, TableBuilder};
+        let headers = [
+            "Grapheme",
+            "Offset",
+            "Char",
+            "Code Point",
+            "Name",
+            "Category",
+            "Script",
+            "UTF-8",
+            "UTF-16",
+            "Note",
+        ];
+        let mut builder = TableBuilder::new(ui)
+            .id_salt("tool/encode_tool/unicode")
+            .striped(true)
+            .max_scroll_height(320.0);
+        for _ in 0..headers.len() {
+            builder = builder.column(Column::auto().resizable(true).at_least(40.0));
+        }
+        builder
+            .header(20.0, |mut header| {
+                for title in headers {
+                    header.col(|ui| {
+                        ui.strong(title);
+                    });
+                }
+            })
+            .body(|body| {
+                body.rows(18.0, chars.len(), |mut row| {
+                    let c = &chars[row.index()];
+                    row.col(|ui| {
+                        if let Some(grapheme) = c.grapheme {
+                            ui.label(grapheme.to_string());
+                        }
+                    });
+                    row.col(|ui| {
+                        ui.monospace(c.offset.to_string());
+                    });
+                    row.col(|ui| {
+                        ui.label(c.glyph());
+                    });
+                    row.col(|ui| {
+                        ui.monospace(c.code_point());
+                    });
+                    row.col(|ui| {
+                        ui.label(&c.name);
+                    });
+                    row.col(|ui| {
+                        ui.label(c.category);
+                    });
+                    row.col(|ui| {
+                        ui.label(c.script);
+                    });
+                    row.col(|ui| {
+                        ui.monospace(&c.utf8);
+                    });
+                    row.col(|ui| {
+                        ui.monospace(&c.utf16);
+                    });
+                    row.col(|ui| {
+                        if let Some((_, note)) = &c.note {
+                            ui.colored_label(ui.visuals().warn_fg_color, note);
+                        }
+                    });
+                });
+            });
+    }
+    /// Rows of the decoded bytes that contain invalid ones, those highlighted
+    fn show_invalid(ui: &mut egui::Ui, decoded: &encoding::Decoded) {
+        const ROW_BYTES: usize = 16;
+        ui.label("Invalid bytes");
+        let mut rows: Vec<usize> = decoded
+            .invalid
+            .iter()
+            .flat_map(|r| r.start / ROW_BYTES..=(r.end - 1) / ROW_BYTES)
+            .collect();
+        rows.dedup();
+        let font_id = egui::FontId::monospace(12.0);
+        let row_height = ui.fonts(|f| f.row_height(&font_id));
+        egui::ScrollArea::vertical()
+            .id_salt("tool/encode_tool/invalid")
+            .max_height(160.0)
+            .show_rows(ui, row_height, rows.len(), |ui, range| {
+                let normal = egui::TextFormat::simple(font_id.clone(), ui.visuals().text_color());
+                let highlight = egui::TextFormat {
+                    color: ui.visuals().strong_text_color(),
+                    background: ui.visuals().error_fg_color.gamma_multiply(0.5),
+                    ..normal.clone()
+                };
+                for &row in &rows[range] {
+                    let start = row * ROW_BYTES;
+                    let end = (start + ROW_BYTES).min(decoded.bytes.len());
+                    let mut job = egui::text::LayoutJob::default();
+                    job.append(&format!("{:08X} ", start), 0.0, normal.clone());
+                    for i in start..end {
+                        // ranges are sorted and disjoint
+                        let next = decoded.invalid.partition_point(|r| r.end <= i);
+                        let invalid = decoded.invalid.get(next).is_some_and(|r| r.contains(&i));
+                        let format = if invalid { &highlight } else { &normal };
+                        job.append(&format!("{:02x}", decoded.bytes[i]), 0.0, format.clone());
+                        job.append(" ", 0.0, normal.clone());
+                    }
+                    ui.label(job);
+                }
+            });
+    }
+}
+
+impl Encoder {
+    fn transcode_ui(&mut self, ui: &mut egui::Ui) {
+        let form = &mut self.transcode_form;
+        ui.horizontal(|ui| {
+            if ui
+                .button("Scan Dir")
+                .on_hover_text("Append the files of a directory to Input")
+                .clicked()
+            {
+                self.state = FileState::ScanDir;
+            }
+            ui.checkbox(&mut form.recursive, "Recursive");
+            ui.label("Files are read from the lines of Input");
+        });
+        ui.horizontal(|ui| {
+            if ui.button("Output Dir").clicked() {
+                self.state = FileState::OutputDir;
+            }
+            match &form.options.out_dir {
+                Some(dir) => {
+                    ui.label(dir.to_string_lossy());
+                }
+                None => {
+                    ui.colored_label(ui.visuals().warn_fg_color, "No output directory chosen");
+                }
+            };
+        });
+        let mut start = false;
+        ui.horizontal(|ui| {
+            TranscodeForm::encoding_combo(
+                ui,
+                "tool/encode_tool/transcode_from",
+                &mut form.options.from,
+            );
+            if ui.button("⇄").on_hover_text("Swap the encodings").clicked() {
+                let options = &mut form.options;
+                std::mem::swap(&mut options.from, &mut options.to);
+            }
+            TranscodeForm::encoding_combo(
+                ui,
+                "tool/encode_tool/transcode_to",
+                &mut form.options.to,
+            );
+            if encoding::bom(form.options.to).is_some() {
+                ui.checkbox(&mut form.options.bom, "BOM")
+                    .on_hover_text("Start the converted files with a byte order mark");
+            }
+            egui::ComboBox::from_id_salt("tool/encode_tool/transcode_eol")
+                .selected_text(form.options.line_ending.name())
+                .show_ui(ui, |ui| {
+                    for ending in transcode::LINE_ENDINGS {
+                        ui.selectable_value(&mut form.options.line_ending, ending, ending.name());
+                    }
+                });
+            ui.checkbox(&mut form.options.lossy, "Write Lossy Files")
+                .on_hover_text(
+                    "Also write files with invalid bytes or unmappable characters, replacing them",
+                );
+            if form.recv.is_some() {
+                ui.spinner();
+            } else if ui.button("Transcode").clicked() {
+                start = true;
+            }
+        });
+        if let Some(report) = &form.report {
+            egui::ScrollArea::vertical()
+                .id_salt("tool/encode_tool/transcode_report")
+                .max_height(160.0)
+                .show(ui, |ui| {
+                    ui.label(format!("{} files converted", report.converted));
+                    if !report.lossy.is_empty() {
+                        ui.colored_label(
+                            ui.visuals().warn_fg_color,
+                            format!("Lost Data ({}):", report.lossy.len()),
+                        );
+                        for (path, loss) in &report.lossy {
+                            ui.label(format!("    {}: {}", path, loss.describe()));
+                        }
+                    }
+                    if !report.skipped.is_empty() {
+                        ui.colored_label(
+                            ui.visuals().warn_fg_color,
+                            format!("Already UTF-8 ({}):", report.skipped.len()),
+                        );
+                        for path in &report.skipped {
+                            ui.label(format!("    {}", path));
+                        }
+                    }
+                    if !report.failed.is_empty() {
+                        ui.colored_label(
+                            ui.visuals().error_fg_color,
+                            format!("Failed ({}):", report.failed.len()),
+                        );
+                        for error in &report.failed {
+                            ui.label(format!("    {}", error));
+                        }
+                    }
+                });
+        }
+        if start {
+            if let Err(e) = self.transcode_form.start(&self.input) {
+                self.msg = Some(Msg::new(format!("Error: {}", e), MsgType::Error));
+            }
+        }
+    }
+    fn url_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            if ui.button("Parse Input").clicked() {
+                match urls::parse(&self.input) {
+                    Ok(parts) => {
+                        self.url = Some(parts);
+                        self.url_rows.clear();
+                    }
+                    Err(e) => self.msg = Some(Msg::new(format!("Error: {}", e), MsgType::Error)),
+                }
+            }
+            if ui
+                .button("Compare Input Lines")
+                .on_hover_text("Line up the URLs on each line of Input, parameter by parameter")
+                .clicked()
+            {
+                match urls::compare(&self.input) {
+                    Ok(rows) => {
+                        self.url_rows = rows;
+                        self.url = None;
+                    }
+                    Err(e) => self.msg = Some(Msg::new(format!("Error: {}", e), MsgType::Error)),
+                }
+            }
+        });
+        if !self.url_rows.is_empty() {
+            egui::ScrollArea::both()
+                .id_salt("tool/encode_tool/url_compare")
+                .show(ui, |ui| {
+                    egui::Grid::new("tool/encode_tool/url_compare")
+                        .striped(true)
+                        .show(ui, |ui| {
+                            ui.strong("");
+                            for i in 0..self.url_rows[0].values.len() {
+                                ui.strong(format!("Line {}", i + 1));
+                            }
+                            ui.end_row();
+                            for row in &self.url_rows {
+                                let color = if row.differs() {
+                                    ui.visuals().warn_fg_color
+                                } else {
+                                    ui.visuals().text_color()
+                                };
+                                ui.colored_label(color, egui::RichText::new(&row.name).monospace());
+                                for value in &row.values {
+                                    match value {
+                                        Some(v) => ui.colored_label(color, v),
+                                        None => ui.weak("(none)"),
+                                    };
+                                }
+                                ui.end_row();
+                            }
+                        });
+                });
+        }
+        let Some(parts) = &mut self.url else {
+            return;
+        };
+        let mut changed = false;
+        egui::ScrollArea::vertical()
+            .id_salt("tool/encode_tool/url")
+            .show(ui, |ui| {
+                egui::Grid::new("tool/encode_tool/url_parts")
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        let mut field = |ui: &mut egui::Ui, name: &str, value: &mut String| {
+                            ui.label(name);
+                            changed |= ui.text_edit_singleline(value).changed();
+                            ui.end_row();
+                        };
+                        field(ui, "Scheme", &mut parts.scheme);
+                        if !parts.opaque {
+                            field(ui, "Username", &mut parts.username);
+                            field(ui, "Password", &mut parts.password);
+                            field(ui, "Host", &mut parts.host);
+                            field(ui, "Port", &mut parts.port);
+                        }
+                        field(ui, "Fragment", &mut parts.fragment);
+                    });
+                if !parts.opaque && !parts.host.is_ascii() {
+                    if let Ok(ascii) = idna::domain_to_ascii(&parts.host) {
+                        ui.label(format!("Host in ASCII: {}", ascii));
+                    }
+                }
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.label(if parts.opaque {
+                        "Path"
+                    } else {
+                        "Path Segments"
+                    });
+                    if !parts.opaque && ui.button("+").clicked() {
+                        parts.path.push(String::new());
+                        changed = true;
+                    }
+                });
+                let mut remove = None;
+                for (i, segment) in parts.path.iter_mut().enumerate() {
+                    ui.horizontal(|ui| {
+                        changed |= ui.text_edit_singleline(segment).changed();
+                        if !parts.opaque && ui.small_button("-").clicked() {
+                            remove = Some(i);
+                        }
+                    });
+                }
+                if let Some(i) = remove {
+                    parts.path.remove(i);
+                    changed = true;
+                }
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.label("Query Parameters");
+                    if ui.button("+").clicked() {
+                        parts.query.push((String::new(), String::new()));
+                        changed = true;
+                    }
+                });
+                let mut remove = None;
+                egui::Grid::new("tool/encode_tool/url_query")
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for (i, (key, value)) in parts.query.iter_mut().enumerate() {
+                            changed |= ui.text_edit_singleline(key).changed();
+                            changed |= ui.text_edit_singleline(value).changed();
+                            if ui.small_button("-").clicked() {
+                                remove = Some(i);
+                            }
+                            ui.end_row();
+                        }
+                    });
+                if let Some(i) = remove {
+                    parts.query.remove(i);
+                    changed = true;
+                }
+            });
+        if changed {
+            match urls::build(parts) {
+                Ok(url) => self.set_output(url),
+                Err(e) => self.set_output(format!("Error: {}", e)),
+            }
         }
     }
 }
@@ -26,10 +540,30 @@ enum Catagory {
     Op,
     Basic,
     Encoding,
+    Detect,
+    Unicode,
+    Literal,
+    Url,
+    Transcode,
 }
 
 impl ToolComponent for Encoder {
     fn paint_ui(&mut self, ui: &mut egui::Ui, _ctx: &egui::Context) {
+        if let Some(recv) = &self.transcode_form.recv {
+            if let Ok(report) = recv.try_recv() {
+                let form = &mut self.transcode_form;
+                let msg_type = if !report.failed.is_empty() {
+                    MsgType::Error
+                } else if !report.lossy.is_empty() || !report.skipped.is_empty() {
+                    MsgType::Warning
+                } else {
+                    MsgType::Info
+                };
+                self.msg = Some(Msg::new(report.summary(form.options.lossy), msg_type));
+                form.report = Some(report);
+                form.recv = None;
+            }
+        }
         let width = ui.available_width();
         ui.horizontal(|ui| {
             let mut cursor = ui.cursor();
@@ -49,11 +583,16 @@ impl ToolComponent for Encoder {
             ui.allocate_new_ui(egui::UiBuilder::new().max_rect(cursor), |ui| {
                 ui.vertical(|ui| {
                     ui.label("Output");
-                    ui.add(
-                        egui::TextEdit::multiline(&mut self.output)
-                            .desired_width(f32::INFINITY)
-                            .desired_rows(10),
-                    );
+                    let edited = ui
+                        .add(
+                            egui::TextEdit::multiline(&mut self.output)
+                                .desired_width(f32::INFINITY)
+                                .desired_rows(10),
+                        )
+                        .changed();
+                    if edited {
+                        self.decoded = None;
+                    }
                 });
             });
         });
@@ -62,6 +601,15 @@ impl ToolComponent for Encoder {
             ui.radio_value(&mut self.selected_catagory, Catagory::Op, "Operation");
             ui.radio_value(&mut self.selected_catagory, Catagory::Basic, "Basic");
             ui.radio_value(&mut self.selected_catagory, Catagory::Encoding, "Encoding");
+            ui.radio_value(&mut self.selected_catagory, Catagory::Detect, "Detect");
+            ui.radio_value(&mut self.selected_catagory, Catagory::Unicode, "Unicode");
+            ui.radio_value(&mut self.selected_catagory, Catagory::Literal, "Literal");
+            ui.radio_value(&mut self.selected_catagory, Catagory::Url, "URL");
+            ui.radio_value(
+                &mut self.selected_catagory,
+                Catagory::Transcode,
+                "Transcode",
+            );
         });
         match self.selected_catagory {
             Catagory::Op => {
@@ -70,10 +618,11 @@ impl ToolComponent for Encoder {
                         self.input.clear();
                     }
                     if ui.button("Clear Output").clicked() {
-                        self.output.clear();
+                        self.set_output(String::new());
                     }
                     if ui.button("Swap").clicked() {
                         std::mem::swap(&mut self.input, &mut self.output);
+                        self.decoded = None;
                     }
                     if ui.button("Copy Input").clicked() {
                         ui.output_mut(|o| {
@@ -88,133 +637,484 @@ impl ToolComponent for Encoder {
                 });
             }
             Catagory::Basic => {
+                ui.horizontal(|ui| {
+                    ui.label("Invalid UTF-8 in decoded data");
+                    Self::trap_combo(ui, &mut self.trap);
+                });
                 ui.label("Base64");
                 ui.horizontal(|ui| {
                     if ui.button("Encode").clicked() {
-                        self.output = basic::base64_encode_std(&self.input);
+                        self.set_output(basic::base64_encode_std(&self.input));
                     }
                     if ui.button("Decode").clicked() {
-                        self.output = basic::base64_decode_std(&self.input);
+                        self.set_decoded(basic::base64_decode_std(&self.input, self.trap));
                     }
                     if ui.button("Encode URL").clicked() {
-                        self.output = basic::base64_encode_url(&self.input);
+                        self.set_output(basic::base64_encode_url(&self.input));
                     }
                     if ui.button("Decode URL").clicked() {
-                        self.output = basic::base64_decode_url(&self.input);
+                        self.set_decoded(basic::base64_decode_url(&self.input, self.trap));
                     }
                 });
                 ui.label("Base64 No Padding");
                 ui.horizontal(|ui| {
                     if ui.button("Encode").clicked() {
-                        self.output = basic::base64_encode_std_no_pad(&self.input);
+                        self.set_output(basic::base64_encode_std_no_pad(&self.input));
                     }
                     if ui.button("Decode").clicked() {
-                        self.output = basic::base64_decode_std_no_pad(&self.input);
+                        self.set_decoded(basic::base64_decode_std_no_pad(&self.input, self.trap));
                     }
                     if ui.button("Encode URL").clicked() {
-                        self.output = basic::base64_encode_url_no_pad(&self.input);
+                        self.set_output(basic::base64_encode_url_no_pad(&self.input));
                     }
                     if ui.button("Decode URL").clicked() {
-                        self.output = basic::base64_decode_url_no_pad(&self.input);
+                        self.set_decoded(basic::base64_decode_url_no_pad(&self.input, self.trap));
+                    }
+                });
+                ui.label("Base32/58/85/91/45, uuencode, yEnc");
+                ui.horizontal(|ui| {
+                    egui::ComboBox::from_id_salt("tool/encode_tool/binascii")
+                        .selected_text(self.scheme.name())
+                        .show_ui(ui, |ui| {
+                            for scheme in byte_tool::binascii::SCHEMES {
+                                ui.selectable_value(&mut self.scheme, scheme, scheme.name());
+                            }
+                        });
+                    if ui.button("Encode").clicked() {
+                        self.set_output(basic::binascii_encode(&self.input, self.scheme));
+                    }
+                    if ui.button("Decode").clicked() {
+                        self.set_decoded(basic::binascii_decode(
+                            &self.input,
+                            self.scheme,
+                            self.trap,
+                        ));
+                    }
+                    if ui
+                        .button("Auto Decode")
+                        .on_hover_text("Try every scheme and keep the first that decodes")
+                        .clicked()
+                    {
+                        let (scheme, result) = basic::binascii_decode_auto(&self.input, self.trap);
+                        if let Some(scheme) = scheme {
+                            self.scheme = scheme;
+                        }
+                        self.set_decoded(result);
                     }
                 });
                 ui.label("URL");
                 ui.horizontal(|ui| {
                     if ui.button("Encode").clicked() {
-                        self.output = basic::url_encode(&self.input);
+                        self.set_output(basic::url_encode(&self.input));
                     }
                     if ui.button("Decode").clicked() {
-                        self.output = basic::url_decode(&self.input);
+                        self.set_decoded(basic::url_decode(&self.input, self.trap));
                     }
                 });
                 ui.label("Hex");
                 ui.horizontal(|ui| {
                     if ui.button("Encode").clicked() {
-                        self.output = basic::hex_encode(&self.input);
+                        self.set_output(basic::hex_encode(&self.input));
                     }
                     if ui.button("Decode").clicked() {
-                        self.output = basic::hex_decode(&self.input);
+                        self.set_decoded(basic::hex_decode(&self.input, self.trap));
                     }
                 });
-            }
-            Catagory::Encoding => {
+                ui.label("Form (application/x-www-form-urlencoded)");
                 ui.horizontal(|ui| {
-                    ui.label("UTF-8");
-                    ui.horizontal(|ui| {
-                        if ui.button("To Hex").clicked() {
-                            self.output = encoding::utf8_to_hex(&self.input);
-                        }
-                        if ui.button("From Hex").clicked() {
-                            self.output = encoding::hex_to_utf8(&self.input);
-                        }
-                    });
+                    if ui.button("Encode").clicked() {
+                        self.set_output(basic::form_encode(&self.input));
+                    }
+                    if ui.button("Decode").clicked() {
+                        self.set_decoded(basic::form_decode(&self.input, self.trap));
+                    }
                 });
+                ui.label("HTML/XML Entities");
                 ui.horizontal(|ui| {
-                    ui.label("UTF-16LE");
-                    ui.horizontal(|ui| {
-                        if ui.button("To Hex").clicked() {
-                            self.output = encoding::utf16le_to_hex(&self.input);
-                        }
-                        if ui.button("From Hex").clicked() {
-                            self.output = encoding::hex_to_utf16le(&self.input);
-                        }
-                    });
+                    if ui.button("Encode").clicked() {
+                        self.set_output(basic::html_encode(&self.input, false));
+                    }
+                    if ui
+                        .button("Encode Numeric")
+                        .on_hover_text("Also write non-ASCII characters as &#x..;")
+                        .clicked()
+                    {
+                        self.set_output(basic::html_encode(&self.input, true));
+                    }
+                    if ui.button("Decode").clicked() {
+                        self.set_output(basic::html_decode(&self.input));
+                    }
                 });
+                ui.label("Quoted-Printable");
                 ui.horizontal(|ui| {
-                    ui.label("UTF-16BE");
-                    ui.horizontal(|ui| {
-                        if ui.button("To Hex").clicked() {
-                            self.output = encoding::utf16be_to_hex(&self.input);
-                        }
-                        if ui.button("From Hex").clicked() {
-                            self.output = encoding::hex_to_utf16be(&self.input);
-                        }
-                    });
+                    if ui.button("Encode").clicked() {
+                        self.set_output(basic::qp_encode(&self.input));
+                    }
+                    if ui.button("Decode").clicked() {
+                        self.set_decoded(basic::qp_decode(&self.input, self.trap));
+                    }
                 });
+                ui.label("MIME Encoded-Word (RFC 2047)");
                 ui.horizontal(|ui| {
-                    ui.label("GBK");
-                    ui.horizontal(|ui| {
-                        if ui.button("To Hex").clicked() {
-                            self.output = encoding::gbk_to_hex(&self.input);
-                        }
-                        if ui.button("From Hex").clicked() {
-                            self.output = encoding::hex_to_gbk(&self.input);
-                        }
-                    });
+                    if ui.button("Encode B").clicked() {
+                        self.set_output(basic::rfc2047_encode(&self.input, false));
+                    }
+                    if ui.button("Encode Q").clicked() {
+                        self.set_output(basic::rfc2047_encode(&self.input, true));
+                    }
+                    if ui.button("Decode").clicked() {
+                        let output = match basic::rfc2047_decode(&self.input, self.trap) {
+                            Ok(v) => v,
+                            Err(e) => format!("Error: {}", e),
+                        };
+                        self.set_output(output);
+                    }
                 });
+                ui.label("Punycode / IDNA, one per line");
                 ui.horizontal(|ui| {
-                    ui.label("Shift-JIS");
-                    ui.horizontal(|ui| {
-                        if ui.button("To Hex").clicked() {
-                            self.output = encoding::shiftjis_to_hex(&self.input);
+                    if ui.button("Encode").clicked() {
+                        self.set_output(basic::punycode_encode(&self.input));
+                    }
+                    if ui.button("Decode").clicked() {
+                        self.set_output(basic::punycode_decode(&self.input));
+                    }
+                    if ui.button("Domain To ASCII").clicked() {
+                        self.set_output(basic::idna_to_ascii(&self.input));
+                    }
+                    if ui.button("Domain To Unicode").clicked() {
+                        self.set_output(basic::idna_to_unicode(&self.input));
+                    }
+                });
+                ui.label("ROT13 / ROT47");
+                ui.horizontal(|ui| {
+                    if ui
+                        .button("ROT13")
+                        .on_hover_text("Encoding and decoding are the same")
+                        .clicked()
+                    {
+                        self.set_output(basic::rot13(&self.input));
+                    }
+                    if ui
+                        .button("ROT47")
+                        .on_hover_text("Encoding and decoding are the same")
+                        .clicked()
+                    {
+                        self.set_output(basic::rot47(&self.input));
+                    }
+                });
+                ui.label("Morse");
+                ui.horizontal(|ui| {
+                    if ui.button("Encode").clicked() {
+                        self.set_output(basic::morse_encode(&self.input));
+                    }
+                    if ui.button("Decode").clicked() {
+                        self.set_output(basic::morse_decode(&self.input));
+                    }
+                });
+                ui.label("Binary / Octal / Decimal Bytes");
+                ui.horizontal(|ui| {
+                    for (radix, name) in [(2, "Binary"), (8, "Octal"), (10, "Decimal")] {
+                        if ui.button(format!("{} Encode", name)).clicked() {
+                            self.set_output(basic::radix_encode(&self.input, radix));
                         }
-                        if ui.button("From Hex").clicked() {
-                            self.output = encoding::hex_to_shiftjis(&self.input);
+                        if ui.button(format!("{} Decode", name)).clicked() {
+                            self.set_decoded(basic::radix_decode(&self.input, radix, self.trap));
                         }
+                    }
+                });
+            }
+            Catagory::Encoding => {
+                ui.horizontal(|ui| {
+                    ui.label("Search");
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.encoding_filter)
+                            .hint_text("name or label, e.g. sjis, latin1"),
+                    );
+                    ui.label(self.encoding);
+                });
+                egui::ScrollArea::vertical()
+                    .id_salt("tool/encode_tool/encoding")
+                    .max_height(160.0)
+                    .show(ui, |ui| {
+                        ui.horizontal_wrapped(|ui| {
+                            for name in encoding::search(&self.encoding_filter) {
+                                ui.selectable_value(&mut self.encoding, name, name);
+                            }
+                        });
                     });
+                ui.horizontal(|ui| {
+                    if ui.button("To Hex").clicked() {
+                        self.set_output(encoding::text_to_hex(
+                            &self.input,
+                            self.encoding,
+                            self.bom,
+                        ));
+                    }
+                    if ui.button("From Hex").clicked() {
+                        self.set_decoded(encoding::hex_to_text(
+                            &self.input,
+                            self.encoding,
+                            self.trap,
+                        ));
+                    }
+                    Self::trap_combo(ui, &mut self.trap);
+                    if encoding::bom(self.encoding).is_some() {
+                        ui.checkbox(&mut self.bom, "BOM")
+                            .on_hover_text("Prepend the byte order mark when encoding");
+                    }
                 });
+            }
+            Catagory::Detect => {
                 ui.horizontal(|ui| {
-                    ui.label("Big5");
-                    ui.horizontal(|ui| {
-                        if ui.button("To Hex").clicked() {
-                            self.output = encoding::big5_to_hex(&self.input);
-                        }
-                        if ui.button("From Hex").clicked() {
-                            self.output = encoding::hex_to_big5(&self.input);
+                    if ui.button("Detect Hex Input").clicked() {
+                        let digits: String =
+                            self.input.chars().filter(|c| !c.is_whitespace()).collect();
+                        match hex::decode(digits) {
+                            Ok(v) => self.set_detect_data(v),
+                            Err(e) => {
+                                self.msg = Some(Msg::new(format!("Error: {}", e), MsgType::Error))
+                            }
                         }
+                    }
+                    if ui.button("Load File").clicked() {
+                        self.state = FileState::LoadFile;
+                    }
+                    if ui
+                        .button("Save Output")
+                        .on_hover_text("Save Output as UTF-8, e.g. a re-decoded log")
+                        .clicked()
+                    {
+                        self.state = FileState::SaveOutput;
+                    }
+                    if !self.detect_data.is_empty() {
+                        ui.label(format!("{} bytes", self.detect_data.len()));
+                    }
+                });
+                let mut decode = None;
+                egui::ScrollArea::vertical()
+                    .id_salt("tool/encode_tool/detect")
+                    .max_height(240.0)
+                    .show(ui, |ui| {
+                        egui::Grid::new("tool/encode_tool/detect_grid")
+                            .striped(true)
+                            .show(ui, |ui| {
+                                for guess in &self.guesses {
+                                    if ui
+                                        .button("Decode")
+                                        .on_hover_text("Decode all bytes into Output")
+                                        .clicked()
+                                    {
+                                        decode = Some(guess.name);
+                                    }
+                                    ui.label(guess.name);
+                                    ui.label(format!("{}%", guess.score));
+                                    match (guess.bom, guess.errors) {
+                                        (true, _) => ui.label("BOM"),
+                                        (false, 0) => ui.label(""),
+                                        (false, n) => ui.label(format!("{} errors", n)),
+                                    };
+                                    ui.monospace(&guess.preview);
+                                    ui.end_row();
+                                }
+                            });
                     });
+                if let Some(name) = decode {
+                    match detect::decode(&self.detect_data, name) {
+                        Ok((text, 0)) => self.set_output(text),
+                        Ok((text, errors)) => {
+                            self.set_output(text);
+                            self.msg = Some(Msg::new(
+                                format!("{} invalid sequences replaced with U+FFFD", errors),
+                                MsgType::Warning,
+                            ));
+                        }
+                        Err(e) => {
+                            self.msg = Some(Msg::new(format!("Error: {}", e), MsgType::Error))
+                        }
+                    }
+                }
+            }
+            Catagory::Unicode => {
+                if self.inspected != self.input {
+                    self.inspected = self.input.clone();
+                    self.chars = unicode::inspect(&self.input);
+                    self.unicode_warnings = unicode::warnings(&self.input, &self.chars);
+                }
+                ui.horizontal(|ui| {
+                    for form in unicode::FORMS {
+                        if ui
+                            .button(form.name())
+                            .on_hover_text(format!(
+                                "Normalize Input to {} into Output",
+                                form.name()
+                            ))
+                            .clicked()
+                        {
+                            self.set_output(form.apply(&self.input));
+                        }
+                    }
+                    let forms: Vec<&str> = unicode::FORMS
+                        .iter()
+                        .filter(|f| f.is_normalized(&self.input))
+                        .map(|f| f.name())
+                        .collect();
+                    if !forms.is_empty() {
+                        ui.label(format!("Input is in {}", forms.join(", ")));
+                    }
                 });
+                let total = self.input.chars().count();
+                if total > self.chars.len() {
+                    ui.label(format!(
+                        "Showing the first {} of {} characters",
+                        self.chars.len(),
+                        total
+                    ));
+                }
+                for warning in &self.unicode_warnings {
+                    ui.colored_label(ui.visuals().warn_fg_color, warning);
+                }
+                Self::char_table(ui, &self.chars);
+            }
+            Catagory::Literal => {
                 ui.horizontal(|ui| {
-                    ui.label("ascii");
-                    ui.horizontal(|ui| {
-                        if ui.button("To Hex").clicked() {
-                            self.output = encoding::ascii_to_hex(&self.input);
+                    egui::ComboBox::from_id_salt("tool/encode_tool/literal")
+                        .selected_text(self.literal_lang.name())
+                        .show_ui(ui, |ui| {
+                            for lang in literal::LANGS {
+                                ui.selectable_value(&mut self.literal_lang, lang, lang.name());
+                            }
+                        });
+                    if ui
+                        .button("Escape")
+                        .on_hover_text("Write Input as a quoted literal")
+                        .clicked()
+                    {
+                        let raw = self.literal_raw;
+                        let output = match literal::escape(&self.input, self.literal_lang, raw) {
+                            Ok(v) => v,
+                            Err(e) => format!("Error: {}", e),
+                        };
+                        self.set_output(output);
+                    }
+                    if ui
+                        .button("Unescape")
+                        .on_hover_text("Read a literal, with or without its quotes")
+                        .clicked()
+                    {
+                        let output = match literal::unescape(&self.input, self.literal_lang) {
+                            Ok(v) => v,
+                            Err(e) => format!("Error: {}", e),
+                        };
+                        self.set_output(output);
+                    }
+                    if self.literal_lang.has_raw() {
+                        ui.checkbox(&mut self.literal_raw, "Raw String");
+                    }
+                });
+            }
+            Catagory::Url => self.url_ui(ui),
+            Catagory::Transcode => self.transcode_ui(ui),
+        }
+        if let (Catagory::Basic | Catagory::Encoding, Some(decoded)) =
+            (&self.selected_catagory, &self.decoded)
+        {
+            ui.separator();
+            Self::show_invalid(ui, decoded);
+        }
+    }
+    fn get_msg(&mut self) -> Option<Msg> {
+        self.msg.take()
+    }
+    fn get_file_op(&mut self) -> Option<(FileOp, u8)> {
+        match self.state {
+            FileState::LoadFile => {
+                self.state = FileState::None;
+                Some((
+                    FileOp {
+                        title: "Load File".to_string(),
+                        filter: Vec::new(),
+                        mode: FileOpMode::Open,
+                    },
+                    LOAD_FILE,
+                ))
+            }
+            FileState::SaveOutput => {
+                self.state = FileState::None;
+                Some((
+                    FileOp {
+                        title: "Save Output".to_string(),
+                        filter: Vec::new(),
+                        mode: FileOpMode::Save,
+                    },
+                    SAVE_OUTPUT,
+                ))
+            }
+            FileState::ScanDir => {
+                self.state = FileState::None;
+                Some((
+                    FileOp {
+                        title: "Scan Dir".to_string(),
+                        filter: Vec::new(),
+                        mode: FileOpMode::Dir,
+                    },
+                    SCAN_DIR,
+                ))
+            }
+            FileState::OutputDir => {
+                self.state = FileState::None;
+                Some((
+                    FileOp {
+                        title: "Output Dir".to_string(),
+                        filter: Vec::new(),
+                        mode: FileOpMode::Dir,
+                    },
+                    OUTPUT_DIR,
+                ))
+            }
+            FileState::None => None,
+        }
+    }
+    fn set_file_op(&mut self, file_path: Option<(path::PathBuf, u8)>) {
+        if let Some((fp, id)) = file_path {
+            match id {
+                LOAD_FILE => match std::fs::read(fp) {
+                    Ok(v) => self.set_detect_data(v),
+                    Err(e) => {
+                        self.msg = Some(Msg::new(format!("Error: {}", e), MsgType::Error));
+                    }
+                },
+                SAVE_OUTPUT => match std::fs::write(fp, &self.output) {
+                    Ok(_) => {
+                        self.msg = Some(Msg::new("Save Success".to_string(), MsgType::Info));
+                    }
+                    Err(e) => {
+                        self.msg = Some(Msg::new(format!("Error: {}", e), MsgType::Error));
+                    }
+                },
+                SCAN_DIR => {
+                    let mut files = Vec::new();
+                    let recursive = self.transcode_form.recursive;
+                    match crate::tools::batch_process::scan_dir(&fp, recursive, &mut files) {
+                        Ok(_) => {
+                            for file in &files {
+                                if !self.input.is_empty() && !self.input.ends_with('\n') {
+                                    self.input.push('\n');
+                                }
+                                self.input.push_str(&file.to_string_lossy());
+                            }
+                            self.msg = Some(Msg::new(
+                                format!("Listed {} files", files.len()),
+                                MsgType::Info,
+                            ));
+                            self.transcode_form.options.bases.push(fp);
                         }
-                        if ui.button("From Hex").clicked() {
-                            self.output = encoding::hex_to_ascii(&self.input);
+                        Err(e) => {
+                            self.msg = Some(Msg::new(format!("Error: {}", e), MsgType::Error));
                         }
-                    });
-                });
+                    }
+                }
+                OUTPUT_DIR => self.transcode_form.options.out_dir = Some(fp),
+                _ => {}
             }
         }
     }
@@ -0,0 +1,316 @@
+use super::encoding;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum LineEnding {
+    Keep,
+    Lf,
+    CrLf,
+}
+
+pub const LINE_ENDINGS: [LineEnding; 3] = [LineEnding::Keep, LineEnding::Lf, LineEnding::CrLf];
+
+impl LineEnding {
+    pub fn name(&self) -> &'static str {
+        match self {
+            LineEnding::Keep => "Keep Line Endings",
+            LineEnding::Lf => "LF",
+            LineEnding::CrLf => "CRLF",
+        }
+    }
+    fn apply(&self, text: String) -> String {
+        match self {
+            LineEnding::Keep => text,
+            LineEnding::Lf => text.replace("\r\n", "\n"),
+            LineEnding::CrLf => text.replace("\r\n", "\n").replace('\n', "\r\n"),
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Hash, Clone, Debug)]
+pub struct Options {
+    pub from: &'static str,
+    pub to: &'static str,
+    /// Write a BOM when `to` has one
+    pub bom: bool,
+    pub line_ending: LineEnding,
+    /// Write files with invalid bytes or unmappable characters anyway, with replacements
+    pub lossy: bool,
+    /// Directory the files below `bases` are mirrored into, required as sources are never
+    /// overwritten
+    pub out_dir: Option<PathBuf>,
+    /// Directories the files were scanned from; a file keeps its path below the deepest
+    /// one it is in, other files only keep their name
+    pub bases: Vec<PathBuf>,
+}
+
+/// Where conversion lost data, with the line the first loss is on
+#[derive(PartialEq, Eq, Hash, Clone, Debug)]
+pub struct Loss {
+    pub invalid: usize,
+    pub unmappable: usize,
+    pub line: usize,
+}
+
+impl Loss {
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if self.invalid > 0 {
+            parts.push(format!("{} invalid byte sequences", self.invalid));
+        }
+        if self.unmappable > 0 {
+            parts.push(format!("{} unmappable characters", self.unmappable));
+        }
+        format!("{}, first on line {}", parts.join(", "), self.line)
+    }
+}
+
+/// Converts the contents of one file, reporting the loss instead of failing on it
+pub fn convert(bytes: Vec<u8>, options: &Options) -> Result<(Vec<u8>, Option<Loss>), String> {
+    let decoded = encoding::decode_text(bytes, options.from, encoding::Trap::Replace)?;
+    let text = options.line_ending.apply(decoded.text);
+    let (encoded, unmappable) = encoding::encode_lossy(&text, options.to)?;
+    let mut out = match encoding::bom(options.to) {
+        Some(bom) if options.bom => bom.to_vec(),
+        _ => Vec::new(),
+    };
+    out.extend(encoded);
+    let line =
+        |data: &[u8], offset: usize| data[..offset].iter().filter(|b| **b == b'\n').count() + 1;
+    let loss = match (decoded.invalid.first(), unmappable.first()) {
+        (None, None) => None,
+        (first_invalid, first_unmappable) => Some(Loss {
+            invalid: decoded.invalid.len(),
+            unmappable: unmappable.len(),
+            line: match first_invalid {
+                Some(range) => line(&decoded.bytes, range.start),
+                None => line(text.as_bytes(), first_unmappable.map_or(0, |r| r.start)),
+            },
+        }),
+    };
+    Ok((out, loss))
+}
+
+#[derive(PartialEq, Eq, Hash, Clone, Debug, Default)]
+pub struct Report {
+    pub converted: usize,
+    /// Files that lost data, written or not depending on `Options::lossy`
+    pub lossy: Vec<(String, Loss)>,
+    /// Files that are valid UTF-8 already, left out of a conversion from another encoding
+    pub skipped: Vec<String>,
+    pub failed: Vec<String>,
+}
+
+impl Report {
+    pub fn summary(&self, written_lossy: bool) -> String {
+        format!(
+            "{} converted, {} that lost data{}, {} already UTF-8, {} failed",
+            self.converted,
+            self.lossy.len(),
+            if written_lossy { "" } else { " left untouched" },
+            self.skipped.len(),
+            self.failed.len()
+        )
+    }
+}
+
+fn target(path: &Path, bases: &[PathBuf], out_dir: &Path) -> PathBuf {
+    let relative = bases
+        .iter()
+        .filter_map(|base| path.strip_prefix(base).ok())
+        .min_by_key(|relative| relative.components().count())
+        .or(path.file_name().map(Path::new))
+        .unwrap_or(path);
+    out_dir.join(relative)
+}
+
+/// Pairs each file with where its conversion goes, refusing plans that would write one
+/// output twice or replace a source file, before anything is written
+pub fn plan(files: &[PathBuf], options: &Options) -> Result<Vec<(PathBuf, PathBuf)>, String> {
+    let out_dir = options
+        .out_dir
+        .as_deref()
+        .ok_or("Choose an output directory, files are not overwritten in place".to_string())?;
+    let sources: HashSet<PathBuf> = files.iter().filter_map(|f| f.canonicalize().ok()).collect();
+    let mut targets: HashMap<PathBuf, &PathBuf> = HashMap::new();
+    let mut plan = Vec::new();
+    for file in files {
+        let target = target(file, &options.bases, out_dir);
+        if let Some(other) = targets.insert(target.clone(), file) {
+            return Err(format!(
+                "{} and {} would both be written to {}",
+                other.to_string_lossy(),
+                file.to_string_lossy(),
+                target.to_string_lossy()
+            ));
+        }
+        if target.canonicalize().is_ok_and(|t| sources.contains(&t)) {
+            return Err(format!(
+                "{} would overwrite a source file",
+                target.to_string_lossy()
+            ));
+        }
+        plan.push((file.clone(), target));
+    }
+    Ok(plan)
+}
+
+enum Outcome {
+    Converted,
+    Lossy(Loss),
+    /// Valid UTF-8 read as a legacy encoding, likely converted already
+    AlreadyUtf8,
+}
+
+fn run_one(source: &Path, target: &Path, options: &Options) -> Result<Outcome, String> {
+    let bytes = std::fs::read(source).map_err(|e| e.to_string())?;
+    // legacy multi-byte encodings read most UTF-8 without errors, as mojibake
+    if options.from != "UTF-8" && !bytes.is_ascii() && std::str::from_utf8(&bytes).is_ok() {
+        return Ok(Outcome::AlreadyUtf8);
+    }
+    let (out, loss) = convert(bytes, options)?;
+    if loss.is_none() || options.lossy {
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        std::fs::write(target, out).map_err(|e| e.to_string())?;
+    }
+    Ok(match loss {
+        Some(loss) => Outcome::Lossy(loss),
+        None => Outcome::Converted,
+    })
+}
+
+pub fn run(plan: &[(PathBuf, PathBuf)], options: &Options) -> Report {
+    let mut report = Report::default();
+    for (source, target) in plan {
+        let name = source.to_string_lossy().to_string();
+        match run_one(source, target, options) {
+            Ok(Outcome::Converted) => report.converted += 1,
+            Ok(Outcome::Lossy(loss)) => {
+                if options.lossy {
+                    report.converted += 1;
+                }
+                report.lossy.push((name, loss));
+            }
+            Ok(Outcome::AlreadyUtf8) => report.skipped.push(name),
+            Err(e) => report.failed.push(format!("{}: {}", name, e)),
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn options(from: &'static str, to: &'static str) -> Options {
+        Options {
+            from,
+            to,
+            bom: false,
+            line_ending: LineEnding::Keep,
+            lossy: false,
+            out_dir: None,
+            bases: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_convert() {
+        let gbk = encoding::encode("中文\r\n第二行\n", "GBK", false).unwrap();
+        let mut opts = options("GBK", "UTF-8");
+        opts.bom = true;
+        opts.line_ending = LineEnding::Lf;
+        let (utf8, loss) = convert(gbk.clone(), &opts).unwrap();
+        assert_eq!(utf8, "\u{FEFF}中文\n第二行\n".as_bytes());
+        assert!(loss.is_none());
+        // and back, dropping the BOM
+        let mut back = options("UTF-8", "GBK");
+        back.line_ending = LineEnding::CrLf;
+        let (out, loss) = convert(utf8, &back).unwrap();
+        assert_eq!(
+            out,
+            encoding::encode("中文\r\n第二行\r\n", "GBK", false).unwrap()
+        );
+        assert!(loss.is_none());
+    }
+
+    #[test]
+    fn test_loss() {
+        let (out, loss) = convert(
+            "ok\nあ 😀 x\n".as_bytes().to_vec(),
+            &options("UTF-8", "Big5"),
+        )
+        .unwrap();
+        let loss = loss.unwrap();
+        assert_eq!(loss.unmappable, 1);
+        assert_eq!(loss.line, 2);
+        assert_eq!(
+            out,
+            encoding::encode("ok\nあ ? x\n", "Big5", false).unwrap()
+        );
+        let (_, loss) = convert(b"a\n\n\xffb".to_vec(), &options("Shift_JIS", "UTF-8")).unwrap();
+        assert_eq!(
+            loss.unwrap(),
+            Loss {
+                invalid: 1,
+                unmappable: 0,
+                line: 3
+            }
+        );
+    }
+
+    #[test]
+    fn test_run() {
+        let dir = std::env::temp_dir().join(format!("transcode_test_{}", std::process::id()));
+        let out_dir = dir.join("out");
+        std::fs::create_dir_all(dir.join("sub")).unwrap();
+        let good = dir.join("sub").join("good.txt");
+        let bad = dir.join("bad.txt");
+        let done = dir.join("done.txt");
+        std::fs::write(
+            &good,
+            encoding::encode("日本語", "Shift_JIS", false).unwrap(),
+        )
+        .unwrap();
+        std::fs::write(&bad, b"\x82").unwrap();
+        std::fs::write(&done, "日本語").unwrap();
+        let mut opts = options("Shift_JIS", "UTF-8");
+        let files = [good.clone(), bad.clone(), done.clone()];
+        assert!(plan(&files, &opts).is_err());
+        opts.out_dir = Some(out_dir.clone());
+        opts.bases = vec![dir.clone()];
+        let report = run(&plan(&files, &opts).unwrap(), &opts);
+        assert_eq!(report.converted, 1);
+        assert_eq!(report.lossy.len(), 1);
+        assert_eq!(report.skipped, vec![done.to_string_lossy().to_string()]);
+        assert_eq!(
+            std::fs::read_to_string(out_dir.join("sub").join("good.txt")).unwrap(),
+            "日本語"
+        );
+        assert!(!out_dir.join("bad.txt").exists());
+        let report = run(&plan(&[dir.join("missing.txt")], &opts).unwrap(), &opts);
+        assert_eq!(report.failed.len(), 1);
+        // each file keeps its path below the deepest scanned directory it is in
+        let other = std::env::temp_dir().join(format!("transcode_other_{}", std::process::id()));
+        std::fs::create_dir_all(other.join("sub")).unwrap();
+        std::fs::write(other.join("sub").join("x.txt"), b"x").unwrap();
+        opts.bases = vec![dir.clone(), other.clone(), dir.join("sub")];
+        let planned = plan(&[good.clone(), other.join("sub").join("x.txt")], &opts).unwrap();
+        assert_eq!(planned[0].1, out_dir.join("good.txt"));
+        assert_eq!(planned[1].1, out_dir.join("sub").join("x.txt"));
+        std::fs::remove_dir_all(other).unwrap();
+        // without a base, files are flattened by name and may collide
+        std::fs::write(dir.join("good.txt"), b"x").unwrap();
+        opts.bases.clear();
+        let err = plan(&[good.clone(), dir.join("good.txt")], &opts).unwrap_err();
+        assert!(err.contains("would both be written"), "{}", err);
+        // nor may an output replace a source
+        opts.out_dir = Some(dir.join("sub"));
+        let err = plan(&[good], &opts).unwrap_err();
+        assert!(err.contains("overwrite a source"), "{}", err);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
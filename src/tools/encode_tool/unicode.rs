@@ -0,0 +1,264 @@
+use unicode_general_category::{get_general_category, GeneralCategory};
+use unicode_normalization::UnicodeNormalization;
+use unicode_script::{Script, UnicodeScript};
+use unicode_security::confusable_detection::skeleton;
+use unicode_security::MixedScript;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Characters inspected at most, the rest of Input is only counted
+pub const LIMIT: usize = 100_000;
+
+#[derive(PartialEq, Eq, Hash, Clone, Copy)]
+pub enum Form {
+    Nfc,
+    Nfd,
+    Nfkc,
+    Nfkd,
+}
+
+pub const FORMS: [Form; 4] = [Form::Nfc, Form::Nfd, Form::Nfkc, Form::Nfkd];
+
+impl Form {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Form::Nfc => "NFC",
+            Form::Nfd => "NFD",
+            Form::Nfkc => "NFKC",
+            Form::Nfkd => "NFKD",
+        }
+    }
+    pub fn apply(&self, text: &str) -> String {
+        match self {
+            Form::Nfc => text.nfc().collect(),
+            Form::Nfd => text.nfd().collect(),
+            Form::Nfkc => text.nfkc().collect(),
+            Form::Nfkd => text.nfkd().collect(),
+        }
+    }
+    pub fn is_normalized(&self, text: &str) -> bool {
+        match self {
+            Form::Nfc => unicode_normalization::is_nfc(text),
+            Form::Nfd => unicode_normalization::is_nfd(text),
+            Form::Nfkc => unicode_normalization::is_nfkc(text),
+            Form::Nfkd => unicode_normalization::is_nfkd(text),
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum Note {
+    Bidi,
+    Invisible,
+    Control,
+    Space,
+    /// Looks like the ASCII text of its confusable skeleton
+    Confusable,
+}
+
+#[derive(PartialEq, Eq, Hash, Clone)]
+pub struct CharInfo {
+    /// Index of the grapheme cluster, set on the cluster's first character only
+    pub grapheme: Option<usize>,
+    /// Byte offset in the UTF-8 text
+    pub offset: usize,
+    pub ch: char,
+    pub name: String,
+    pub category: &'static str,
+    pub script: &'static str,
+    pub utf8: String,
+    pub utf16: String,
+    pub note: Option<(Note, String)>,
+}
+
+impl CharInfo {
+    pub fn code_point(&self) -> String {
+        format!("U+{:04X}", self.ch as u32)
+    }
+    /// The character as it can be shown in a table cell
+    pub fn glyph(&self) -> String {
+        match self.note {
+            Some((Note::Bidi | Note::Invisible | Note::Control | Note::Space, _)) => String::new(),
+            _ if get_general_category(self.ch) == GeneralCategory::NonspacingMark => {
+                format!("\u{25CC}{}", self.ch)
+            }
+            _ => self.ch.to_string(),
+        }
+    }
+}
+
+fn note(c: char) -> Option<(Note, String)> {
+    match c {
+        '\u{202A}'..='\u{202E}'
+        | '\u{2066}'..='\u{2069}'
+        | '\u{200E}'
+        | '\u{200F}'
+        | '\u{061C}' => {
+            return Some((
+                Note::Bidi,
+                "bidi control, reorders the displayed text".to_string(),
+            ))
+        }
+        '\u{034F}'
+        | '\u{115F}'
+        | '\u{1160}'
+        | '\u{3164}'
+        | '\u{FFA0}'
+        | '\u{FE00}'..='\u{FE0F}'
+        | '\u{E0100}'..='\u{E01EF}' => {
+            return Some((Note::Invisible, "invisible".to_string()));
+        }
+        _ => {}
+    }
+    match get_general_category(c) {
+        GeneralCategory::Control if !matches!(c, '\t' | '\n' | '\r') => {
+            return Some((Note::Control, "control character".to_string()));
+        }
+        GeneralCategory::Format => {
+            return Some((Note::Invisible, "invisible format character".to_string()));
+        }
+        GeneralCategory::SpaceSeparator if c != ' ' => {
+            return Some((Note::Space, "not an ASCII space".to_string()));
+        }
+        _ => {}
+    }
+    if !c.is_ascii() {
+        let prototype: String = skeleton(c.encode_utf8(&mut [0; 4])).collect();
+        if prototype.is_ascii() && !prototype.trim().is_empty() {
+            return Some((Note::Confusable, format!("looks like \"{}\"", prototype)));
+        }
+    }
+    None
+}
+
+/// Breaks the first `LIMIT` characters of `text` down, in order
+pub fn inspect(text: &str) -> Vec<CharInfo> {
+    let mut chars = Vec::new();
+    for (grapheme, (start, cluster)) in text.grapheme_indices(true).enumerate() {
+        for (i, (offset, ch)) in cluster.char_indices().enumerate() {
+            if chars.len() == LIMIT {
+                return chars;
+            }
+            let name = match unicode_names2::name(ch) {
+                Some(name) => name.to_string(),
+                None => format!("<{:?}>", get_general_category(ch)),
+            };
+            let mut units = [0; 2];
+            chars.push(CharInfo {
+                grapheme: (i == 0).then_some(grapheme),
+                offset: start + offset,
+                ch,
+                name,
+                category: get_general_category(ch).abbreviation(),
+                script: ch.script().full_name(),
+                utf8: hex::encode_upper(ch.encode_utf8(&mut [0; 4])),
+                utf16: ch
+                    .encode_utf16(&mut units)
+                    .iter()
+                    .map(|u| format!("{:04X}", u))
+                    .collect::<Vec<_>>()
+                    .join(" "),
+                note: note(ch),
+            });
+        }
+    }
+    chars
+}
+
+/// Findings about the text as a whole: hidden characters and words mixing scripts
+pub fn warnings(text: &str, chars: &[CharInfo]) -> Vec<String> {
+    let mut warnings = Vec::new();
+    let count = |kind| {
+        chars
+            .iter()
+            .filter(|c| c.note.as_ref().is_some_and(|n| n.0 == kind))
+            .count()
+    };
+    let bidi = count(Note::Bidi);
+    if bidi > 0 {
+        warnings.push(format!(
+            "{} bidi control characters, the displayed order may differ from the stored one",
+            bidi
+        ));
+    }
+    let invisible = count(Note::Invisible) + count(Note::Control);
+    if invisible > 0 {
+        warnings.push(format!("{} invisible or control characters", invisible));
+    }
+    let confusable = count(Note::Confusable);
+    if confusable > 0 {
+        warnings.push(format!("{} characters that look like ASCII", confusable));
+    }
+    for word in text.unicode_words().take(LIMIT) {
+        if word.is_single_script() {
+            continue;
+        }
+        let mut scripts: Vec<&str> = Vec::new();
+        for c in word.chars() {
+            let script = c.script();
+            if !matches!(script, Script::Common | Script::Inherited | Script::Unknown)
+                && !scripts.contains(&script.full_name())
+            {
+                scripts.push(script.full_name());
+            }
+        }
+        warnings.push(format!("\"{}\" mixes {} scripts", word, scripts.join(", ")));
+    }
+    warnings
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_inspect() {
+        let chars = inspect("e\u{301}😀");
+        assert_eq!(chars.len(), 3);
+        assert_eq!(chars[0].grapheme, Some(0));
+        assert_eq!(chars[1].grapheme, None);
+        assert_eq!(chars[1].name, "COMBINING ACUTE ACCENT");
+        assert_eq!(chars[1].category, "Mn");
+        assert_eq!(chars[1].utf8, "CC81");
+        assert_eq!(chars[2].grapheme, Some(1));
+        assert_eq!(chars[2].offset, 3);
+        assert_eq!(chars[2].code_point(), "U+1F600");
+        assert_eq!(chars[2].utf16, "D83D DE00");
+        assert_eq!(inspect("a")[0].script, "Latin");
+    }
+
+    #[test]
+    fn test_notes() {
+        let notes: Vec<_> = inspect("a\u{202E}\u{200B}\u{A0}\u{430}\t")
+            .into_iter()
+            .map(|c| c.note.map(|n| n.0))
+            .collect();
+        assert_eq!(
+            notes,
+            vec![
+                None,
+                Some(Note::Bidi),
+                Some(Note::Invisible),
+                Some(Note::Space),
+                Some(Note::Confusable),
+                None
+            ]
+        );
+        let text = "p\u{430}ypal \u{202E}";
+        let warnings = warnings(text, &inspect(text));
+        assert_eq!(warnings.len(), 3);
+        assert_eq!(
+            warnings[2],
+            "\"p\u{430}ypal\" mixes Latin, Cyrillic scripts"
+        );
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(Form::Nfd.apply("é"), "e\u{301}");
+        assert_eq!(Form::Nfc.apply("e\u{301}"), "é");
+        assert_eq!(Form::Nfkc.apply("ﬁ²"), "fi2");
+        assert_eq!(Form::Nfkd.apply("ǅ"), "Dz\u{30C}");
+        assert!(Form::Nfc.is_normalized("é"));
+        assert!(!Form::Nfd.is_normalized("é"));
+    }
+}
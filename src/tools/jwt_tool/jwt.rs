@@ -0,0 +1,455 @@
+use super::byte_tool::pubkey::{self, PrivateKey, PublicKey};
+use data_encoding::{BASE64, BASE64URL_NOPAD};
+use digest::const_oid::AssociatedOid;
+use digest::core_api::BlockSizeUser;
+use digest::{Digest, FixedOutputReset};
+use hmac::{Mac, SimpleHmac};
+use rand_core::OsRng;
+use rsa::pkcs8::{DecodePrivateKey, DecodePublicKey};
+use rsa::signature::{RandomizedSigner, SignatureEncoding, Signer, Verifier};
+use serde_json::Value;
+
+pub const ALGORITHMS: [&str; 14] = [
+    "HS256", "HS384", "HS512", "RS256", "RS384", "RS512", "PS256", "PS384", "PS512", "ES256",
+    "ES256K", "ES384", "ES512", "EdDSA",
+];
+
+/// Claims holding NumericDate timestamps
+const TIME_CLAIMS: [&str; 4] = ["exp", "nbf", "iat", "auth_time"];
+
+#[derive(PartialEq, Clone)]
+pub struct Token {
+    pub header: Value,
+    pub payload: Value,
+    pub alg: String,
+    /// `header.payload` as it appears in the token, what the signature covers
+    signing_input: String,
+    signature: Vec<u8>,
+}
+
+fn segment(part: &str, name: &str) -> Result<Vec<u8>, String> {
+    BASE64URL_NOPAD
+        .decode(part.trim_end_matches('=').as_bytes())
+        .map_err(|e| format!("{} is not base64url: {}", name, e))
+}
+
+pub fn decode(token: &str) -> Result<Token, String> {
+    let token: String = token.chars().filter(|c| !c.is_whitespace()).collect();
+    let parts: Vec<&str> = token.split('.').collect();
+    match parts.len() {
+        3 => {}
+        5 => return Err("5 segments, this is an encrypted JWE, not a signed JWT".to_string()),
+        n => return Err(format!("{} segments, a JWT has 3", n)),
+    }
+    let header: Value = serde_json::from_slice(&segment(parts[0], "header")?)
+        .map_err(|e| format!("header is not JSON: {}", e))?;
+    let alg = match header.get("alg") {
+        Some(Value::String(alg)) => alg.clone(),
+        _ => return Err("header has no alg".to_string()),
+    };
+    let payload = segment(parts[1], "payload")?;
+    // JWS payloads need not be JSON, show those as a string
+    let payload = serde_json::from_slice(&payload)
+        .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(&payload).into_owned()));
+    Ok(Token {
+        header,
+        payload,
+        alg,
+        signing_input: format!("{}.{}", parts[0], parts[1]),
+        signature: segment(parts[2], "signature")?,
+    })
+}
+
+/// Reads an HMAC secret, base64 or base64url if `base64`
+fn secret(key: &str, base64: bool) -> Result<Vec<u8>, String> {
+    if !base64 {
+        return Ok(key.as_bytes().to_vec());
+    }
+    let key = key.trim().trim_end_matches('=');
+    BASE64URL_NOPAD
+        .decode(key.as_bytes())
+        .or_else(|_| {
+            BASE64.decode(format!("{}{}", key, "=".repeat((4 - key.len() % 4) % 4)).as_bytes())
+        })
+        .map_err(|e| format!("secret is not base64: {}", e))
+}
+
+// HMAC accepts keys of any length
+fn hmac_sign<D: Digest + BlockSizeUser>(secret: &[u8], message: &[u8]) -> Vec<u8> {
+    SimpleHmac::<D>::new_from_slice(secret)
+        .unwrap()
+        .chain_update(message)
+        .finalize()
+        .into_bytes()
+        .to_vec()
+}
+
+/// Compares in constant time
+fn hmac_verify<D: Digest + BlockSizeUser>(secret: &[u8], message: &[u8], tag: &[u8]) -> bool {
+    SimpleHmac::<D>::new_from_slice(secret)
+        .unwrap()
+        .chain_update(message)
+        .verify_slice(tag)
+        .is_ok()
+}
+
+fn rsa_sign<D>(key: &rsa::RsaPrivateKey, message: &[u8], pss: bool) -> Vec<u8>
+where
+    D: Digest + AssociatedOid + FixedOutputReset,
+{
+    match pss {
+        true => rsa::pss::BlindedSigningKey::<D>::new(key.clone())
+            .sign_with_rng(&mut OsRng, message)
+            .to_vec(),
+        false => rsa::pkcs1v15::SigningKey::<D>::new(key.clone())
+            .sign(message)
+            .to_vec(),
+    }
+}
+
+fn rsa_verify<D>(key: &rsa::RsaPublicKey, message: &[u8], signature: &[u8], pss: bool) -> bool
+where
+    D: Digest + AssociatedOid + FixedOutputReset,
+{
+    match pss {
+        true => rsa::pss::Signature::try_from(signature).is_ok_and(|s| {
+            rsa::pss::VerifyingKey::<D>::new(key.clone())
+                .verify(message, &s)
+                .is_ok()
+        }),
+        false => rsa::pkcs1v15::Signature::try_from(signature).is_ok_and(|s| {
+            rsa::pkcs1v15::VerifyingKey::<D>::new(key.clone())
+                .verify(message, &s)
+                .is_ok()
+        }),
+    }
+}
+
+// P-384 and P-521 are only used here, so their keys are read in PEM or JWK without Byte Tool
+fn p384_secret(key: &str) -> Result<p384::SecretKey, String> {
+    let key = key.trim();
+    match key.starts_with('{') {
+        true => p384::SecretKey::from_jwk_str(key).map_err(|e| e.to_string()),
+        false => p384::SecretKey::from_pkcs8_pem(key)
+            .or_else(|_| p384::SecretKey::from_sec1_pem(key))
+            .map_err(|_| "expected a P-384 private key in PEM or JWK".to_string()),
+    }
+}
+fn p384_public(key: &str) -> Result<p384::PublicKey, String> {
+    let key = key.trim();
+    match key.starts_with('{') {
+        true => p384::PublicKey::from_jwk_str(key).map_err(|e| e.to_string()),
+        false => p384::PublicKey::from_public_key_pem(key)
+            .or_else(|_| p384_secret(key).map(|k| k.public_key()))
+            .map_err(|_| "expected a P-384 key in PEM or JWK".to_string()),
+    }
+}
+fn p521_secret(key: &str) -> Result<p521::SecretKey, String> {
+    let key = key.trim();
+    match key.starts_with('{') {
+        true => p521::SecretKey::from_jwk_str(key).map_err(|e| e.to_string()),
+        false => p521::SecretKey::from_pkcs8_pem(key)
+            .or_else(|_| p521::SecretKey::from_sec1_pem(key))
+            .map_err(|_| "expected a P-521 private key in PEM or JWK".to_string()),
+    }
+}
+fn p521_public(key: &str) -> Result<p521::PublicKey, String> {
+    let key = key.trim();
+    match key.starts_with('{') {
+        true => p521::PublicKey::from_jwk_str(key).map_err(|e| e.to_string()),
+        false => p521::PublicKey::from_public_key_pem(key)
+            .or_else(|_| p521_secret(key).map(|k| k.public_key()))
+            .map_err(|_| "expected a P-521 key in PEM or JWK".to_string()),
+    }
+}
+
+fn wrong_key(alg: &str, expected: &str) -> String {
+    format!("{} needs {} key", alg, expected)
+}
+
+/// Signs `payload` with a `{"alg", "typ": "JWT"}` header. `key` is the HMAC secret for HS*,
+/// otherwise a private key in any format Byte Tool reads (PEM or JWK for ES384/ES512).
+pub fn sign(payload: &Value, alg: &str, key: &str, secret_base64: bool) -> Result<String, String> {
+    let header = serde_json::json!({"alg": alg, "typ": "JWT"});
+    let signing_input = format!(
+        "{}.{}",
+        BASE64URL_NOPAD.encode(header.to_string().as_bytes()),
+        BASE64URL_NOPAD.encode(payload.to_string().as_bytes())
+    );
+    let message = signing_input.as_bytes();
+    let signature = match alg {
+        "HS256" => hmac_sign::<sha2::Sha256>(&secret(key, secret_base64)?, message),
+        "HS384" => hmac_sign::<sha2::Sha384>(&secret(key, secret_base64)?, message),
+        "HS512" => hmac_sign::<sha2::Sha512>(&secret(key, secret_base64)?, message),
+        "ES384" => {
+            let signing = p384::ecdsa::SigningKey::from(p384_secret(key)?);
+            let signature: p384::ecdsa::Signature = signing.sign(message);
+            signature.to_bytes().to_vec()
+        }
+        "ES512" => {
+            let signing = p521::ecdsa::SigningKey::from_bytes(&p521_secret(key)?.to_bytes())
+                .map_err(|e| e.to_string())?;
+            let signature: p521::ecdsa::Signature = signing.sign(message);
+            signature.to_bytes().to_vec()
+        }
+        _ => match (alg, pubkey::import_private(key)?) {
+            ("RS256", PrivateKey::Rsa(k)) => rsa_sign::<sha2::Sha256>(&k, message, false),
+            ("RS384", PrivateKey::Rsa(k)) => rsa_sign::<sha2::Sha384>(&k, message, false),
+            ("RS512", PrivateKey::Rsa(k)) => rsa_sign::<sha2::Sha512>(&k, message, false),
+            ("PS256", PrivateKey::Rsa(k)) => rsa_sign::<sha2::Sha256>(&k, message, true),
+            ("PS384", PrivateKey::Rsa(k)) => rsa_sign::<sha2::Sha384>(&k, message, true),
+            ("PS512", PrivateKey::Rsa(k)) => rsa_sign::<sha2::Sha512>(&k, message, true),
+            ("ES256", PrivateKey::P256(k)) => {
+                let signature: p256::ecdsa::Signature =
+                    p256::ecdsa::SigningKey::from(k).sign(message);
+                signature.to_bytes().to_vec()
+            }
+            ("ES256K", PrivateKey::Secp256k1(k)) => {
+                let signature: k256::ecdsa::Signature =
+                    k256::ecdsa::SigningKey::from(k).sign(message);
+                signature.to_bytes().to_vec()
+            }
+            ("EdDSA", PrivateKey::Ed25519(k)) => k.sign(message).to_bytes().to_vec(),
+            ("RS256" | "RS384" | "RS512" | "PS256" | "PS384" | "PS512", _) => {
+                return Err(wrong_key(alg, "an RSA"))
+            }
+            ("ES256", _) => return Err(wrong_key(alg, "a P-256")),
+            ("ES256K", _) => return Err(wrong_key(alg, "a secp256k1")),
+            ("EdDSA", _) => return Err(wrong_key(alg, "an Ed25519")),
+            _ => return Err(format!("cannot sign with alg {}", alg)),
+        },
+    };
+    Ok(format!(
+        "{}.{}",
+        signing_input,
+        BASE64URL_NOPAD.encode(&signature)
+    ))
+}
+
+/// Checks the signature with the HMAC secret or a public (or private) key.
+/// Errors tell why a token cannot be checked, `Ok(false)` is a mismatch.
+pub fn verify(token: &Token, key: &str, secret_base64: bool) -> Result<bool, String> {
+    let alg = token.alg.as_str();
+    let message = token.signing_input.as_bytes();
+    let signature = token.signature.as_slice();
+    if alg.eq_ignore_ascii_case("none") {
+        return Err("alg is none, the token is not signed".to_string());
+    }
+    Ok(match alg {
+        "HS256" => hmac_verify::<sha2::Sha256>(&secret(key, secret_base64)?, message, signature),
+        "HS384" => hmac_verify::<sha2::Sha384>(&secret(key, secret_base64)?, message, signature),
+        "HS512" => hmac_verify::<sha2::Sha512>(&secret(key, secret_base64)?, message, signature),
+        "ES384" => {
+            let key = p384::ecdsa::VerifyingKey::from(p384_public(key)?);
+            p384::ecdsa::Signature::from_slice(signature)
+                .is_ok_and(|s| key.verify(message, &s).is_ok())
+        }
+        "ES512" => {
+            let key = p521::ecdsa::VerifyingKey::from_affine(*p521_public(key)?.as_affine())
+                .map_err(|e| e.to_string())?;
+            p521::ecdsa::Signature::from_slice(signature)
+                .is_ok_and(|s| key.verify(message, &s).is_ok())
+        }
+        _ => {
+            let public = pubkey::import_public(key)
+                .or_else(|_| pubkey::import_private(key).map(|k| k.public()))?;
+            match (alg, public) {
+                ("RS256", PublicKey::Rsa(k)) => {
+                    rsa_verify::<sha2::Sha256>(&k, message, signature, false)
+                }
+                ("RS384", PublicKey::Rsa(k)) => {
+                    rsa_verify::<sha2::Sha384>(&k, message, signature, false)
+                }
+                ("RS512", PublicKey::Rsa(k)) => {
+                    rsa_verify::<sha2::Sha512>(&k, message, signature, false)
+                }
+                ("PS256", PublicKey::Rsa(k)) => {
+                    rsa_verify::<sha2::Sha256>(&k, message, signature, true)
+                }
+                ("PS384", PublicKey::Rsa(k)) => {
+                    rsa_verify::<sha2::Sha384>(&k, message, signature, true)
+                }
+                ("PS512", PublicKey::Rsa(k)) => {
+                    rsa_verify::<sha2::Sha512>(&k, message, signature, true)
+                }
+                ("ES256", PublicKey::P256(k)) => p256::ecdsa::Signature::from_slice(signature)
+                    .is_ok_and(|s| {
+                        p256::ecdsa::VerifyingKey::from(k)
+                            .verify(message, &s)
+                            .is_ok()
+                    }),
+                ("ES256K", PublicKey::Secp256k1(k)) => {
+                    k256::ecdsa::Signature::from_slice(signature).is_ok_and(|s| {
+                        k256::ecdsa::VerifyingKey::from(k)
+                            .verify(message, &s)
+                            .is_ok()
+                    })
+                }
+                ("EdDSA", PublicKey::Ed25519(k)) => ed25519_dalek::Signature::from_slice(signature)
+                    .is_ok_and(|s| k.verify(message, &s).is_ok()),
+                ("RS256" | "RS384" | "RS512" | "PS256" | "PS384" | "PS512", _) => {
+                    return Err(wrong_key(alg, "an RSA"))
+                }
+                ("ES256", _) => return Err(wrong_key(alg, "a P-256")),
+                ("ES256K", _) => return Err(wrong_key(alg, "a secp256k1")),
+                ("EdDSA", _) => return Err(wrong_key(alg, "an Ed25519")),
+                _ => return Err(format!("unsupported alg {}", alg)),
+            }
+        }
+    })
+}
+
+/// "in 3 days" or "5 minutes ago"
+pub fn humanize(seconds: i64) -> String {
+    let abs = seconds.unsigned_abs();
+    let (n, unit) = match abs {
+        0..=59 => (abs, "second"),
+        60..=3599 => (abs / 60, "minute"),
+        3600..=86399 => (abs / 3600, "hour"),
+        86400..=31_535_999 => (abs / 86400, "day"),
+        _ => (abs / 31_536_000, "year"),
+    };
+    let plural = if n == 1 { "" } else { "s" };
+    match seconds >= 0 {
+        true => format!("in {} {}{}", n, unit, plural),
+        false => format!("{} {}{} ago", n, unit, plural),
+    }
+}
+
+/// Timestamp claims of `payload` as UTC dates relative to `now`
+pub fn times(payload: &Value, now: i64) -> Vec<(&'static str, String)> {
+    let mut times = Vec::new();
+    for claim in TIME_CLAIMS {
+        let Some(value) = payload.get(claim) else {
+            continue;
+        };
+        let text = match value.as_i64().or_else(|| value.as_f64().map(|f| f as i64)) {
+            Some(t) => match chrono::DateTime::from_timestamp(t, 0) {
+                Some(date) => format!(
+                    "{}, {}",
+                    date.format("%Y-%m-%d %H:%M:%S UTC"),
+                    humanize(t - now)
+                ),
+                None => format!("{} is out of range", t),
+            },
+            None => "not a number of seconds".to_string(),
+        };
+        times.push((claim, text));
+    }
+    times
+}
+
+/// Reasons not to trust the token, whatever its signature
+pub fn warnings(token: &Token, now: i64) -> Vec<String> {
+    let mut warnings = Vec::new();
+    if token.alg.eq_ignore_ascii_case("none") {
+        warnings.push("alg is none: the token is unsigned and must not be trusted".to_string());
+        if !token.signature.is_empty() {
+            warnings.push("alg is none but a signature is present".to_string());
+        }
+    }
+    let claim = |name| token.payload.get(name).and_then(Value::as_i64);
+    if let Some(exp) = claim("exp").filter(|exp| *exp <= now) {
+        warnings.push(format!("expired {}", humanize(exp.saturating_sub(now))));
+    }
+    if let Some(nbf) = claim("nbf").filter(|nbf| *nbf > now) {
+        warnings.push(format!(
+            "not valid yet, becomes valid {}",
+            humanize(nbf.saturating_sub(now))
+        ));
+    }
+    if let Some(iat) = claim("iat").filter(|iat| *iat > now) {
+        warnings.push(format!(
+            "issued in the future, {}",
+            humanize(iat.saturating_sub(now))
+        ));
+    }
+    warnings
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rsa::pkcs8::{EncodePrivateKey, LineEnding};
+
+    #[test]
+    fn test_hs256_vector() {
+        let token = decode(
+            "eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9.\
+             eyJzdWIiOiIxMjM0NTY3ODkwIiwibmFtZSI6IkpvaG4gRG9lIiwiaWF0IjoxNTE2MjM5MDIyfQ.\
+             SflKxwRJSMeKKF2QT4fwpMeJf36POk6yJV_adQssw5c",
+        )
+        .unwrap();
+        assert_eq!(token.alg, "HS256");
+        assert_eq!(token.payload["name"], "John Doe");
+        assert_eq!(verify(&token, "your-256-bit-secret", false), Ok(true));
+        assert_eq!(verify(&token, "wrong", false), Ok(false));
+        let base64 = BASE64.encode(b"your-256-bit-secret");
+        assert_eq!(verify(&token, &base64, true), Ok(true));
+        assert_eq!(
+            times(&token.payload, 1516239022 + 7200),
+            vec![("iat", "2018-01-18 01:30:22 UTC, 2 hours ago".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let payload = serde_json::json!({"sub": "42", "exp": 2000000000});
+        let rsa = pubkey::generate(pubkey::KeyType::Rsa, 2048).unwrap();
+        let rsa = pubkey::export_private(&rsa, pubkey::KeyFormat::Pem).unwrap();
+        let key = |key_type| {
+            let key = pubkey::generate(key_type, 0).unwrap();
+            pubkey::export_private(&key, pubkey::KeyFormat::Pem).unwrap()
+        };
+        let p384 = p384::SecretKey::random(&mut OsRng);
+        let p521 = p521::SecretKey::random(&mut OsRng);
+        let keys = [
+            ("HS256", "secret".to_string()),
+            ("HS384", "secret".to_string()),
+            ("HS512", "secret".to_string()),
+            ("RS256", rsa.clone()),
+            ("RS512", rsa.clone()),
+            ("PS384", rsa),
+            ("ES256", key(pubkey::KeyType::P256)),
+            ("ES256K", key(pubkey::KeyType::Secp256k1)),
+            (
+                "ES384",
+                p384.to_pkcs8_pem(LineEnding::LF).unwrap().to_string(),
+            ),
+            ("ES512", p521.to_jwk_string().to_string()),
+            ("EdDSA", key(pubkey::KeyType::Ed25519)),
+        ];
+        for (alg, key) in keys {
+            let token =
+                sign(&payload, alg, &key, false).unwrap_or_else(|e| panic!("{}: {}", alg, e));
+            let decoded = decode(&token).unwrap();
+            assert_eq!(decoded.alg, alg);
+            assert_eq!(decoded.payload, payload);
+            assert_eq!(verify(&decoded, &key, false), Ok(true), "{}", alg);
+            // a changed payload must not verify
+            let (_, signature) = token.rsplit_once('.').unwrap();
+            let forged = sign(&serde_json::json!({"sub": "0"}), "HS256", "x", false).unwrap();
+            let (forged, _) = forged.rsplit_once('.').unwrap();
+            let mut tampered = decode(&format!("{}.{}", forged, signature)).unwrap();
+            tampered.alg = alg.to_string();
+            assert_eq!(verify(&tampered, &key, false), Ok(false), "{}", alg);
+        }
+        assert!(sign(&payload, "ES256", &key(pubkey::KeyType::Ed25519), false).is_err());
+    }
+
+    #[test]
+    fn test_none() {
+        let token = decode("eyJhbGciOiJub25lIn0.eyJleHAiOjEwMDAsIm5iZiI6MzAwMH0.").unwrap();
+        assert!(verify(&token, "", false).is_err());
+        let warnings = warnings(&token, 2000);
+        assert_eq!(warnings.len(), 3);
+        assert!(warnings[0].starts_with("alg is none"));
+        assert_eq!(warnings[1], "expired 16 minutes ago");
+        assert_eq!(warnings[2], "not valid yet, becomes valid in 16 minutes");
+        assert!(decode("a.b").is_err());
+        // extreme but valid claims
+        let payload = serde_json::json!({"exp": i64::MIN, "nbf": i64::MAX, "iat": i64::MAX});
+        let token = decode(&sign(&payload, "HS256", "x", false).unwrap()).unwrap();
+        assert_eq!(super::warnings(&token, 2000).len(), 3);
+    }
+}
@@ -0,0 +1,195 @@
+use super::*;
+use eframe::egui;
+
+mod jwt;
+
+#[derive(PartialEq, Clone)]
+pub struct JwtTool {
+    token: String,
+    decoded: Result<jwt::Token, String>,
+    key: String,
+    secret_base64: bool,
+    algorithm: &'static str,
+    /// Claims to sign, as JSON
+    payload: String,
+    msg: Option<Msg>,
+}
+
+impl JwtTool {
+    pub fn new() -> Self {
+        Self {
+            token: String::new(),
+            decoded: Err(String::new()),
+            key: String::new(),
+            secret_base64: false,
+            algorithm: "HS256",
+            payload: String::new(),
+            msg: None,
+        }
+    }
+    fn sync(&mut self) {
+        self.decoded = jwt::decode(&self.token);
+    }
+    fn now() -> i64 {
+        chrono::Utc::now().timestamp()
+    }
+    fn verify(&mut self) {
+        let Ok(token) = &self.decoded else {
+            return;
+        };
+        self.msg = Some(match jwt::verify(token, &self.key, self.secret_base64) {
+            Ok(true) => Msg::new(format!("{} signature is valid", token.alg), MsgType::Info),
+            Ok(false) => Msg::new(
+                format!("{} signature does not match", token.alg),
+                MsgType::Error,
+            ),
+            Err(e) => Msg::new(format!("Error: {}", e), MsgType::Error),
+        });
+    }
+    fn sign(&mut self) {
+        let payload = match serde_json::from_str(&self.payload) {
+            Ok(v) => v,
+            Err(e) => {
+                self.msg = Some(Msg::new(
+                    format!("Error: payload is not JSON: {}", e),
+                    MsgType::Error,
+                ));
+                return;
+            }
+        };
+        match jwt::sign(&payload, self.algorithm, &self.key, self.secret_base64) {
+            Ok(token) => {
+                self.token = token;
+                self.sync();
+            }
+            Err(e) => self.msg = Some(Msg::new(format!("Error: {}", e), MsgType::Error)),
+        }
+    }
+}
+
+impl ToolComponent for JwtTool {
+    fn paint_ui(&mut self, ui: &mut egui::Ui, _ctx: &egui::Context) {
+        let width = ui.available_width();
+        ui.horizontal(|ui| {
+            let mut cursor = ui.cursor();
+            cursor.set_width(width / 2.0 - 5.0);
+            ui.allocate_new_ui(egui::UiBuilder::new().max_rect(cursor), |ui| {
+                ui.vertical(|ui| {
+                    ui.horizontal(|ui| {
+                        ui.label("Token");
+                        if ui.button("Clear").clicked() {
+                            self.token.clear();
+                            self.sync();
+                        }
+                        if ui.button("Copy").clicked() {
+                            ui.output_mut(|o| {
+                                o.copied_text = self.token.clone();
+                            });
+                        }
+                    });
+                    if ui
+                        .add(
+                            egui::TextEdit::multiline(&mut self.token)
+                                .desired_width(f32::INFINITY)
+                                .desired_rows(6)
+                                .font(egui::TextStyle::Monospace),
+                        )
+                        .changed()
+                    {
+                        self.sync();
+                    }
+                    ui.label("Key");
+                    ui.add(
+                        egui::TextEdit::multiline(&mut self.key)
+                            .desired_width(f32::INFINITY)
+                            .desired_rows(6)
+                            .font(egui::TextStyle::Monospace)
+                            .hint_text("HMAC secret, or a PEM / DER hex / JWK / OpenSSH key"),
+                    );
+                    ui.horizontal(|ui| {
+                        ui.checkbox(&mut self.secret_base64, "Secret is Base64");
+                        if ui
+                            .button("Verify")
+                            .on_hover_text("A private key also verifies, by its public half")
+                            .clicked()
+                        {
+                            self.verify();
+                        }
+                    });
+                    ui.separator();
+                    ui.horizontal(|ui| {
+                        ui.label("Payload to Sign");
+                        if ui
+                            .button("From Token")
+                            .on_hover_text("Start from the decoded payload")
+                            .clicked()
+                        {
+                            if let Ok(token) = &self.decoded {
+                                self.payload = serde_json::to_string_pretty(&token.payload)
+                                    .unwrap_or_default();
+                            }
+                        }
+                    });
+                    ui.add(
+                        egui::TextEdit::multiline(&mut self.payload)
+                            .desired_width(f32::INFINITY)
+                            .desired_rows(6)
+                            .code_editor()
+                            .hint_text("{\"sub\": \"1234567890\", \"exp\": 2000000000}"),
+                    );
+                    ui.horizontal(|ui| {
+                        egui::ComboBox::from_id_salt("tool/jwt_tool/alg")
+                            .selected_text(self.algorithm)
+                            .show_ui(ui, |ui| {
+                                for alg in jwt::ALGORITHMS {
+                                    ui.selectable_value(&mut self.algorithm, alg, alg);
+                                }
+                            });
+                        if ui
+                            .button("Sign")
+                            .on_hover_text("Replace Token with the signed payload")
+                            .clicked()
+                        {
+                            self.sign();
+                        }
+                    });
+                });
+            });
+            cursor = ui.cursor();
+            cursor.set_width(width / 2.0 - 5.0);
+            ui.allocate_new_ui(egui::UiBuilder::new().max_rect(cursor), |ui| {
+                ui.vertical(|ui| match &self.decoded {
+                    Ok(token) => {
+                        let now = Self::now();
+                        for warning in jwt::warnings(token, now) {
+                            ui.colored_label(ui.visuals().error_fg_color, warning);
+                        }
+                        ui.label("Header");
+                        egui_json_tree::JsonTree::new("tool/jwt_tool/header", &token.header)
+                            .show(ui);
+                        ui.label("Payload");
+                        egui_json_tree::JsonTree::new("tool/jwt_tool/payload", &token.payload)
+                            .show(ui);
+                        let times = jwt::times(&token.payload, now);
+                        if !times.is_empty() {
+                            ui.separator();
+                            egui::Grid::new("tool/jwt_tool/times").show(ui, |ui| {
+                                for (claim, time) in times {
+                                    ui.monospace(claim);
+                                    ui.label(time);
+                                    ui.end_row();
+                                }
+                            });
+                        }
+                    }
+                    Err(e) => {
+                        ui.label(e);
+                    }
+                });
+            });
+        });
+    }
+    fn get_msg(&mut self) -> Option<Msg> {
+        self.msg.take()
+    }
+}
@@ -4,10 +4,12 @@ mod batch_process;
 mod blank;
 mod bool_logic;
 mod byte_tool;
+mod cipher_tool;
 mod encode_tool;
 mod fake_data_gen;
 mod game2048;
 mod json_tool;
+mod jwt_tool;
 mod lua_tool;
 mod polyglot_png;
 mod randstr;
@@ -35,6 +37,8 @@ pub enum Tools {
     EncodeTool,
     ByteTool,
     JsonTool,
+    JwtTool,
+    CipherTool,
     TimeTool,
     LuaTool,
     TextUtil,
@@ -98,6 +102,8 @@ pub fn get_component(component: Tools) -> Box<dyn ToolComponent> {
         Tools::EncodeTool => Box::new(encode_tool::Encoder::new()),
         Tools::ByteTool => Box::new(byte_tool::ByteTool::new()),
         Tools::JsonTool => Box::new(json_tool::JsonTool::new()),
+        Tools::JwtTool => Box::new(jwt_tool::JwtTool::new()),
+        Tools::CipherTool => Box::new(cipher_tool::CipherTool::new()),
         Tools::TimeTool => Box::new(time_tool::TimeTool::new()),
         Tools::LuaTool => match lua_tool::LuaTool::new() {
             Ok(t) => Box::new(t),
@@ -126,6 +132,8 @@ pub fn get_component_namelist() -> Vec<(String, Vec<(String, Tools)>)> {
     string_list.push(("Fake Data".to_string(), Tools::FakeDataGen));
     string_list.push(("Encode Tool".to_string(), Tools::EncodeTool));
     string_list.push(("JSON Tool".to_string(), Tools::JsonTool));
+    string_list.push(("JWT Tool".to_string(), Tools::JwtTool));
+    string_list.push(("Classical Cipher".to_string(), Tools::CipherTool));
     string_list.push(("Text Util".to_string(), Tools::TextUtil));
     list.push(("String".to_string(), string_list));
 
@@ -1,5 +1,7 @@
 mod polyglot_png_algo;
 
+pub use polyglot_png_algo::png_chunks;
+
 use super::*;
 use eframe::egui;
 
@@ -1,119 +1,154 @@
-use std::io::Read;
+use crate::{find_index_from, rfind_index};
 const PNG_MAGIC: &[u8; 8] = b"\x89PNG\r\n\x1a\n";
 
 pub fn polyglot(png: &[u8], content: &[u8], fix_zip: bool) -> Result<Vec<u8>, String> {
-    // Create reader for PNG data
-    let mut png_in = std::io::Cursor::new(png);
-
-    // Create writer for output
-    let mut out = Vec::new();
-
-    // Check the PNG magic is present in the input file
-    let mut png_header = [0; PNG_MAGIC.len()];
-    png_in
-        .read_exact(&mut png_header)
-        .map_err(|e| format!("Failed to read PNG header: {}", e))?;
-
-    if png_header != *PNG_MAGIC {
-        return Err("Input file is not a valid PNG (incorrect magic bytes)".to_string());
-    }
+    let chunks = png_chunks(png)?;
 
     // Write PNG header to output
-    out.extend_from_slice(&png_header);
+    let mut out = PNG_MAGIC.to_vec();
 
     let mut idat_body = Vec::new();
 
     let mut width = 0;
     let mut height = 0;
 
-    // iterate through the chunks of the PNG file
-    loop {
-        // parse a chunk
-        let mut chunk_len_bytes = [0; 4];
-        png_in
-            .read_exact(&mut chunk_len_bytes)
-            .map_err(|e| format!("Failed to read chunk length: {}", e))?;
-        let chunk_len = u32::from_be_bytes(chunk_len_bytes) as usize;
-        let mut chunk_type = [0; 4];
-        png_in
-            .read_exact(&mut chunk_type)
-            .map_err(|e| format!("Failed to read chunk type: {}", e))?;
-        let mut chunk_body = vec![0; chunk_len];
-        png_in
-            .read_exact(&mut chunk_body)
-            .map_err(|e| format!("Failed to read chunk body: {}", e))?;
-        let mut chunk_csum_bytes = [0; 4];
-        png_in
-            .read_exact(&mut chunk_csum_bytes)
-            .map_err(|e| format!("Failed to read chunk checksum: {}", e))?;
-
-        // if it's a non-essential chunk, skip over it
-        let essential_chunk_type_bytes = [b"IHDR", b"PLTE", b"IDAT", b"IEND"];
-        let is_essential = essential_chunk_type_bytes.iter().any(|&x| *x == chunk_type);
-        if !is_essential {
-            continue;
-        }
-
-        // take note of the image width and height, for future calculations
-        if &chunk_type == b"IHDR" {
-            width = u32::from_be_bytes([chunk_body[0], chunk_body[1], chunk_body[2], chunk_body[3]])
-                as usize;
-            height =
-                u32::from_be_bytes([chunk_body[4], chunk_body[5], chunk_body[6], chunk_body[7]])
-                    as usize;
-        }
-
-        // There might be multiple IDAT chunks, we will concatenate their contents
-        // and write them into a single chunk later
-        if &chunk_type == b"IDAT" {
-            idat_body.extend_from_slice(&chunk_body);
-            continue;
+    // iterate through the chunks of the PNG file, up to IEND
+    for chunk in &chunks {
+        let chunk_body = &png[chunk.offset + 8..chunk.end() - 4];
+        match &chunk.chunk_type {
+            // take note of the image width and height, for future calculations
+            b"IHDR" => {
+                if chunk_body.len() < 8 {
+                    return Err("IHDR chunk is too short".to_string());
+                }
+                width = u32::from_be_bytes([
+                    chunk_body[0],
+                    chunk_body[1],
+                    chunk_body[2],
+                    chunk_body[3],
+                ]) as usize;
+                height = u32::from_be_bytes([
+                    chunk_body[4],
+                    chunk_body[5],
+                    chunk_body[6],
+                    chunk_body[7],
+                ]) as usize;
+            }
+            b"PLTE" => {}
+            // There might be multiple IDAT chunks, we will concatenate their contents
+            // and write them into a single chunk later
+            b"IDAT" => {
+                idat_body.extend_from_slice(chunk_body);
+                continue;
+            }
+            // the IEND chunk should be at the end, now is the time to write our IDAT
+            // chunk, before we actually write the IEND chunk
+            b"IEND" => {
+                let start_offset = out.len() + 8 + idat_body.len();
+
+                // concatenate our content that we want to embed
+                idat_body.extend_from_slice(content);
+
+                if idat_body.len() > width * height {
+                    return Err(format!(
+                        "Content too large to embed: {} bytes exceeds capacity of {} bytes ({}x{})",
+                        idat_body.len(),
+                        width * height,
+                        width,
+                        height
+                    ));
+                }
+
+                // if its a zip file, fix the offsets
+                if fix_zip {
+                    idat_body = fixup_zip(&idat_body, start_offset)?;
+                }
+
+                // write the IDAT chunk
+                out.extend_from_slice(&(idat_body.len() as u32).to_be_bytes());
+                out.extend_from_slice(b"IDAT");
+                out.extend_from_slice(&idat_body);
+                let data_2_crc = [&b"IDAT"[..], &idat_body[..]].concat();
+                let mut crc32_calc = flate2::Crc::new();
+                crc32_calc.update(&data_2_crc);
+                let crc = crc32_calc.sum();
+                out.extend_from_slice(&crc.to_be_bytes());
+            }
+            // if it's a non-essential chunk, skip over it
+            _ => continue,
         }
+        // if we reached here, we're writing the IHDR, PLTE or IEND chunk
+        out.extend_from_slice(&png[chunk.offset..chunk.end()]);
+    }
+    Ok(out)
+}
 
-        // the IEND chunk should be at the end, now is the time to write our IDAT
-        // chunk, before we actually write the IEND chunk
-        if &chunk_type == b"IEND" {
-            let start_offset = out.len() + 8 + idat_body.len();
-
-            // concatenate our content that we want to embed
-            idat_body.extend_from_slice(content);
-
-            if idat_body.len() > width * height {
-                return Err(format!(
-                    "Content too large to embed: {} bytes exceeds capacity of {} bytes ({}x{})",
-                    idat_body.len(),
-                    width * height,
-                    width,
-                    height
-                ));
-            }
+#[derive(Clone, Debug)]
+pub struct PngChunk {
+    /// Offset of the length field of the chunk
+    pub offset: usize,
+    pub length: usize,
+    pub chunk_type: [u8; 4],
+    pub crc: u32,
+}
 
-            // if its a zip file, fix the offsets
-            if fix_zip {
-                idat_body = fixup_zip(&idat_body, start_offset)?;
-            }
+impl PngChunk {
+    /// Offset just past the CRC, i.e. where the next chunk starts
+    pub fn end(&self) -> usize {
+        self.offset + 12 + self.length
+    }
+}
 
-            // write the IDAT chunk
-            out.extend_from_slice(&(idat_body.len() as u32).to_be_bytes());
-            out.extend_from_slice(b"IDAT");
-            out.extend_from_slice(&idat_body);
-            let data_2_crc = [&b"IDAT"[..], &idat_body[..]].concat();
-            let mut crc32_calc = flate2::Crc::new();
-            crc32_calc.update(&data_2_crc);
-            let crc = crc32_calc.sum();
-            out.extend_from_slice(&crc.to_be_bytes());
+/// Walks the chunks of a PNG stream up to and including IEND.
+/// Bytes after IEND are ignored, so `png` may be a larger buffer that merely starts with a PNG.
+pub fn png_chunks(png: &[u8]) -> Result<Vec<PngChunk>, String> {
+    if !png.starts_with(PNG_MAGIC) {
+        return Err("Input is not a valid PNG (incorrect magic bytes)".to_string());
+    }
+    let mut chunks = Vec::new();
+    let mut offset = PNG_MAGIC.len();
+    loop {
+        if offset + 8 > png.len() {
+            return Err(format!("Truncated chunk header at offset {}", offset));
         }
-        // if we reached here, we're writing the IHDR, PLTE or IEND chunk
-        out.extend_from_slice(&chunk_len_bytes);
-        out.extend_from_slice(&chunk_type);
-        out.extend_from_slice(&chunk_body);
-        out.extend_from_slice(&chunk_csum_bytes);
+        let length = u32::from_be_bytes([
+            png[offset],
+            png[offset + 1],
+            png[offset + 2],
+            png[offset + 3],
+        ]) as usize;
+        let chunk_type = [
+            png[offset + 4],
+            png[offset + 5],
+            png[offset + 6],
+            png[offset + 7],
+        ];
+        let crc_offset = offset + 8 + length;
+        if crc_offset + 4 > png.len() {
+            return Err(format!(
+                "Truncated chunk {} at offset {}",
+                String::from_utf8_lossy(&chunk_type),
+                offset
+            ));
+        }
+        let crc = u32::from_be_bytes([
+            png[crc_offset],
+            png[crc_offset + 1],
+            png[crc_offset + 2],
+            png[crc_offset + 3],
+        ]);
+        let chunk = PngChunk {
+            offset,
+            length,
+            chunk_type,
+            crc,
+        };
+        offset = chunk.end();
+        chunks.push(chunk);
         if &chunk_type == b"IEND" {
-            // done
-            break;
+            return Ok(chunks);
         }
     }
-    Ok(out)
 }
 
 fn fixup_zip(data: &[u8], offset: usize) -> Result<Vec<u8>, String> {
@@ -189,89 +224,3 @@ fn fixup_zip(data: &[u8], offset: usize) -> Result<Vec<u8>, String> {
     }
     Ok(data_copy)
 }
-
-/// Finds the first occurrence of a byte pattern in a slice, starting from a given index
-///
-/// This function uses a sliding window approach to find the pattern, which is more efficient
-/// than checking every possible position with starts_with.
-///
-/// # Arguments
-/// * `data` - The data to search in
-/// * `pattern` - The pattern to search for
-/// * `from_index` - The index to start searching from
-///
-/// # Returns
-/// * `Option<usize>` - The index of the first occurrence, or None if not found
-fn find_index_from(data: &[u8], pattern: &[u8], from_index: usize) -> Option<usize> {
-    // Handle edge cases
-    if pattern.is_empty() {
-        return Some(from_index);
-    }
-
-    if pattern.len() > data.len() || from_index > data.len() - pattern.len() {
-        return None;
-    }
-
-    // Use memchr for single-byte patterns for better performance
-    if pattern.len() == 1 {
-        return memchr::memchr(pattern[0], &data[from_index..]).map(|idx| idx + from_index);
-    }
-
-    // For multi-byte patterns, use a more efficient search algorithm
-    // that doesn't check every position with starts_with
-    let end = data.len() - pattern.len() + 1;
-    let mut i = from_index;
-
-    while i < end {
-        if data[i] == pattern[0] && data[i..i + pattern.len()] == pattern[..] {
-            return Some(i);
-        }
-        i += 1;
-    }
-
-    None
-}
-
-/// Finds the last occurrence of a byte pattern in a slice
-///
-/// This function searches from the end of the data toward the beginning,
-/// returning the position of the last occurrence of the pattern.
-///
-/// # Arguments
-/// * `data` - The data to search in
-/// * `pattern` - The pattern to search for
-///
-/// # Returns
-/// * `Option<usize>` - The index of the last occurrence, or None if not found
-fn rfind_index(data: &[u8], pattern: &[u8]) -> Option<usize> {
-    // Handle edge cases
-    if pattern.is_empty() {
-        return Some(data.len());
-    }
-
-    if pattern.len() > data.len() {
-        return None;
-    }
-
-    // Use memrchr for single-byte patterns for better performance
-    if pattern.len() == 1 {
-        return memchr::memrchr(pattern[0], data).filter(|&idx| data[idx..].starts_with(pattern));
-    }
-
-    // For multi-byte patterns, search from the end
-    let mut i = data.len() - pattern.len();
-
-    loop {
-        if data[i] == pattern[0] && data[i..i + pattern.len()] == pattern[..] {
-            return Some(i);
-        }
-
-        if i == 0 {
-            break;
-        }
-
-        i -= 1;
-    }
-
-    None
-}
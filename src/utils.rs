@@ -218,3 +218,93 @@ pub fn format_mem(mem: usize) -> String {
         format!("{:.2}GB", mem as f64 / 1024.0 / 1024.0 / 1024.0)
     }
 }
+
+//
+// Byte Search
+//
+
+/// Finds the first occurrence of a byte pattern in a slice, starting from a given index
+///
+/// This function uses a sliding window approach to find the pattern, which is more efficient
+/// than checking every possible position with starts_with.
+///
+/// # Arguments
+/// * `data` - The data to search in
+/// * `pattern` - The pattern to search for
+/// * `from_index` - The index to start searching from
+///
+/// # Returns
+/// * `Option<usize>` - The index of the first occurrence, or None if not found
+pub fn find_index_from(data: &[u8], pattern: &[u8], from_index: usize) -> Option<usize> {
+    // Handle edge cases
+    if pattern.is_empty() {
+        return Some(from_index);
+    }
+
+    if pattern.len() > data.len() || from_index > data.len() - pattern.len() {
+        return None;
+    }
+
+    // Use memchr for single-byte patterns for better performance
+    if pattern.len() == 1 {
+        return memchr::memchr(pattern[0], &data[from_index..]).map(|idx| idx + from_index);
+    }
+
+    // For multi-byte patterns, use a more efficient search algorithm
+    // that doesn't check every position with starts_with
+    let end = data.len() - pattern.len() + 1;
+    let mut i = from_index;
+
+    while i < end {
+        if data[i] == pattern[0] && data[i..i + pattern.len()] == pattern[..] {
+            return Some(i);
+        }
+        i += 1;
+    }
+
+    None
+}
+
+/// Finds the last occurrence of a byte pattern in a slice
+///
+/// This function searches from the end of the data toward the beginning,
+/// returning the position of the last occurrence of the pattern.
+///
+/// # Arguments
+/// * `data` - The data to search in
+/// * `pattern` - The pattern to search for
+///
+/// # Returns
+/// * `Option<usize>` - The index of the last occurrence, or None if not found
+pub fn rfind_index(data: &[u8], pattern: &[u8]) -> Option<usize> {
+    // Handle edge cases
+    if pattern.is_empty() {
+        return Some(data.len());
+    }
+
+    if pattern.len() > data.len() {
+        return None;
+    }
+
+    // Use memrchr for single-byte patterns for better performance
+    if pattern.len() == 1 {
+        return memchr::memrchr(pattern[0], data).filter(|&idx| data[idx..].starts_with(pattern));
+    }
+
+    // For multi-byte patterns, search from the end
+    let mut i = data.len() - pattern.len();
+
+    loop {
+        if data[i] == pattern[0] && data[i..i + pattern.len()] == pattern[..] {
+            return Some(i);
+        }
+
+        if i == 0 {
+            break;
+        }
+
+        i -= 1;
+    }
+
+    None
+}